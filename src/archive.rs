@@ -0,0 +1,310 @@
+use super::timezone::Zone;
+use custom_error::custom_error;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::result;
+
+custom_error! {pub ArchiveError
+    IO{source: std::io::Error} = "Unable to write the archive",
+}
+pub type Result<T> = result::Result<T, ArchiveError>;
+
+/// Markup the archive's pages are written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageFormat {
+    Markdown,
+    Html,
+}
+
+impl PageFormat {
+    pub fn parse(format: &str) -> Option<PageFormat> {
+        match format {
+            "markdown" => Some(PageFormat::Markdown),
+            "html" => Some(PageFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PageFormat::Markdown => "md",
+            PageFormat::Html => "html",
+        }
+    }
+}
+
+/// How items are split into pages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Subreddit,
+    Month,
+}
+
+impl GroupBy {
+    pub fn parse(group_by: &str) -> Option<GroupBy> {
+        match group_by {
+            "subreddit" => Some(GroupBy::Subreddit),
+            "month" => Some(GroupBy::Month),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of a raw comment or submission that end up on a page.
+struct Entry {
+    heading: String,
+    kind: &'static str,
+    subreddit: String,
+    created_utc: f64,
+    score: i64,
+    text: String,
+    link: String,
+}
+
+impl Entry {
+    fn new(item: &Map<String, Value>) -> Entry {
+        let text = |key: &str| {
+            item.get(key)
+                .and_then(Value::as_str)
+                .map(String::from)
+                .unwrap_or_default()
+        };
+        let is_comment = text("name").starts_with("t1_");
+        let heading = if is_comment {
+            format!("Comment on \"{}\"", text("link_title"))
+        } else {
+            text("title")
+        };
+        let body = if is_comment {
+            text("body")
+        } else if item.get("is_self").and_then(Value::as_bool) == Some(false) {
+            text("url")
+        } else {
+            text("selftext")
+        };
+        Entry {
+            heading,
+            kind: if is_comment { "comment" } else { "submission" },
+            subreddit: text("subreddit"),
+            created_utc: item
+                .get("created_utc")
+                .and_then(Value::as_f64)
+                .unwrap_or(0.0),
+            score: item.get("score").and_then(Value::as_i64).unwrap_or(0),
+            text: body,
+            link: format!("https://www.reddit.com{}", text("permalink")),
+        }
+    }
+
+    fn meta(&self, zone: &Zone) -> String {
+        format!(
+            "{} in /r/{}, {}, score {}",
+            self.kind,
+            self.subreddit,
+            zone.format_timestamp(self.created_utc),
+            self.score
+        )
+    }
+}
+
+/// Writes `history` to `dir` as one page per subreddit or month, newest items first, with
+/// an index page linking them. Returns how many pages were written, not counting the index.
+pub fn write_archive(
+    dir: &Path,
+    history: &[Map<String, Value>],
+    format: PageFormat,
+    group_by: GroupBy,
+    zone: &Zone,
+) -> Result<usize> {
+    let mut pages: BTreeMap<String, Vec<Entry>> = BTreeMap::new();
+    for entry in history.iter().map(Entry::new) {
+        let key = match group_by {
+            GroupBy::Subreddit => format!("/r/{}", entry.subreddit),
+            GroupBy::Month => zone.format_month(entry.created_utc),
+        };
+        pages.entry(key).or_insert_with(Vec::new).push(entry);
+    }
+    fs::create_dir_all(dir)?;
+    let mut index: Vec<(String, String, usize)> = Vec::new();
+    for (title, entries) in pages.iter_mut() {
+        entries.sort_by(|a, b| {
+            b.created_utc
+                .partial_cmp(&a.created_utc)
+                .unwrap_or(Ordering::Equal)
+        });
+        let file = format!("{}.{}", file_stem(title), format.extension());
+        let page = match format {
+            PageFormat::Markdown => markdown_page(title, entries, zone),
+            PageFormat::Html => html_page(title, entries, zone),
+        };
+        fs::write(dir.join(&file), page)?;
+        index.push((String::from(title), file, entries.len()));
+    }
+    let index_page = match format {
+        PageFormat::Markdown => markdown_index(&index),
+        PageFormat::Html => html_index(&index),
+    };
+    fs::write(
+        dir.join(format!("index.{}", format.extension())),
+        index_page,
+    )?;
+    Ok(index.len())
+}
+
+/// Subreddit names and months are already safe file names, this is just in case.
+fn file_stem(title: &str) -> String {
+    title
+        .trim_start_matches("/r/")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn markdown_page(title: &str, entries: &[Entry], zone: &Zone) -> String {
+    let mut page = format!("# {}\n", title);
+    for entry in entries {
+        page.push_str(&format!(
+            "\n## [{}]({})\n\n*{}*\n\n{}\n\n---\n",
+            entry.heading,
+            entry.link,
+            entry.meta(zone),
+            entry.text
+        ));
+    }
+    page
+}
+
+fn markdown_index(pages: &[(String, String, usize)]) -> String {
+    let mut index = String::from("# redelete archive\n\n");
+    for (title, file, count) in pages {
+        index.push_str(&format!("- [{}]({}) ({})\n", title, file, count));
+    }
+    index
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n<h1>{}</h1>\n{}</body>\n</html>\n",
+        escape_html(title),
+        escape_html(title),
+        body
+    )
+}
+
+fn html_page(title: &str, entries: &[Entry], zone: &Zone) -> String {
+    let mut body = String::from("<p><a href=\"index.html\">Index</a></p>\n");
+    for entry in entries {
+        body.push_str(&format!(
+            "<article>\n<h2><a href=\"{}\">{}</a></h2>\n<p><em>{}</em></p>\n<div style=\"white-space: pre-wrap\">{}</div>\n</article>\n<hr>\n",
+            escape_html(&entry.link),
+            escape_html(&entry.heading),
+            escape_html(&entry.meta(zone)),
+            escape_html(&entry.text)
+        ));
+    }
+    html_document(title, &body)
+}
+
+fn html_index(pages: &[(String, String, usize)]) -> String {
+    let mut body = String::from("<ul>\n");
+    for (title, file, count) in pages {
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}</a> ({})</li>\n",
+            escape_html(file),
+            escape_html(title),
+            count
+        ));
+    }
+    body.push_str("</ul>\n");
+    html_document("redelete archive", &body)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> Vec<Map<String, Value>> {
+        vec![
+            serde_json::from_str(
+                r#"{"name": "t1_a", "subreddit": "rust", "created_utc": 1579506505.0, "score": 2,
+                    "body": "I <3 rust", "link_title": "Why rust?", "permalink": "/r/rust/comments/x/y/a/"}"#,
+            )
+            .unwrap(),
+            serde_json::from_str(
+                r#"{"name": "t3_b", "subreddit": "pics", "created_utc": 1582000000.0, "score": 9,
+                    "title": "My cat", "is_self": false, "url": "https://i.redd.it/cat.jpg", "permalink": "/r/pics/comments/b/my_cat/"}"#,
+            )
+            .unwrap(),
+        ]
+    }
+
+    fn archive_dir(name: &str) -> std::path::PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(name);
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_markdown_archive_by_subreddit() {
+        let dir = archive_dir("redelete-test-archive-md");
+        let zone = Zone::from_config(&Some("UTC".into())).unwrap();
+        let pages = write_archive(
+            &dir,
+            &history(),
+            PageFormat::Markdown,
+            GroupBy::Subreddit,
+            &zone,
+        )
+        .unwrap();
+        assert_eq!(pages, 2);
+        let rust = fs::read_to_string(dir.join("rust.md")).unwrap();
+        assert_eq!(rust.starts_with("# /r/rust\n"), true);
+        assert_eq!(
+            rust.contains(
+                "## [Comment on \"Why rust?\"](https://www.reddit.com/r/rust/comments/x/y/a/)"
+            ),
+            true
+        );
+        assert_eq!(
+            rust.contains("*comment in /r/rust, 2020-01-20 07:48 UTC, score 2*"),
+            true
+        );
+        let pics = fs::read_to_string(dir.join("pics.md")).unwrap();
+        assert_eq!(pics.contains("https://i.redd.it/cat.jpg"), true);
+        let index = fs::read_to_string(dir.join("index.md")).unwrap();
+        assert_eq!(index.contains("- [/r/rust](rust.md) (1)"), true);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_html_archive_by_month() {
+        let dir = archive_dir("redelete-test-archive-html");
+        let zone = Zone::from_config(&Some("UTC".into())).unwrap();
+        let pages =
+            write_archive(&dir, &history(), PageFormat::Html, GroupBy::Month, &zone).unwrap();
+        assert_eq!(pages, 2);
+        let january = fs::read_to_string(dir.join("2020-01.html")).unwrap();
+        assert_eq!(january.contains("I &lt;3 rust"), true);
+        assert_eq!(dir.join("2020-02.html").exists(), true);
+        assert_eq!(dir.join("index.html").exists(), true);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+use super::config::config_dir;
+use super::reddit_api::DeletionInfo;
+use super::shred::can_shred;
+use custom_error::custom_error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+use serial_test::serial;
+
+const GENESIS_HASH: &'static str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+/// Characters of a comment/submission kept in the log, enough to recognise it.
+const CONTENT_LIMIT: usize = 200;
+
+custom_error! {pub AuditError
+    Serde{source: serde_json::Error} = "Serde parsing error",
+    IO{source: std::io::Error} = "IO Error",
+    Tampered{line: usize, reason: String} = "Audit log entry {line} failed verification: {reason}"
+}
+pub type Result<T> = result::Result<T, AuditError>;
+
+/// One line of the audit log. `hash` covers every other field, including `prev_hash`,
+/// so editing or removing an entry breaks the chain for everything after it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub name: String,
+    pub subreddit: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    /// The start of the comment body, or of the submission title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// The item only matched a dry run and wasn't deleted.
+    #[serde(default)]
+    pub dry_run: bool,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// An item to delete and what the audit log should say about it. Score and content
+/// aren't known for deletions retried from the failure journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub subreddit: String,
+    pub score: Option<i32>,
+    pub content: Option<String>,
+    pub created_utc: Option<f64>,
+    /// Whether the text can be overwritten before deleting, see `shred::can_shred`.
+    pub editable: bool,
+}
+
+impl Item {
+    pub fn new(name: String, subreddit: String) -> Item {
+        Item {
+            name,
+            subreddit,
+            score: None,
+            content: None,
+            created_utc: None,
+            editable: false,
+        }
+    }
+}
+
+impl From<DeletionInfo> for Item {
+    fn from(info: DeletionInfo) -> Item {
+        let editable = can_shred(&info);
+        let content = info
+            .body
+            .or(info.title)
+            .map(|text| text.chars().take(CONTENT_LIMIT).collect());
+        Item {
+            name: info.name,
+            subreddit: info.subreddit,
+            score: Some(info.score),
+            content,
+            created_utc: Some(info.created_utc),
+            editable,
+        }
+    }
+}
+
+fn audit_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("audit");
+    std::fs::create_dir_all(&path).expect("Unable to create audit log directory.");
+    path
+}
+
+pub fn audit_file_path(username: &str) -> PathBuf {
+    let mut path = audit_dir();
+    path.push(format!("{}.jsonl", username));
+    path
+}
+
+/// The hash of the newest entry is kept next to the log so that truncating
+/// the tail of the log is detected as well.
+fn head_file_path(username: &str) -> PathBuf {
+    let mut path = audit_dir();
+    path.push(format!("{}.head", username));
+    path
+}
+
+/// Hashes the entry as a json object with sorted keys and without its own `hash`,
+/// so entries written by older versions still verify after fields are added.
+fn entry_hash(entry: &Value) -> String {
+    let mut unhashed = entry.clone();
+    if let Some(map) = unhashed.as_object_mut() {
+        map.remove("hash");
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(unhashed.to_string().as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn read_head(username: &str) -> Result<Option<String>> {
+    let path = head_file_path(username);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(Some(String::from(contents.trim())))
+}
+
+/// Appends one entry per item, marked as a dry run when nothing was actually deleted.
+pub fn record_deletions(username: &str, deleted: &Vec<Item>, dry_run: bool) -> Result<()> {
+    if deleted.is_empty() {
+        return Ok(());
+    }
+    let mut prev_hash = read_head(username)?.unwrap_or(String::from(GENESIS_HASH));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_file_path(username))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Couldn't get systemtime")
+        .as_secs();
+    for item in deleted {
+        let mut entry = serde_json::to_value(AuditEntry {
+            timestamp,
+            name: String::from(&item.name),
+            subreddit: String::from(&item.subreddit),
+            score: item.score,
+            content: item.content.clone(),
+            dry_run,
+            prev_hash: prev_hash.clone(),
+            hash: String::new(),
+        })?;
+        let hash = entry_hash(&entry);
+        entry["hash"] = Value::String(hash.clone());
+        writeln!(file, "{}", entry.to_string())?;
+        prev_hash = hash;
+    }
+    File::create(head_file_path(username))?.write_all(prev_hash.as_bytes())?;
+    Ok(())
+}
+
+/// Walks the whole chain and returns the number of entries if it is intact.
+pub fn verify_log(username: &str) -> Result<usize> {
+    let path = audit_file_path(username);
+    let head = read_head(username)?;
+    if !path.exists() {
+        return match head {
+            None => Ok(0),
+            Some(_) => Err(AuditError::Tampered {
+                line: 1,
+                reason: String::from("log file is missing"),
+            }),
+        };
+    }
+    let mut prev_hash = String::from(GENESIS_HASH);
+    let mut count = 0;
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Value = serde_json::from_str(&line)?;
+        if entry["prev_hash"].as_str() != Some(&*prev_hash) {
+            return Err(AuditError::Tampered {
+                line: i + 1,
+                reason: String::from(
+                    "previous hash does not match, an entry was removed or reordered",
+                ),
+            });
+        }
+        let hash = entry_hash(&entry);
+        if entry["hash"].as_str() != Some(&*hash) {
+            return Err(AuditError::Tampered {
+                line: i + 1,
+                reason: String::from("entry contents were modified"),
+            });
+        }
+        prev_hash = hash;
+        count += 1;
+    }
+    if head.unwrap_or(String::from(GENESIS_HASH)) != prev_hash {
+        return Err(AuditError::Tampered {
+            line: count + 1,
+            reason: String::from("log ends before the last recorded entry, it was truncated"),
+        });
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TEST_USER: &'static str = "AuditTestUser";
+
+    fn cleanup() {
+        let _ = std::fs::remove_file(audit_file_path(TEST_USER));
+        let _ = std::fs::remove_file(head_file_path(TEST_USER));
+    }
+
+    fn deleted() -> Vec<Item> {
+        vec![
+            Item::new("t1_a".into(), "rust".into()),
+            Item::new("t3_b".into(), "webdev".into()),
+            Item::new("t1_c".into(), "rust".into()),
+        ]
+    }
+
+    #[test]
+    #[serial]
+    fn test_verify_intact_log() {
+        cleanup();
+        assert_eq!(verify_log(TEST_USER).unwrap(), 0);
+        record_deletions(TEST_USER, &deleted(), false).unwrap();
+        record_deletions(TEST_USER, &deleted(), false).unwrap();
+        assert_eq!(verify_log(TEST_USER).unwrap(), 6);
+        cleanup();
+    }
+
+    #[test]
+    #[serial]
+    fn test_entries_keep_details() {
+        cleanup();
+        let item = Item {
+            name: "t1_d".into(),
+            subreddit: "rust".into(),
+            score: Some(-3),
+            content: Some("x".repeat(CONTENT_LIMIT)),
+            created_utc: None,
+            editable: true,
+        };
+        record_deletions(TEST_USER, &vec![item.clone()], true).unwrap();
+        record_deletions(TEST_USER, &deleted(), false).unwrap();
+        let lines: Vec<AuditEntry> =
+            BufReader::new(File::open(audit_file_path(TEST_USER)).unwrap())
+                .lines()
+                .map(|l| serde_json::from_str(&l.unwrap()).unwrap())
+                .collect();
+        assert_eq!(lines[0].score, Some(-3));
+        assert_eq!(lines[0].content, item.content);
+        assert_eq!(lines[0].dry_run, true);
+        assert_eq!(lines[1].score, None);
+        assert_eq!(lines[1].dry_run, false);
+        assert_eq!(verify_log(TEST_USER).unwrap(), 4);
+        cleanup();
+    }
+
+    #[test]
+    #[serial]
+    fn test_verify_detects_tampering() {
+        cleanup();
+        record_deletions(TEST_USER, &deleted(), false).unwrap();
+        let mut contents = String::new();
+        File::open(audit_file_path(TEST_USER))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        let edited = contents.replace("webdev", "rust");
+        File::create(audit_file_path(TEST_USER))
+            .unwrap()
+            .write_all(edited.as_bytes())
+            .unwrap();
+        assert_eq!(verify_log(TEST_USER).is_err(), true);
+
+        let truncated: Vec<&str> = contents.lines().take(2).collect();
+        File::create(audit_file_path(TEST_USER))
+            .unwrap()
+            .write_all(format!("{}\n", truncated.join("\n")).as_bytes())
+            .unwrap();
+        assert_eq!(verify_log(TEST_USER).is_err(), true);
+        cleanup();
+    }
+}
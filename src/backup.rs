@@ -0,0 +1,123 @@
+use super::config::{config_dir, BackupRecipient};
+use super::reddit_api::DeletionInfo;
+use custom_error::custom_error;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+custom_error! {pub BackupError
+    Serde{source: serde_json::Error} = "Serde parsing error",
+    IO{source: std::io::Error} = "IO Error",
+    Encryption{program: String, status: String} = "{program} failed to encrypt the backup ({status})"
+}
+pub type Result<T> = result::Result<T, BackupError>;
+
+pub fn backup_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("backups");
+    std::fs::create_dir_all(&path).expect("Unable to create backup directory.");
+    path
+}
+
+fn backup_file_path(username: &str, recipient: Option<&BackupRecipient>) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Couldn't get systemtime")
+        .as_secs();
+    let extension = match recipient {
+        Some(BackupRecipient::Age(_)) => "json.age",
+        Some(BackupRecipient::Gpg(_)) => "json.gpg",
+        None => "json",
+    };
+    let mut path = backup_dir();
+    path.push(format!("{}-{}.{}", username, timestamp, extension));
+    path
+}
+
+/// Pipes the plaintext straight into age/gpg so it never touches the disk unencrypted.
+fn encrypt_to(recipient: &BackupRecipient, plaintext: &[u8], output: &PathBuf) -> Result<()> {
+    let mut command = match recipient {
+        BackupRecipient::Age(key) => {
+            let mut c = Command::new("age");
+            c.arg("--encrypt").arg("--recipient").arg(key);
+            c.arg("--output").arg(output);
+            c
+        }
+        BackupRecipient::Gpg(key) => {
+            let mut c = Command::new("gpg");
+            c.args(&["--batch", "--yes", "--encrypt", "--recipient"])
+                .arg(key);
+            c.arg("--output").arg(output);
+            c
+        }
+    };
+    let program = format!("{:?}", command);
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("Unable to open encryption program stdin.")
+        .write_all(plaintext)?;
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        if output.exists() {
+            std::fs::remove_file(output)?;
+        }
+        Err(BackupError::Encryption {
+            program,
+            status: status.to_string(),
+        })
+    }
+}
+
+/// Writes the items about to be deleted to the backup directory and returns the file written.
+pub fn write_backup(
+    username: &str,
+    items: &Vec<DeletionInfo>,
+    recipient: Option<&BackupRecipient>,
+) -> Result<PathBuf> {
+    let path = backup_file_path(username, recipient);
+    let json = serde_json::to_vec(items)?;
+    match recipient {
+        Some(r) => encrypt_to(r, &json, &path)?,
+        None => File::create(&path)?.write_all(&json)?,
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_file_extension() {
+        let age = BackupRecipient::Age("age1qqq".into());
+        let gpg = BackupRecipient::Gpg("ABCDEF".into());
+        assert_eq!(
+            backup_file_path("TestUser", Some(&age))
+                .to_str()
+                .unwrap()
+                .ends_with(".json.age"),
+            true
+        );
+        assert_eq!(
+            backup_file_path("TestUser", Some(&gpg))
+                .to_str()
+                .unwrap()
+                .ends_with(".json.gpg"),
+            true
+        );
+        assert_eq!(
+            backup_file_path("TestUser", None)
+                .to_str()
+                .unwrap()
+                .ends_with(".json"),
+            true
+        );
+    }
+}
@@ -1,17 +1,20 @@
+use super::patterns;
 use super::reddit_api::OAuthToken;
+use super::rules;
 use custom_error::custom_error;
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::result;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(test)]
 use serial_test::serial;
 
-fn config_dir() -> PathBuf {
+pub fn config_dir() -> PathBuf {
     let dirs = ProjectDirs::from("", "", "redelete").expect("Cannot create config folder.");
     let path: PathBuf = dirs.config_dir().into();
     std::fs::create_dir_all(&path).expect("Unable to create config directory.");
@@ -26,6 +29,25 @@ fn config_file_path() -> PathBuf {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     accounts: Vec<AccountInfo>,
+    /// Used instead of redelete's own reddit app when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    app: Option<RedditApp>,
+    /// Where `redelete authorize` waits for reddit's redirect, as HOST or HOST:PORT.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    listen: Option<String>,
+}
+
+/// A reddit app the user registered at https://www.reddit.com/prefs/apps, so they don't
+/// share rate limits with everyone else using redelete's.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RedditApp {
+    pub client_id: String,
+    /// Installed apps don't have a secret.
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// Has to match the redirect uri the app was registered with.
+    #[serde(default)]
+    pub redirect_uri: Option<String>,
 }
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct AccountInfo {
@@ -34,7 +56,218 @@ pub struct AccountInfo {
     pub token_expires: u64,
     pub excluded_subreddits: Option<Vec<String>>,
     pub minimum_score: Option<i32>,
+    /// Only items scoring at or below this are deleted. Unlike `minimum_score`, zero and
+    /// negative values are allowed so downvoted content can be targeted.
+    pub max_score: Option<i32>,
     pub max_hours: Option<u64>,
+    /// Number of most recent comments and submissions that are never deleted.
+    pub keep_latest: Option<usize>,
+    /// Number of highest scoring comments/submissions kept in each subreddit.
+    pub keep_top_per_subreddit: Option<usize>,
+    /// Percentage of the account's highest scoring comments/submissions that are never deleted.
+    pub keep_top_percent: Option<u8>,
+    pub smtp: Option<SmtpSettings>,
+    #[serde(default)]
+    pub backup: bool,
+    pub backup_recipient: Option<BackupRecipient>,
+    pub s3: Option<S3Settings>,
+    pub timezone: Option<String>,
+    pub match_patterns: Option<Vec<String>>,
+    pub keywords: Option<Vec<String>>,
+    /// When set, only these subreddits are touched. `excluded_subreddits` still wins
+    /// for a subreddit that is on both lists.
+    pub included_subreddits: Option<Vec<String>>,
+    /// Never delete comments/submissions distinguished as a moderator or admin.
+    #[serde(default)]
+    pub keep_distinguished: bool,
+    /// Never delete submissions pinned to the top of a subreddit.
+    #[serde(default)]
+    pub keep_stickied: bool,
+    /// Link submissions to these domains (or their subdomains) are never deleted.
+    pub kept_domains: Option<Vec<String>>,
+    /// When set, link submissions are only deleted if they point at one of these domains.
+    /// Comments and self posts are not affected.
+    pub deleted_domains: Option<Vec<String>>,
+    /// Items with a post or user flair matching one of these are never deleted.
+    pub excluded_flairs: Option<Vec<String>>,
+    /// When set, only items with a post or user flair matching one of these are deleted.
+    pub included_flairs: Option<Vec<String>>,
+    pub edited: Option<EditedFilter>,
+    pub controversial: Option<ControversialFilter>,
+    /// Rule expression every item must also satisfy to be deleted, see `rules::Expr`.
+    pub rule: Option<String>,
+    /// Executable that gets each matched item as json on stdin. Items are only deleted
+    /// when it exits with status 0.
+    pub filter_command: Option<String>,
+    /// Rhai script in the config directory that has the final say on each matched item.
+    pub script: Option<String>,
+    /// How to treat archived items. `None` deletes them like any other.
+    pub archived: Option<ArchivedPolicy>,
+    /// Delete items in quarantined subreddits, opting in to the subreddit when needed.
+    #[serde(default)]
+    pub include_quarantined: bool,
+    /// When set, only submissions of these types ("self", "link", "image", "video",
+    /// "gallery") are deleted. Comments are not affected.
+    pub post_types: Option<Vec<String>>,
+    /// Whether an item has to pass every filter or just one of them. Options that keep
+    /// items, like excluded subreddits, always apply. `None` means every filter.
+    pub combine: Option<CombineMode>,
+    /// How many delete calls may be in flight at once. `None` means the default.
+    pub concurrency: Option<usize>,
+    /// Cron expression, like "0 3 * * *", for when `redelete schedule` runs this account.
+    /// Read in the account's time zone.
+    pub schedule: Option<String>,
+    /// Show a desktop notification when a run finishes.
+    #[serde(default)]
+    pub desktop_notifications: bool,
+    /// Gets a json summary of every run POSTed to it.
+    pub webhook_url: Option<String>,
+    /// Chat service `webhook_url` belongs to. `None` posts the plain json summary.
+    pub webhook_format: Option<WebhookFormat>,
+    /// Shell command run before each run, with its details in `REDELETE_*` variables. The
+    /// run is aborted if it fails.
+    pub pre_run_hook: Option<String>,
+    /// Shell command run after each run, with its results in `REDELETE_*` variables.
+    pub post_run_hook: Option<String>,
+    /// Base URL of a Pushshift style archive searched for items older than reddit's
+    /// listings reach. `None` only uses the listings.
+    pub archive_api: Option<String>,
+    /// Fetch comments and submissions together from the overview listing.
+    #[serde(default)]
+    pub use_overview: bool,
+    /// When the last run for this account finished, in epoch seconds.
+    #[serde(default)]
+    pub last_run: Option<u64>,
+    /// Seconds before the access token expires that it gets refreshed, so a request
+    /// doesn't race the expiry. `None` uses `DEFAULT_REFRESH_MARGIN_SECS`.
+    #[serde(default)]
+    pub refresh_margin: Option<u64>,
+}
+
+/// Access tokens closer than this to expiring count as expiring soon.
+const EXPIRING_SOON_SECS: u64 = 5 * 60;
+pub const DEFAULT_REFRESH_MARGIN_SECS: u64 = 60;
+
+/// Whether an account's saved token can still be used, for `redelete accounts`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TokenStatus {
+    Valid,
+    ExpiringSoon,
+    /// The access token expired, a new one is fetched with the refresh token on next use.
+    Expired,
+    /// There's no refresh token, the account has to be authorized again.
+    MissingRefreshToken,
+}
+
+impl TokenStatus {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TokenStatus::Valid => "valid",
+            TokenStatus::ExpiringSoon => "expiring soon",
+            TokenStatus::Expired => "expired, refreshes on next use",
+            TokenStatus::MissingRefreshToken => "refresh token missing, authorize again",
+        }
+    }
+}
+
+impl AccountInfo {
+    /// The token's status at `now`, in epoch seconds.
+    pub fn token_status(&self, now: u64) -> TokenStatus {
+        if self.token.refresh_token.is_none() {
+            TokenStatus::MissingRefreshToken
+        } else if self.token_expires <= now {
+            TokenStatus::Expired
+        } else if self.token_expires - now < EXPIRING_SOON_SECS {
+            TokenStatus::ExpiringSoon
+        } else {
+            TokenStatus::Valid
+        }
+    }
+
+    /// Whether the access token should be refreshed before a request made at `now`.
+    pub fn needs_refresh(&self, now: u64) -> bool {
+        let margin = self.refresh_margin.unwrap_or(DEFAULT_REFRESH_MARGIN_SECS);
+        self.token_expires <= now + margin
+    }
+}
+
+/// Message layouts for chat services' incoming webhooks.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    Discord,
+    Slack,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CombineMode {
+    /// Delete items that pass every filter.
+    All,
+    /// Delete items that pass at least one filter.
+    Any,
+}
+
+/// What to do with comments/submissions that were edited after posting.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EditedFilter {
+    /// Never delete edited items.
+    Keep,
+    /// Only delete edited items.
+    Only,
+}
+
+/// What to do with items reddit has archived.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchivedPolicy {
+    /// Never touch archived items.
+    Skip,
+    /// Delete archived items but point them out in the run output.
+    Warn,
+}
+
+/// What to do with comments reddit marked as controversial.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ControversialFilter {
+    /// Never delete controversial comments.
+    Keep,
+    /// Only delete controversial comments.
+    Only,
+}
+
+/// S3-compatible bucket that backups are uploaded to. The secret key is read from
+/// the `REDELETE_S3_SECRET_ACCESS_KEY` environment variable.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct S3Settings {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: Option<String>,
+}
+
+/// Public key that backups are encrypted to before they are written.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupRecipient {
+    Age(String),
+    Gpg(String),
+}
+
+/// Where to email the run report. The password is never stored here, it is read from
+/// the `REDELETE_SMTP_PASSWORD` environment variable or the system keyring.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SmtpSettings {
+    pub server: String,
+    pub port: u16,
+    pub username: String,
+    pub from: String,
+    pub to: String,
+    /// Only send the report when some deletions failed.
+    #[serde(default)]
+    pub failures_only: bool,
 }
 
 #[cfg_attr(tarpaulin, skip)]
@@ -42,17 +275,193 @@ custom_error! {pub ConfigError
     Serde{source: serde_json::Error} = "Serde parsing error",
     IO{source: std::io::Error} = "IO Error",
     TOML{source: toml::ser::Error} = "Toml parsing error",
+    TomlRead{source: toml::de::Error} = "Unable to read the settings: {source}",
+    InvalidSettings{reason: String} = "Invalid settings: {reason}",
     NotFound {what: String} = "{what} not found"
 }
 pub type Result<T> = result::Result<T, ConfigError>;
 
+/// Runs the checks the `config` flags run on the account's pattern lists and rule, so a list
+/// that was edited by hand can't reach a run where it would protect nothing.
+pub fn validate_account_info(ai: &AccountInfo) -> Result<()> {
+    let strs = |list: &Option<Vec<String>>| -> Vec<String> { list.clone().unwrap_or_default() };
+    let excluded = strs(&ai.excluded_subreddits);
+    let matched = strs(&ai.match_patterns);
+    let flairs: Vec<String> = strs(&ai.excluded_flairs)
+        .into_iter()
+        .chain(strs(&ai.included_flairs))
+        .collect();
+    patterns::validate_subreddit_patterns(excluded.iter().map(|p| p.as_str()))
+        .and_then(|_| patterns::validate_patterns(matched.iter().map(|p| p.as_str())))
+        .and_then(|_| patterns::validate_flair_patterns(flairs.iter().map(|p| p.as_str())))
+        .map_err(|reason| ConfigError::InvalidSettings { reason })?;
+    if let Some(rule) = &ai.rule {
+        rules::parse(rule).map_err(|reason| ConfigError::InvalidSettings { reason })?;
+    }
+    Ok(())
+}
+
+/// Creates a file only the user can read or write, for anything holding tokens. Fails when
+/// something is already at `path` instead of writing through it.
+pub fn create_private_file(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Writes to a temporary file first and renames it over the config, so an interrupted save
+/// can't leave a half written config behind. The temporary file gets its own name so
+/// accounts saving at the same time don't write over each other's.
 fn save_config(config: Config) -> Result<()> {
     let file_path = config_file_path();
-    let mut file = File::create(file_path)?;
+    let temp_path = file_path.with_extension(format!("conf.{}.tmp", nanoid::simple()));
+    let mut file = create_private_file(&temp_path)?;
     let json = serde_json::to_string(&config)
         .expect(&format!("Unable to parse config to save {:?}", &config));
-    file.write_all(&json.as_bytes())?;
-    Ok(())
+    let written = file
+        .write_all(&json.as_bytes())
+        .and_then(|_| file.sync_all())
+        .and_then(|_| std::fs::rename(&temp_path, &file_path));
+    if written.is_err() {
+        std::fs::remove_file(&temp_path).ok();
+    }
+    Ok(written?)
+}
+
+/// Writes every account to `path` as json for `redelete config import` on another machine.
+/// Without `include_tokens` the accounts need authorizing again after importing. Returns
+/// how many accounts were written.
+pub fn export_accounts(path: &Path, include_tokens: bool) -> Result<usize> {
+    let config = get_config()?;
+    let mut accounts = Vec::new();
+    for ai in &config.accounts {
+        let mut account = serde_json::to_value(ai)?;
+        if let (false, Some(object)) = (include_tokens, account.as_object_mut()) {
+            object.remove("token");
+            object.remove("token_expires");
+        }
+        accounts.push(account);
+    }
+    let count = accounts.len();
+    let export = serde_json::to_string_pretty(&json!({ "accounts": accounts }))?;
+    // The export can hold refresh tokens, so it's only readable by the user, even when it
+    // replaces a file that wasn't.
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(export.as_bytes())?;
+    Ok(count)
+}
+
+/// Adds the accounts in a file from `export_accounts`, replacing saved accounts with the
+/// same username. Accounts exported without tokens keep the token already saved here, or
+/// get an empty one until they're authorized. Nothing is saved unless every account parses
+/// and passes the checks the `config` flags run.
+/// Returns each imported username and whether it needs authorizing.
+pub fn import_accounts(path: &Path) -> Result<Vec<(String, bool)>> {
+    let invalid = |reason: &str| ConfigError::InvalidSettings {
+        reason: format!("{} {}", path.display(), reason),
+    };
+    let export: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let accounts = export["accounts"]
+        .as_array()
+        .ok_or(invalid("has no accounts"))?;
+    let mut config = get_config()?;
+    let mut imported = Vec::new();
+    for account in accounts {
+        let mut account = account.clone();
+        let username = account["username"]
+            .as_str()
+            .map(String::from)
+            .ok_or(invalid("has an account without a username"))?;
+        let current = config.accounts.iter().find(|ai| ai.username == username);
+        if let Some(object) = account.as_object_mut() {
+            if !object.contains_key("token") {
+                let (token, token_expires) = match current {
+                    Some(ai) => (ai.token.clone(), ai.token_expires),
+                    None => (unauthorized_token(), 0),
+                };
+                object.insert("token".into(), serde_json::to_value(token)?);
+                object.insert("token_expires".into(), token_expires.into());
+            }
+        }
+        let ai: AccountInfo =
+            serde_json::from_value(account).map_err(|e| ConfigError::InvalidSettings {
+                reason: format!("{}: {}", username, e),
+            })?;
+        validate_account_info(&ai).map_err(|e| ConfigError::InvalidSettings {
+            reason: format!("{}: {}", username, e),
+        })?;
+        imported.push((username.clone(), ai.token.refresh_token.is_none()));
+        config.accounts.retain(|a| a.username != username);
+        config.accounts.push(ai);
+    }
+    save_config(config)?;
+    Ok(imported)
+}
+
+/// Stands in for the token of an account that hasn't been authorized, `redelete authorize`
+/// replaces it.
+pub fn unauthorized_token() -> OAuthToken {
+    OAuthToken {
+        access_token: String::new(),
+        token_type: String::from("bearer"),
+        expires_in: 0,
+        scope: String::new(),
+        refresh_token: None,
+        client_id: None,
+    }
+}
+
+/// Fields `redelete config --edit` doesn't show, they belong to authorizing.
+const UNEDITABLE: [&str; 3] = ["username", "token", "token_expires"];
+
+/// The account's settings as toml for `redelete config --edit`, without the token. Unset
+/// settings are left out since toml has no null.
+pub fn editable_settings(username: &str) -> Result<String> {
+    let (_, ai) = get_config_and_account_info(username)?;
+    let mut settings = serde_json::to_value(&ai)?;
+    if let Some(object) = settings.as_object_mut() {
+        object.retain(|key, value| !value.is_null() && !UNEDITABLE.contains(&key.as_str()));
+    }
+    let settings = toml::Value::try_from(settings)?;
+    Ok(toml::to_string_pretty(&settings)?)
+}
+
+/// Replaces the account's settings with edited toml from `editable_settings`. Nothing is
+/// saved unless every setting parses and passes the checks the `config` flags run.
+pub fn set_edited_settings(username: &str, edited: &str) -> Result<()> {
+    let (mut c, ai) = get_config_and_account_info(username)?;
+    let edited: toml::Value = toml::from_str(edited)?;
+    let mut settings = serde_json::to_value(edited)?;
+    let current = serde_json::to_value(&ai)?;
+    if let Some(object) = settings.as_object_mut() {
+        for key in UNEDITABLE.iter() {
+            object.insert(String::from(*key), current[*key].clone());
+        }
+    }
+    let ai: AccountInfo =
+        serde_json::from_value(settings).map_err(|e| ConfigError::InvalidSettings {
+            reason: e.to_string(),
+        })?;
+    validate_account_info(&ai)?;
+    c.accounts.push(ai);
+    Ok(save_config(c)?)
 }
 
 pub fn get_config_and_account_info(username: &str) -> Result<(Config, AccountInfo)> {
@@ -105,247 +514,1374 @@ pub fn set_excluded_subreddits(username: String, excluded_subreddits: Vec<String
     c.accounts.push(ai.clone());
     save_config(c)?;
     match ai.excluded_subreddits {
-        Some(x) => println!("Excluded subreddits set to {}.", &x.join(", ")),
-        None => println!("Excluded subreddits updated -- no subreddits are excluded."),
+        Some(x) => say!("Excluded subreddits set to {}.", &x.join(", ")),
+        None => say!("Excluded subreddits updated -- no subreddits are excluded."),
     }
     Ok(())
 }
 
-pub fn set_max_hours(username: String, max_hours: u64) -> Result<()> {
-    let (mut c, mut ai) = get_config_and_account_info(&username)?;
-    if max_hours > 0 {
-        ai.max_hours = Some(max_hours);
-    } else {
-        ai.max_hours = None;
+pub fn remove_match_patterns(username: String, patterns: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mp: Vec<String> = ai
+        .match_patterns
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|p| !patterns.contains(&p.as_str()))
+        .collect();
+    set_match_patterns(username, mp)?;
+    Ok(())
+}
+
+pub fn add_match_patterns(username: String, patterns: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut mp = ai.match_patterns.unwrap_or(Vec::new());
+    for p in patterns {
+        let s = String::from(p);
+        if !mp.contains(&s) {
+            mp.push(s)
+        }
     }
-    c.accounts.push(ai.clone());
-    Ok(save_config(c)?)
+    set_match_patterns(username, mp)?;
+    Ok(())
 }
 
-pub fn set_minimum_score(username: String, score: i32) -> Result<()> {
+pub fn set_match_patterns(username: String, match_patterns: Vec<String>) -> Result<()> {
     let (mut c, mut ai) = get_config_and_account_info(&username)?;
-    if score > 0 {
-        ai.minimum_score = Some(score);
+    ai.match_patterns = if match_patterns.len() > 0 {
+        Some(match_patterns)
     } else {
-        ai.minimum_score = None;
-    }
+        None
+    };
     c.accounts.push(ai.clone());
-    Ok(save_config(c)?)
+    save_config(c)?;
+    match ai.match_patterns {
+        Some(x) => say!("Only deleting posts matching {}.", &x.join(", ")),
+        None => say!("Match patterns updated -- posts are not filtered by content."),
+    }
+    Ok(())
 }
 
-pub fn save_token(username: String, token: OAuthToken) -> Result<AccountInfo> {
-    let token_expires = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Couldn't get systemtime")
-        .as_secs()
-        + &token.expires_in;
-    let (mut config, account_info) = match get_config_and_account_info(&username) {
-        Ok((c, mut ai)) => {
-            ai.token = token;
-            ai.token_expires = token_expires;
-            (c, ai)
-        }
-        Err(_) => {
-            let c = get_config()?;
-            let ai = AccountInfo {
-                username: String::from(&username),
-                token,
-                token_expires,
-                minimum_score: None,
-                excluded_subreddits: None,
-                max_hours: None,
-            };
-            (c, ai)
+pub fn remove_keywords(username: String, keywords: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let kw: Vec<String> = ai
+        .keywords
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|k| !keywords.iter().any(|r| r.eq_ignore_ascii_case(k)))
+        .collect();
+    set_keywords(username, kw)?;
+    Ok(())
+}
+
+pub fn add_keywords(username: String, keywords: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut kw = ai.keywords.unwrap_or(Vec::new());
+    for k in keywords {
+        if !kw.iter().any(|existing| existing.eq_ignore_ascii_case(k)) {
+            kw.push(String::from(k))
         }
+    }
+    set_keywords(username, kw)?;
+    Ok(())
+}
+
+pub fn set_keywords(username: String, keywords: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.keywords = if keywords.len() > 0 {
+        Some(keywords)
+    } else {
+        None
     };
-    let cloned = account_info.clone();
-    config.accounts.push(cloned);
-    save_config(config)?;
-    println!(
-        "Saved user <{}> information into {}",
-        username,
-        config_file_path().display()
-    );
-    Ok(account_info)
+    c.accounts.push(ai.clone());
+    save_config(c)?;
+    match ai.keywords {
+        Some(x) => say!("Only deleting posts containing {}.", &x.join(", ")),
+        None => say!("Keywords updated -- posts are not filtered by keyword."),
+    }
+    Ok(())
 }
 
-// I think I may have accidentally coded this twice.
-#[cfg(test)]
-pub fn update_token(username: String, token: OAuthToken) -> Result<()> {
-    let account_config =
-        read_config_account_info(&username).expect(&format!("Unable to find user {}", username));
-    let mut token_ = token.clone();
-    token_.refresh_token = account_config.token.refresh_token;
-    save_token(username, token_)?;
+pub fn remove_kept_domains(username: String, domains: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let ds: Vec<String> = ai
+        .kept_domains
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|d| !domains.iter().any(|r| r.eq_ignore_ascii_case(d)))
+        .collect();
+    set_kept_domains(username, ds)?;
     Ok(())
 }
 
-fn get_config() -> Result<Config> {
-    if !config_file_path().exists() {
-        let dirs = config_dir();
-        std::fs::create_dir_all(dirs)?;
-        let _f = File::create(config_file_path())?;
-        Ok(Config {
-            accounts: Vec::new(),
-        })
-    } else {
-        let file_path = config_file_path();
-        let mut file = std::fs::File::open(&file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        if &contents == "" {
-            Ok(Config {
-                accounts: Vec::new(),
-            })
-        } else {
-            let config: Config = serde_json::from_str(&contents).unwrap();
-            Ok(config)
+pub fn add_kept_domains(username: String, domains: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut ds = ai.kept_domains.unwrap_or(Vec::new());
+    for d in domains {
+        let d = d.to_lowercase();
+        if !ds.contains(&d) {
+            ds.push(d)
         }
     }
+    set_kept_domains(username, ds)?;
+    Ok(())
 }
 
-pub fn delete_user(username: &str) -> Result<bool> {
-    let config = get_config().unwrap();
-    let mut accounts: Vec<AccountInfo> = Vec::new();
-    for account in &config.accounts {
-        if account.username != String::from(username) {
-            let acct = account.clone();
-            accounts.push(acct);
-        } else {
-            continue;
+pub fn set_kept_domains(username: String, domains: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.kept_domains = if domains.len() > 0 {
+        Some(domains)
+    } else {
+        None
+    };
+    c.accounts.push(ai.clone());
+    save_config(c)?;
+    match ai.kept_domains {
+        Some(x) => say!("Never deleting submissions linking to {}.", &x.join(", ")),
+        None => say!("Domains updated -- no domains are kept."),
+    }
+    Ok(())
+}
+
+pub fn remove_deleted_domains(username: String, domains: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let ds: Vec<String> = ai
+        .deleted_domains
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|d| !domains.iter().any(|r| r.eq_ignore_ascii_case(d)))
+        .collect();
+    set_deleted_domains(username, ds)?;
+    Ok(())
+}
+
+pub fn add_deleted_domains(username: String, domains: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut ds = ai.deleted_domains.unwrap_or(Vec::new());
+    for d in domains {
+        let d = d.to_lowercase();
+        if !ds.contains(&d) {
+            ds.push(d)
         }
     }
-    if accounts.len() < config.accounts.len() {
-        save_config(Config { accounts }).expect("Failed to delete user from config.");
-        Ok(true)
+    set_deleted_domains(username, ds)?;
+    Ok(())
+}
+
+pub fn set_deleted_domains(username: String, domains: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.deleted_domains = if domains.len() > 0 {
+        Some(domains)
     } else {
-        Ok(false)
+        None
+    };
+    c.accounts.push(ai.clone());
+    save_config(c)?;
+    match ai.deleted_domains {
+        Some(x) => say!("Only deleting submissions linking to {}.", &x.join(", ")),
+        None => say!("Domains updated -- submissions are not filtered by domain."),
     }
+    Ok(())
 }
 
-pub fn read_config_account_info(username: &str) -> Option<AccountInfo> {
-    let config = get_config().unwrap();
-    for account in config.accounts {
-        if account.username == String::from(username) {
-            return Some(account);
+pub fn remove_excluded_flairs(username: String, flairs: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let fs: Vec<String> = ai
+        .excluded_flairs
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|f| !flairs.iter().any(|r| r == f))
+        .collect();
+    set_excluded_flairs(username, fs)?;
+    Ok(())
+}
+
+pub fn add_excluded_flairs(username: String, flairs: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut fs = ai.excluded_flairs.unwrap_or(Vec::new());
+    for f in flairs {
+        if !fs.iter().any(|existing| existing == f) {
+            fs.push(String::from(f))
         }
     }
-    None
+    set_excluded_flairs(username, fs)?;
+    Ok(())
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_config_dir() {
-        assert_eq!(
-            config_dir(),
-            PathBuf::from(&*format!(
-                "{}/.config/redelete",
-                dirs::home_dir().unwrap().to_str().unwrap()
-            ))
-        )
+pub fn set_excluded_flairs(username: String, flairs: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.excluded_flairs = if flairs.len() > 0 { Some(flairs) } else { None };
+    c.accounts.push(ai.clone());
+    save_config(c)?;
+    match ai.excluded_flairs {
+        Some(x) => say!("Never deleting posts flaired {}.", &x.join(", ")),
+        None => say!("Flairs updated -- no flairs are excluded."),
     }
-    #[test]
-    #[cfg(not(target_os = "windows"))]
-    fn test_token_file_path() {
-        assert_eq!(
-            config_file_path(),
-            PathBuf::from(&format!(
+    Ok(())
+}
+
+pub fn remove_included_flairs(username: String, flairs: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let fs: Vec<String> = ai
+        .included_flairs
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|f| !flairs.iter().any(|r| r == f))
+        .collect();
+    set_included_flairs(username, fs)?;
+    Ok(())
+}
+
+pub fn add_included_flairs(username: String, flairs: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut fs = ai.included_flairs.unwrap_or(Vec::new());
+    for f in flairs {
+        if !fs.iter().any(|existing| existing == f) {
+            fs.push(String::from(f))
+        }
+    }
+    set_included_flairs(username, fs)?;
+    Ok(())
+}
+
+pub fn set_included_flairs(username: String, flairs: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.included_flairs = if flairs.len() > 0 { Some(flairs) } else { None };
+    c.accounts.push(ai.clone());
+    save_config(c)?;
+    match ai.included_flairs {
+        Some(x) => say!("Only deleting posts flaired {}.", &x.join(", ")),
+        None => say!("Flairs updated -- posts are not filtered by flair."),
+    }
+    Ok(())
+}
+
+pub fn remove_included_subreddits(username: String, subreddits: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let is: Vec<String> = ai
+        .included_subreddits
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|sr| !subreddits.iter().any(|r| r.eq_ignore_ascii_case(sr)))
+        .collect();
+    set_included_subreddits(username, is)?;
+    Ok(())
+}
+
+pub fn add_included_subreddits(username: String, subreddits: Vec<&str>) -> Result<()> {
+    let (_, ai) = get_config_and_account_info(&username)?;
+    let mut is = ai.included_subreddits.unwrap_or(Vec::new());
+    for sr in subreddits {
+        if !is.iter().any(|existing| existing.eq_ignore_ascii_case(sr)) {
+            is.push(String::from(sr))
+        }
+    }
+    set_included_subreddits(username, is)?;
+    Ok(())
+}
+
+pub fn set_included_subreddits(username: String, included_subreddits: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.included_subreddits = if included_subreddits.len() > 0 {
+        Some(included_subreddits)
+    } else {
+        None
+    };
+    c.accounts.push(ai.clone());
+    save_config(c)?;
+    match ai.included_subreddits {
+        Some(x) => say!("Only deleting posts in {}.", &x.join(", ")),
+        None => say!("Included subreddits updated -- posts in any subreddit may be deleted."),
+    }
+    Ok(())
+}
+
+pub fn set_max_hours(username: String, max_hours: u64) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    if max_hours > 0 {
+        ai.max_hours = Some(max_hours);
+    } else {
+        ai.max_hours = None;
+    }
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_keep_latest(username: String, keep_latest: usize) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    if keep_latest > 0 {
+        ai.keep_latest = Some(keep_latest);
+    } else {
+        ai.keep_latest = None;
+    }
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_keep_top_per_subreddit(username: String, keep: usize) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    if keep > 0 {
+        ai.keep_top_per_subreddit = Some(keep);
+    } else {
+        ai.keep_top_per_subreddit = None;
+    }
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_keep_top_percent(username: String, percent: u8) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    if percent > 0 {
+        ai.keep_top_percent = Some(percent);
+    } else {
+        ai.keep_top_percent = None;
+    }
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_minimum_score(username: String, score: i32) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    if score > 0 {
+        ai.minimum_score = Some(score);
+    } else {
+        ai.minimum_score = None;
+    }
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_smtp(username: String, smtp: Option<SmtpSettings>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.smtp = smtp;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_backup(username: String, backup: bool) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.backup = backup;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_keep_distinguished(username: String, keep: bool) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.keep_distinguished = keep;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_keep_stickied(username: String, keep: bool) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.keep_stickied = keep;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_edited_filter(username: String, filter: Option<EditedFilter>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.edited = filter;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_controversial_filter(
+    username: String,
+    filter: Option<ControversialFilter>,
+) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.controversial = filter;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_rule(username: String, rule: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.rule = rule;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_filter_command(username: String, command: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.filter_command = command;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_script(username: String, script: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.script = script;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_archived_policy(username: String, policy: Option<ArchivedPolicy>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.archived = policy;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_include_quarantined(username: String, include: bool) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.include_quarantined = include;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_last_run(username: String, finished: u64) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.last_run = Some(finished);
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_use_overview(username: String, overview: bool) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.use_overview = overview;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_post_types(username: String, post_types: Vec<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.post_types = if post_types.len() > 0 {
+        Some(post_types)
+    } else {
+        None
+    };
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_combine_mode(username: String, combine: Option<CombineMode>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.combine = combine;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_concurrency(username: String, concurrency: usize) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    if concurrency > 0 {
+        ai.concurrency = Some(concurrency);
+    } else {
+        ai.concurrency = None;
+    }
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_backup_recipient(username: String, recipient: Option<BackupRecipient>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.backup_recipient = recipient;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_s3(username: String, s3: Option<S3Settings>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.s3 = s3;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_schedule(username: String, schedule: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.schedule = schedule;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_desktop_notifications(username: String, enabled: bool) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.desktop_notifications = enabled;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_webhook_url(username: String, url: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.webhook_url = url;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_webhook_format(username: String, format: Option<WebhookFormat>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.webhook_format = format;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_pre_run_hook(username: String, command: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.pre_run_hook = command;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_post_run_hook(username: String, command: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.post_run_hook = command;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_archive_api(username: String, url: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.archive_api = url;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_refresh_margin(username: String, margin: Option<u64>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.refresh_margin = margin;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_timezone(username: String, timezone: Option<String>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.timezone = timezone;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+pub fn set_max_score(username: String, max_score: Option<i32>) -> Result<()> {
+    let (mut c, mut ai) = get_config_and_account_info(&username)?;
+    ai.max_score = max_score;
+    c.accounts.push(ai.clone());
+    Ok(save_config(c)?)
+}
+
+/// An account with no settings, as `redelete authorize` first saves it.
+pub fn new_account_info(username: &str, token: OAuthToken, token_expires: u64) -> AccountInfo {
+    AccountInfo {
+        username: String::from(username),
+        token,
+        token_expires,
+        minimum_score: None,
+        max_score: None,
+        excluded_subreddits: None,
+        max_hours: None,
+        keep_latest: None,
+        keep_top_per_subreddit: None,
+        keep_top_percent: None,
+        smtp: None,
+        backup: false,
+        backup_recipient: None,
+        s3: None,
+        timezone: None,
+        match_patterns: None,
+        keywords: None,
+        included_subreddits: None,
+        keep_distinguished: false,
+        keep_stickied: false,
+        kept_domains: None,
+        deleted_domains: None,
+        excluded_flairs: None,
+        included_flairs: None,
+        edited: None,
+        controversial: None,
+        rule: None,
+        filter_command: None,
+        script: None,
+        archived: None,
+        include_quarantined: false,
+        post_types: None,
+        combine: None,
+        concurrency: None,
+        schedule: None,
+        desktop_notifications: false,
+        webhook_url: None,
+        webhook_format: None,
+        pre_run_hook: None,
+        post_run_hook: None,
+        archive_api: None,
+        use_overview: false,
+        last_run: None,
+        refresh_margin: None,
+    }
+}
+
+pub fn save_token(username: String, token: OAuthToken) -> Result<AccountInfo> {
+    let token_expires = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Couldn't get systemtime")
+        .as_secs()
+        + &token.expires_in;
+    let (mut config, account_info) = match get_config_and_account_info(&username) {
+        Ok((c, mut ai)) => {
+            ai.token = token;
+            ai.token_expires = token_expires;
+            (c, ai)
+        }
+        Err(_) => {
+            let c = get_config()?;
+            let ai = new_account_info(&username, token, token_expires);
+            (c, ai)
+        }
+    };
+    let cloned = account_info.clone();
+    config.accounts.push(cloned);
+    save_config(config)?;
+    say!(
+        "Saved user <{}> information into {}",
+        username,
+        config_file_path().display()
+    );
+    Ok(account_info)
+}
+
+// I think I may have accidentally coded this twice.
+#[cfg(test)]
+pub fn update_token(username: String, token: OAuthToken) -> Result<()> {
+    let account_config =
+        read_config_account_info(&username).expect(&format!("Unable to find user {}", username));
+    let mut token_ = token.clone();
+    token_.refresh_token = account_config.token.refresh_token;
+    save_token(username, token_)?;
+    Ok(())
+}
+
+fn get_config() -> Result<Config> {
+    if !config_file_path().exists() {
+        let dirs = config_dir();
+        std::fs::create_dir_all(dirs)?;
+        let _f = File::create(config_file_path())?;
+        Ok(Config {
+            accounts: Vec::new(),
+            app: None,
+            listen: None,
+        })
+    } else {
+        let file_path = config_file_path();
+        let mut file = std::fs::File::open(&file_path)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        if &contents == "" {
+            Ok(Config {
+                accounts: Vec::new(),
+                app: None,
+                listen: None,
+            })
+        } else {
+            let config: Config = serde_json::from_str(&contents).unwrap();
+            Ok(config)
+        }
+    }
+}
+
+pub fn delete_user(username: &str) -> Result<bool> {
+    let config = get_config().unwrap();
+    let mut accounts: Vec<AccountInfo> = Vec::new();
+    for account in &config.accounts {
+        if account.username != String::from(username) {
+            let acct = account.clone();
+            accounts.push(acct);
+        } else {
+            continue;
+        }
+    }
+    if accounts.len() < config.accounts.len() {
+        save_config(Config { accounts, ..config }).expect("Failed to delete user from config.");
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// The address set with `redelete authorize --listen`, if any.
+pub fn redirect_listener() -> Option<String> {
+    get_config().ok()?.listen
+}
+
+/// Saves where to wait for reddit's redirect, `None` goes back to the redirect uri's port on
+/// every interface.
+pub fn set_redirect_listener(listen: Option<String>) -> Result<()> {
+    let mut c = get_config()?;
+    c.listen = listen;
+    Ok(save_config(c)?)
+}
+
+/// The user's own reddit app, if they set one with `redelete authorize --client-id`.
+pub fn reddit_app() -> Option<RedditApp> {
+    get_config().ok()?.app
+}
+
+/// Saves the reddit app to authorize and refresh tokens with, `None` goes back to redelete's.
+pub fn set_reddit_app(app: Option<RedditApp>) -> Result<()> {
+    let mut c = get_config()?;
+    c.app = app;
+    Ok(save_config(c)?)
+}
+
+pub fn read_config_account_info(username: &str) -> Option<AccountInfo> {
+    let config = get_config().unwrap();
+    for account in config.accounts {
+        if account.username == String::from(username) {
+            return Some(account);
+        }
+    }
+    None
+}
+
+/// Every username saved in the config, in the order they were authorized.
+pub fn read_config_usernames() -> Result<Vec<String>> {
+    let config = get_config()?;
+    Ok(config.accounts.into_iter().map(|a| a.username).collect())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_config_dir() {
+        assert_eq!(
+            config_dir(),
+            PathBuf::from(&*format!(
+                "{}/.config/redelete",
+                dirs::home_dir().unwrap().to_str().unwrap()
+            ))
+        )
+    }
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_token_file_path() {
+        assert_eq!(
+            config_file_path(),
+            PathBuf::from(&format!(
                 "{}/.config/redelete/redelete.conf",
                 dirs::home_dir().unwrap().to_str().unwrap()
             ))
         )
     }
-
-    pub fn token() -> OAuthToken {
-        OAuthToken {
-            access_token: "ACCESS_TOKEN".into(),
-            token_type: "bearer".into(),
-            expires_in: 3600,
-            scope: "history,edit,account".into(),
-            refresh_token: Some("REFRESH_TOKEN".into()),
-        }
+
+    pub fn token() -> OAuthToken {
+        OAuthToken {
+            access_token: "ACCESS_TOKEN".into(),
+            token_type: "bearer".into(),
+            expires_in: 3600,
+            scope: "history,edit,account".into(),
+            refresh_token: Some("REFRESH_TOKEN".into()),
+            client_id: None,
+        }
+    }
+    pub fn test_username() -> String {
+        "TestUser".into()
+    }
+    pub fn fresh_account_info() -> AccountInfo {
+        AccountInfo {
+            username: test_username(),
+            token: token(),
+            token_expires: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                + token().expires_in,
+            excluded_subreddits: None,
+            max_hours: None,
+            keep_latest: None,
+            keep_top_per_subreddit: None,
+            keep_top_percent: None,
+            minimum_score: None,
+            max_score: None,
+            smtp: None,
+            backup: false,
+            backup_recipient: None,
+            s3: None,
+            timezone: None,
+            match_patterns: None,
+            keywords: None,
+            included_subreddits: None,
+            keep_distinguished: false,
+            keep_stickied: false,
+            kept_domains: None,
+            deleted_domains: None,
+            excluded_flairs: None,
+            included_flairs: None,
+            edited: None,
+            controversial: None,
+            rule: None,
+            filter_command: None,
+            script: None,
+            archived: None,
+            include_quarantined: false,
+            post_types: None,
+            combine: None,
+            concurrency: None,
+            schedule: None,
+            desktop_notifications: false,
+            webhook_url: None,
+            webhook_format: None,
+            pre_run_hook: None,
+            post_run_hook: None,
+            archive_api: None,
+            use_overview: false,
+            last_run: None,
+            refresh_margin: None,
+        }
+    }
+
+    pub fn account_info() -> AccountInfo {
+        AccountInfo {
+            username: test_username(),
+            token: token(),
+            token_expires: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                + token().expires_in,
+            excluded_subreddits: Some(vec!["a".into(), "b".into(), "c".into()]),
+            max_hours: Some(24),
+            keep_latest: None,
+            keep_top_per_subreddit: None,
+            keep_top_percent: None,
+            minimum_score: Some(1000),
+            max_score: None,
+            smtp: None,
+            backup: false,
+            backup_recipient: None,
+            s3: None,
+            timezone: None,
+            match_patterns: None,
+            keywords: None,
+            included_subreddits: None,
+            keep_distinguished: false,
+            keep_stickied: false,
+            kept_domains: None,
+            deleted_domains: None,
+            excluded_flairs: None,
+            included_flairs: None,
+            edited: None,
+            controversial: None,
+            rule: None,
+            filter_command: None,
+            script: None,
+            archived: None,
+            include_quarantined: false,
+            post_types: None,
+            combine: None,
+            concurrency: None,
+            schedule: None,
+            desktop_notifications: false,
+            webhook_url: None,
+            webhook_format: None,
+            pre_run_hook: None,
+            post_run_hook: None,
+            archive_api: None,
+            use_overview: false,
+            last_run: None,
+            refresh_margin: None,
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_save_get_delete_user() {
+        save_token(test_username(), token()).unwrap();
+        assert_eq!(
+            read_config_account_info(&test_username()).unwrap(),
+            fresh_account_info()
+        );
+        assert_eq!(delete_user(&test_username()).unwrap(), true);
+    }
+
+    #[test]
+    #[serial]
+    fn test_read_config_usernames() {
+        save_token(test_username(), token()).unwrap();
+        assert_eq!(
+            read_config_usernames().unwrap().contains(&test_username()),
+            true
+        );
+        delete_user(&test_username()).unwrap();
+        assert_eq!(
+            read_config_usernames().unwrap().contains(&test_username()),
+            false
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_no_duplicates() {
+        let before_config = get_config().unwrap();
+        let token1 = token();
+        let token2 = token();
+        save_token(test_username(), token1).unwrap();
+        save_token(test_username(), token2).unwrap();
+        let config = get_config().unwrap();
+        assert_eq!(config.accounts.len(), before_config.accounts.len() + 1);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_refresh_user_token() {
+        const NEW_ACCESS_TOKEN: &'static str = "NEW_ACCESS_TOKEN";
+        save_token(test_username(), token()).unwrap();
+        let mut new_token = token();
+        new_token.refresh_token = None;
+        new_token.access_token = NEW_ACCESS_TOKEN.into();
+        update_token(test_username(), new_token).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.token.access_token, NEW_ACCESS_TOKEN);
+        assert_eq!(
+            account_info.token.refresh_token,
+            Some("REFRESH_TOKEN".into())
+        );
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_max_hours() {
+        save_token(test_username(), token()).unwrap();
+        assert_eq!(set_max_hours(test_username(), 1).unwrap(), ());
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_keep_latest() {
+        save_token(test_username(), token()).unwrap();
+        set_keep_latest(test_username(), 10).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_latest, Some(10));
+        set_keep_latest(test_username(), 0).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_latest, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_keep_distinguished() {
+        save_token(test_username(), token()).unwrap();
+        set_keep_distinguished(test_username(), true).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_distinguished, true);
+        set_keep_distinguished(test_username(), false).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_distinguished, false);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_keep_stickied() {
+        save_token(test_username(), token()).unwrap();
+        set_keep_stickied(test_username(), true).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_stickied, true);
+        set_keep_stickied(test_username(), false).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_stickied, false);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_edited_filter() {
+        save_token(test_username(), token()).unwrap();
+        set_edited_filter(test_username(), Some(EditedFilter::Keep)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.edited, Some(EditedFilter::Keep));
+        set_edited_filter(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.edited, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_controversial_filter() {
+        save_token(test_username(), token()).unwrap();
+        set_controversial_filter(test_username(), Some(ControversialFilter::Only)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.controversial, Some(ControversialFilter::Only));
+        set_controversial_filter(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.controversial, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_rule() {
+        save_token(test_username(), token()).unwrap();
+        set_rule(test_username(), Some("score < 5 && age > 30d".into())).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.rule, Some("score < 5 && age > 30d".into()));
+        set_rule(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.rule, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_filter_command() {
+        save_token(test_username(), token()).unwrap();
+        set_filter_command(test_username(), Some("./classify.py --strict".into())).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.filter_command,
+            Some("./classify.py --strict".into())
+        );
+        set_filter_command(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.filter_command, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_script() {
+        save_token(test_username(), token()).unwrap();
+        set_script(test_username(), Some("scripts/user.rhai".into())).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.script, Some("scripts/user.rhai".into()));
+        set_script(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.script, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_archived_policy() {
+        save_token(test_username(), token()).unwrap();
+        set_archived_policy(test_username(), Some(ArchivedPolicy::Skip)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.archived, Some(ArchivedPolicy::Skip));
+        set_archived_policy(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.archived, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_include_quarantined() {
+        save_token(test_username(), token()).unwrap();
+        set_include_quarantined(test_username(), true).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.include_quarantined, true);
+        set_include_quarantined(test_username(), false).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.include_quarantined, false);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_post_types() {
+        save_token(test_username(), token()).unwrap();
+        set_post_types(test_username(), vec!["link".into(), "image".into()]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.post_types,
+            Some(vec!["link".into(), "image".into()])
+        );
+        set_post_types(test_username(), vec![]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.post_types, None);
+        delete_user(&test_username()).unwrap();
     }
-    pub fn test_username() -> String {
-        "TestUser".into()
+    #[test]
+    #[serial]
+    fn test_set_concurrency() {
+        save_token(test_username(), token()).unwrap();
+        set_concurrency(test_username(), 16).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.concurrency, Some(16));
+        set_concurrency(test_username(), 0).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.concurrency, None);
+        delete_user(&test_username()).unwrap();
     }
-    pub fn fresh_account_info() -> AccountInfo {
-        AccountInfo {
-            username: test_username(),
-            token: token(),
-            token_expires: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-                + token().expires_in,
-            excluded_subreddits: None,
-            max_hours: None,
-            minimum_score: None,
-        }
+    #[test]
+    #[serial]
+    fn test_set_schedule() {
+        save_token(test_username(), token()).unwrap();
+        set_schedule(test_username(), Some(String::from("0 3 * * *"))).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.schedule, Some(String::from("0 3 * * *")));
+        set_schedule(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.schedule, None);
+        delete_user(&test_username()).unwrap();
     }
-
-    pub fn account_info() -> AccountInfo {
-        AccountInfo {
-            username: test_username(),
-            token: token(),
-            token_expires: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
+    #[test]
+    #[serial]
+    fn test_set_desktop_notifications() {
+        save_token(test_username(), token()).unwrap();
+        set_desktop_notifications(test_username(), true).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.desktop_notifications, true);
+        set_desktop_notifications(test_username(), false).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.desktop_notifications, false);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_webhook_url() {
+        save_token(test_username(), token()).unwrap();
+        set_webhook_url(
+            test_username(),
+            Some(String::from("https://example.com/hook")),
+        )
+        .unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.webhook_url,
+            Some(String::from("https://example.com/hook"))
+        );
+        set_webhook_url(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.webhook_url, None);
+        set_webhook_format(test_username(), Some(WebhookFormat::Slack)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.webhook_format, Some(WebhookFormat::Slack));
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_run_hooks() {
+        save_token(test_username(), token()).unwrap();
+        set_pre_run_hook(test_username(), Some("./snapshot.sh".into())).unwrap();
+        set_post_run_hook(test_username(), Some("make sync".into())).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.pre_run_hook,
+            Some(String::from("./snapshot.sh"))
+        );
+        assert_eq!(account_info.post_run_hook, Some(String::from("make sync")));
+        set_pre_run_hook(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.pre_run_hook, None);
+        assert_eq!(account_info.post_run_hook, Some(String::from("make sync")));
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_export_import_accounts() {
+        let path = std::env::temp_dir().join("redelete-test-accounts.json");
+        save_token(test_username(), token()).unwrap();
+        set_minimum_score(test_username(), 5).unwrap();
+        assert_eq!(export_accounts(&path, false).unwrap() > 0, true);
+        assert_eq!(
+            std::fs::read_to_string(&path)
                 .unwrap()
-                .as_secs()
-                + token().expires_in,
-            excluded_subreddits: Some(vec!["a".into(), "b".into(), "c".into()]),
-            max_hours: Some(24),
-            minimum_score: Some(1000),
-        }
+                .contains("ACCESS_TOKEN"),
+            false
+        );
+        delete_user(&test_username()).unwrap();
+        let imported = import_accounts(&path).unwrap();
+        assert_eq!(imported.contains(&(test_username(), true)), true);
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.minimum_score, Some(5));
+        assert_eq!(account_info.token.refresh_token, None);
+        save_token(test_username(), token()).unwrap();
+        export_accounts(&path, true).unwrap();
+        delete_user(&test_username()).unwrap();
+        assert_eq!(
+            import_accounts(&path)
+                .unwrap()
+                .contains(&(test_username(), false)),
+            true
+        );
+        assert_eq!(
+            read_config_account_info(&test_username()).unwrap().token,
+            token()
+        );
+        delete_user(&test_username()).unwrap();
+        let broken = std::fs::read_to_string(&path).unwrap().replace(
+            "\"excluded_subreddits\": null",
+            "\"excluded_subreddits\": [\"/(rust/\"]",
+        );
+        std::fs::write(&path, broken).unwrap();
+        assert_eq!(import_accounts(&path).is_err(), true);
+        assert_eq!(read_config_account_info(&test_username()).is_none(), true);
+        std::fs::remove_file(&path).unwrap();
     }
-
     #[test]
     #[serial]
-    fn test_save_get_delete_user() {
+    fn test_edit_settings() {
         save_token(test_username(), token()).unwrap();
+        set_minimum_score(test_username(), 5).unwrap();
+        let settings = editable_settings(&test_username()).unwrap();
+        assert_eq!(settings.contains("minimum_score = 5"), true);
+        assert_eq!(settings.contains("ACCESS_TOKEN"), false);
+        let edited = settings.replace("minimum_score = 5", "minimum_score = 7")
+            + "excluded_subreddits = [\"rust\"]\nusername = \"Someone\"\n";
+        set_edited_settings(&test_username(), &edited).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.minimum_score, Some(7));
+        assert_eq!(account_info.excluded_subreddits, Some(vec!["rust".into()]));
+        assert_eq!(account_info.token, token());
+        let invalid = settings.replace("minimum_score = 5", "minimum_score = \"high\"");
         assert_eq!(
-            read_config_account_info(&test_username()).unwrap(),
-            fresh_account_info()
+            set_edited_settings(&test_username(), &invalid).is_err(),
+            true
         );
-        assert_eq!(delete_user(&test_username()).unwrap(), true);
+        assert_eq!(
+            read_config_account_info(&test_username())
+                .unwrap()
+                .minimum_score,
+            Some(7)
+        );
+        for broken in &[
+            "excluded_subreddits = [\"/(rust/\"]\n",
+            "match_patterns = [\"(acme\"]\n",
+            "rule = \"score <\"\n",
+        ] {
+            let edited = settings.clone() + *broken;
+            assert_eq!(
+                set_edited_settings(&test_username(), &edited).is_err(),
+                true
+            );
+        }
+        assert_eq!(
+            read_config_account_info(&test_username())
+                .unwrap()
+                .excluded_subreddits,
+            Some(vec!["rust".into()])
+        );
+        delete_user(&test_username()).unwrap();
     }
-
     #[test]
     #[serial]
-    fn test_no_duplicates() {
-        let before_config = get_config().unwrap();
-        let token1 = token();
-        let token2 = token();
-        save_token(test_username(), token1).unwrap();
-        save_token(test_username(), token2).unwrap();
-        let config = get_config().unwrap();
-        assert_eq!(config.accounts.len(), before_config.accounts.len() + 1);
+    #[cfg(unix)]
+    fn test_config_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        save_token(test_username(), token()).unwrap();
+        let mode = std::fs::metadata(config_file_path())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
         delete_user(&test_username()).unwrap();
     }
     #[test]
     #[serial]
-    fn test_refresh_user_token() {
-        const NEW_ACCESS_TOKEN: &'static str = "NEW_ACCESS_TOKEN";
+    fn test_set_last_run() {
         save_token(test_username(), token()).unwrap();
-        let mut new_token = token();
-        new_token.refresh_token = None;
-        new_token.access_token = NEW_ACCESS_TOKEN.into();
-        update_token(test_username(), new_token).unwrap();
+        set_last_run(test_username(), 1579506505).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.last_run, Some(1579506505));
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_reddit_app() {
+        save_token(test_username(), token()).unwrap();
+        let app = RedditApp {
+            client_id: String::from("my_client_id"),
+            client_secret: Some(String::from("my_secret")),
+            redirect_uri: None,
+        };
+        set_reddit_app(Some(app.clone())).unwrap();
+        assert_eq!(reddit_app(), Some(app));
+        // Removing an account keeps the app.
+        delete_user(&test_username()).unwrap();
+        assert_eq!(reddit_app().is_some(), true);
+        set_reddit_app(None).unwrap();
+        assert_eq!(reddit_app(), None);
+    }
+    #[test]
+    #[serial]
+    fn test_set_redirect_listener() {
+        set_redirect_listener(Some(String::from("127.0.0.1:8080"))).unwrap();
+        assert_eq!(redirect_listener(), Some(String::from("127.0.0.1:8080")));
+        set_redirect_listener(None).unwrap();
+        assert_eq!(redirect_listener(), None);
+    }
+    #[test]
+    fn test_token_status() {
+        let mut ai = fresh_account_info();
+        ai.token_expires = 1000;
+        assert_eq!(ai.token_status(100), TokenStatus::Valid);
+        assert_eq!(ai.token_status(900), TokenStatus::ExpiringSoon);
+        assert_eq!(ai.token_status(1000), TokenStatus::Expired);
+        ai.token.refresh_token = None;
+        assert_eq!(ai.token_status(100), TokenStatus::MissingRefreshToken);
+    }
+    #[test]
+    #[serial]
+    fn test_set_use_overview() {
+        save_token(test_username(), token()).unwrap();
+        set_use_overview(test_username(), true).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.use_overview, true);
+        set_use_overview(test_username(), false).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.use_overview, false);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_refresh_margin() {
+        save_token(test_username(), token()).unwrap();
+        set_refresh_margin(test_username(), Some(300)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.refresh_margin, Some(300));
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    fn test_needs_refresh() {
+        let mut ai = fresh_account_info();
+        ai.token_expires = 1000;
+        assert_eq!(ai.needs_refresh(900), false);
+        assert_eq!(ai.needs_refresh(940), true);
+        assert_eq!(ai.needs_refresh(1000), true);
+        ai.refresh_margin = Some(0);
+        assert_eq!(ai.needs_refresh(999), false);
+        ai.refresh_margin = Some(300);
+        assert_eq!(ai.needs_refresh(700), true);
+    }
+    #[test]
+    #[serial]
+    fn test_set_archive_api() {
+        save_token(test_username(), token()).unwrap();
+        set_archive_api(test_username(), Some("https://api.pushshift.io".into())).unwrap();
         let account_info = read_config_account_info(&test_username()).unwrap();
-        assert_eq!(account_info.token.access_token, NEW_ACCESS_TOKEN);
         assert_eq!(
-            account_info.token.refresh_token,
-            Some("REFRESH_TOKEN".into())
+            account_info.archive_api,
+            Some(String::from("https://api.pushshift.io"))
         );
+        set_archive_api(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.archive_api, None);
         delete_user(&test_username()).unwrap();
     }
     #[test]
     #[serial]
-    fn test_set_max_hours() {
+    fn test_set_combine_mode() {
         save_token(test_username(), token()).unwrap();
-        assert_eq!(set_max_hours(test_username(), 1).unwrap(), ());
+        set_combine_mode(test_username(), Some(CombineMode::Any)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.combine, Some(CombineMode::Any));
+        set_combine_mode(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.combine, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_keep_top_percent() {
+        save_token(test_username(), token()).unwrap();
+        set_keep_top_percent(test_username(), 10).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_top_percent, Some(10));
+        set_keep_top_percent(test_username(), 0).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_top_percent, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_keep_top_per_subreddit() {
+        save_token(test_username(), token()).unwrap();
+        set_keep_top_per_subreddit(test_username(), 3).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_top_per_subreddit, Some(3));
+        set_keep_top_per_subreddit(test_username(), 0).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keep_top_per_subreddit, None);
         delete_user(&test_username()).unwrap();
     }
     #[test]
@@ -357,6 +1893,56 @@ pub mod tests {
     }
     #[test]
     #[serial]
+    fn test_set_max_score() {
+        save_token(test_username(), token()).unwrap();
+        set_max_score(test_username(), Some(-5)).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.max_score, Some(-5));
+        set_max_score(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.max_score, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_smtp() {
+        save_token(test_username(), token()).unwrap();
+        let smtp = SmtpSettings {
+            server: "smtp.example.com".into(),
+            port: 587,
+            username: "me@example.com".into(),
+            from: "me@example.com".into(),
+            to: "me@example.com".into(),
+            failures_only: true,
+        };
+        set_smtp(test_username(), Some(smtp.clone())).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.smtp, Some(smtp));
+        set_smtp(test_username(), None).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.smtp, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_backup() {
+        save_token(test_username(), token()).unwrap();
+        set_backup(test_username(), true).unwrap();
+        set_backup_recipient(
+            test_username(),
+            Some(BackupRecipient::Age("age1recipient".into())),
+        )
+        .unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.backup, true);
+        assert_eq!(
+            account_info.backup_recipient,
+            Some(BackupRecipient::Age("age1recipient".into()))
+        );
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
     fn test_set_excluded_subreddits() {
         save_token(test_username(), token()).unwrap();
         assert_eq!(
@@ -389,4 +1975,91 @@ pub mod tests {
         assert_eq!(account_info.excluded_subreddits, None);
         delete_user(&test_username()).unwrap();
     }
+    #[test]
+    #[serial]
+    fn test_set_match_patterns() {
+        save_token(test_username(), token()).unwrap();
+        add_match_patterns(test_username(), vec!["(?i)employer", "my name"]).unwrap();
+        add_match_patterns(test_username(), vec!["my name"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.match_patterns,
+            Some(vec!["(?i)employer".into(), "my name".into()])
+        );
+        remove_match_patterns(test_username(), vec!["(?i)employer", "my name"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.match_patterns, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_keywords() {
+        save_token(test_username(), token()).unwrap();
+        add_keywords(test_username(), vec!["Acme", "John Doe"]).unwrap();
+        add_keywords(test_username(), vec!["acme"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.keywords,
+            Some(vec!["Acme".into(), "John Doe".into()])
+        );
+        remove_keywords(test_username(), vec!["ACME", "john doe"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.keywords, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_domains() {
+        save_token(test_username(), token()).unwrap();
+        add_kept_domains(test_username(), vec!["GitHub.com"]).unwrap();
+        add_deleted_domains(test_username(), vec!["imgur.com", "i.redd.it"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.kept_domains, Some(vec!["github.com".into()]));
+        assert_eq!(
+            account_info.deleted_domains,
+            Some(vec!["imgur.com".into(), "i.redd.it".into()])
+        );
+        remove_kept_domains(test_username(), vec!["github.com"]).unwrap();
+        remove_deleted_domains(test_username(), vec!["IMGUR.com"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.kept_domains, None);
+        assert_eq!(account_info.deleted_domains, Some(vec!["i.redd.it".into()]));
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_flairs() {
+        save_token(test_username(), token()).unwrap();
+        add_excluded_flairs(test_username(), vec!["keep", "/^OC/"]).unwrap();
+        add_included_flairs(test_username(), vec!["Meta"]).unwrap();
+        add_excluded_flairs(test_username(), vec!["keep"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.excluded_flairs,
+            Some(vec!["keep".into(), "/^OC/".into()])
+        );
+        assert_eq!(account_info.included_flairs, Some(vec!["Meta".into()]));
+        remove_excluded_flairs(test_username(), vec!["keep", "/^OC/"]).unwrap();
+        remove_included_flairs(test_username(), vec!["Meta"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.excluded_flairs, None);
+        assert_eq!(account_info.included_flairs, None);
+        delete_user(&test_username()).unwrap();
+    }
+    #[test]
+    #[serial]
+    fn test_set_included_subreddits() {
+        save_token(test_username(), token()).unwrap();
+        add_included_subreddits(test_username(), vec!["rust", "webdev"]).unwrap();
+        add_included_subreddits(test_username(), vec!["Rust"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(
+            account_info.included_subreddits,
+            Some(vec!["rust".into(), "webdev".into()])
+        );
+        remove_included_subreddits(test_username(), vec!["RUST", "webdev"]).unwrap();
+        let account_info = read_config_account_info(&test_username()).unwrap();
+        assert_eq!(account_info.included_subreddits, None);
+        delete_user(&test_username()).unwrap();
+    }
 }
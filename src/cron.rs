@@ -0,0 +1,143 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use std::result;
+
+/// A standard five field cron expression: minute, hour, day of month, month and day of
+/// week. Each field takes `*`, numbers, ranges like `1-5`, lists like `1,15` and steps
+/// like `*/15`. Day of week runs from 0 (Sunday) to 6, 7 is Sunday too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether day of month and day of week were both restricted. Cron then runs on
+    /// days that match either of them instead of both.
+    either_day: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> result::Result<Schedule, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Invalid schedule {}. Use five fields like \"0 3 * * *\" (minute hour day month weekday).",
+                expression
+            ));
+        }
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Schedule {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            either_day: !fields[2].starts_with('*') && !fields[4].starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule runs on this date, whatever the time.
+    pub fn matches_day(&self, time: &NaiveDateTime) -> bool {
+        if !has(self.months, time.month()) {
+            return false;
+        }
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// Whether the schedule runs at this minute.
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        self.matches_day(time) && has(self.hours, time.hour()) && has(self.minutes, time.minute())
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// The values a field allows, as bits of a mask.
+fn parse_field(field: &str, min: u32, max: u32) -> result::Result<u64, String> {
+    let invalid = || format!("Invalid schedule field {}.", field);
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(i) => (
+                &part[..i],
+                part[i + 1..].parse::<u32>().map_err(|_| invalid())?,
+            ),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some(i) = range.find('-') {
+            let start = range[..i].parse::<u32>().map_err(|_| invalid())?;
+            let end = range[i + 1..].parse::<u32>().map_err(|_| invalid())?;
+            (start, end)
+        } else {
+            let value = range.parse::<u32>().map_err(|_| invalid())?;
+            // `5/10` means every 10th value starting at 5.
+            if part.contains('/') {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "Invalid schedule field {}, values go from {} to {}.",
+                field, min, max
+            ));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2020-06-01 was a Monday.
+        NaiveDate::from_ymd(2020, 6, day).and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let daily = Schedule::parse("0 3 * * *").unwrap();
+        assert_eq!(daily.matches(&at(1, 3, 0)), true);
+        assert_eq!(daily.matches(&at(1, 3, 1)), false);
+        let weekdays = Schedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert_eq!(weekdays.matches(&at(1, 9, 45)), true);
+        assert_eq!(weekdays.matches(&at(1, 9, 50)), false);
+        assert_eq!(weekdays.matches(&at(6, 10, 0)), false);
+        let sundays = Schedule::parse("30 2 * * 7").unwrap();
+        assert_eq!(sundays.matches(&at(7, 2, 30)), true);
+        assert_eq!(Schedule::parse("0 3 * *").is_err(), true);
+        assert_eq!(Schedule::parse("60 3 * * *").is_err(), true);
+        assert_eq!(Schedule::parse("*/0 3 * * *").is_err(), true);
+    }
+
+    #[test]
+    fn test_day_of_month_or_week() {
+        // The 16th or any Monday.
+        let schedule = Schedule::parse("0 0 16 * 1").unwrap();
+        assert_eq!(schedule.matches_day(&at(16, 0, 0)), true);
+        assert_eq!(schedule.matches_day(&at(8, 0, 0)), true);
+        assert_eq!(schedule.matches_day(&at(9, 0, 0)), false);
+    }
+}
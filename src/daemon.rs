@@ -0,0 +1,107 @@
+use std::io;
+use std::time::Duration;
+
+/// Why a wait in `redelete schedule` or `redelete daemon` ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wake {
+    /// The next scheduled run is due.
+    Due,
+    /// SIGHUP, read the config again.
+    Reload,
+    /// Ctrl-C or SIGTERM.
+    Stop,
+}
+
+/// The signals a long running process reacts to. Only Ctrl-C is handled outside unix.
+pub struct Signals {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    #[cfg(unix)]
+    pub fn new() -> io::Result<Signals> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Signals {
+            hangup: signal(SignalKind::hangup())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new() -> io::Result<Signals> {
+        Ok(Signals {})
+    }
+
+    /// Sleeps for `delay`, or until a signal when it's `None`.
+    pub async fn wait(&mut self, delay: Option<Duration>) -> Wake {
+        let sleep = async {
+            match delay {
+                Some(delay) => tokio::time::delay_for(delay).await,
+                None => futures::future::pending::<()>().await,
+            }
+        };
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = sleep => Wake::Due,
+                _ = self.hangup.recv() => Wake::Reload,
+                _ = self.terminate.recv() => Wake::Stop,
+                _ = tokio::signal::ctrl_c() => Wake::Stop,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            tokio::select! {
+                _ = sleep => Wake::Due,
+                _ = tokio::signal::ctrl_c() => Wake::Stop,
+            }
+        }
+    }
+}
+
+/// Tells systemd about the service, e.g. "READY=1", when it was started with
+/// `Type=notify`. Does nothing otherwise. Abstract `@` sockets aren't supported, systemd
+/// only uses those inside containers.
+#[cfg(unix)]
+pub fn notify(state: &str) {
+    use std::os::unix::net::UnixDatagram;
+    let path = match std::env::var_os("NOTIFY_SOCKET") {
+        Some(path) => path,
+        None => return,
+    };
+    if path.to_string_lossy().starts_with('@') {
+        return;
+    }
+    if let Ok(socket) = UnixDatagram::unbound() {
+        let _ = socket.send_to(state.as_bytes(), path);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn notify(_state: &str) {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    #[serial]
+    fn test_notify() {
+        let mut path = std::env::temp_dir();
+        path.push("redelete-test-notify.sock");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixDatagram::bind(&path).unwrap();
+        std::env::set_var("NOTIFY_SOCKET", &path);
+        notify("READY=1");
+        std::env::remove_var("NOTIFY_SOCKET");
+        let mut buf = [0; 16];
+        let n = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
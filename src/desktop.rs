@@ -0,0 +1,25 @@
+use super::report::RunSummary;
+#[cfg(feature = "desktop-notifications")]
+use notify_rust::Notification;
+
+/// Shows a native desktop notification with the run's outcome, so background runs are
+/// noticed without reading their logs.
+#[cfg(feature = "desktop-notifications")]
+pub fn notify(summary: &RunSummary) -> Result<(), String> {
+    Notification::new()
+        .appname("redelete")
+        .summary(&summary.subject())
+        .body(&summary.headline())
+        .show()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Notifications need dbus on linux, so they're left out of builds without the
+/// `desktop-notifications` feature.
+#[cfg(not(feature = "desktop-notifications"))]
+pub fn notify(_summary: &RunSummary) -> Result<(), String> {
+    Err(String::from(
+        "redelete was built without the desktop-notifications feature",
+    ))
+}
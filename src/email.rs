@@ -0,0 +1,57 @@
+use super::config::SmtpSettings;
+use super::report::RunSummary;
+use custom_error::custom_error;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::env;
+use std::result;
+
+const PASSWORD_ENV_VAR: &'static str = "REDELETE_SMTP_PASSWORD";
+const KEYRING_SERVICE: &'static str = "redelete-smtp";
+
+custom_error! {pub EmailError
+    Address{source: lettre::address::AddressError} = "Invalid email address",
+    Message{source: lettre::error::Error} = "Unable to build email",
+    Smtp{source: lettre::transport::smtp::Error} = "SMTP error",
+    Task{source: tokio::task::JoinError} = "Email task failed",
+    MissingPassword{user: String} = "No SMTP password for {user}. Set REDELETE_SMTP_PASSWORD or store it in the system keyring under the service redelete-smtp."
+}
+pub type Result<T> = result::Result<T, EmailError>;
+
+/// The environment variable wins over the keyring so headless setups don't need a keyring daemon.
+fn smtp_password(user: &str) -> Result<String> {
+    if let Ok(password) = env::var(PASSWORD_ENV_VAR) {
+        return Ok(password);
+    }
+    keyring::Entry::new(KEYRING_SERVICE, user)
+        .get_password()
+        .map_err(|_| EmailError::MissingPassword {
+            user: String::from(user),
+        })
+}
+
+/// lettre's SMTP transport and the keyring both block, so the email is sent from tokio's
+/// blocking pool rather than on a runtime thread.
+pub async fn send_report(settings: &SmtpSettings, summary: &RunSummary) -> Result<()> {
+    let settings = settings.clone();
+    let (subject, body) = (summary.subject(), summary.body());
+    tokio::task::spawn_blocking(move || send(&settings, subject, body)).await?
+}
+
+fn send(settings: &SmtpSettings, subject: String, body: String) -> Result<()> {
+    let email = Message::builder()
+        .from(settings.from.parse()?)
+        .to(settings.to.parse()?)
+        .subject(subject)
+        .body(body)?;
+    let credentials = Credentials::new(
+        String::from(&settings.username),
+        smtp_password(&settings.username)?,
+    );
+    let mailer = SmtpTransport::starttls_relay(&settings.server)?
+        .port(settings.port)
+        .credentials(credentials)
+        .build();
+    mailer.send(&email)?;
+    Ok(())
+}
@@ -0,0 +1,372 @@
+use super::output::preview;
+use super::reddit_api::{DeletionInfo, RedditPost, Thing, UserListEntry};
+use chrono::{TimeZone, Utc};
+use custom_error::custom_error;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use std::result;
+
+custom_error! {pub ExportError
+    Csv{source: csv::Error} = "Unable to write csv",
+    Serde{source: serde_json::Error} = "Unable to read or write json",
+    Sqlite{source: rusqlite::Error} = "Unable to write the sqlite database",
+    IO{source: std::io::Error} = "Unable to read or write the export file",
+}
+pub type Result<T> = result::Result<T, ExportError>;
+
+/// One matched item, as it's written to an export file.
+#[derive(Serialize, Debug, PartialEq)]
+struct Row {
+    fullname: String,
+    subreddit: String,
+    /// RFC 3339 in UTC.
+    date: String,
+    score: i32,
+    preview: String,
+    permalink: String,
+}
+
+impl Row {
+    fn new(info: &DeletionInfo, preview_length: Option<usize>) -> Row {
+        let text = info
+            .body
+            .as_ref()
+            .or(info.title.as_ref())
+            .map(|text| preview(text, preview_length))
+            .unwrap_or_default();
+        Row {
+            fullname: String::from(&info.name),
+            subreddit: String::from(&info.subreddit),
+            date: Utc.timestamp(info.created_utc as i64, 0).to_rfc3339(),
+            score: info.score,
+            preview: text,
+            permalink: if info.permalink.is_empty() {
+                String::new()
+            } else {
+                format!("https://www.reddit.com{}", info.permalink)
+            },
+        }
+    }
+}
+
+/// Writes `items` to `path`, as json when it ends in `.json` and csv otherwise.
+pub fn export(path: &Path, items: &[DeletionInfo], preview_length: Option<usize>) -> Result<()> {
+    let rows: Vec<Row> = items.iter().map(|i| Row::new(i, preview_length)).collect();
+    write_rows(path, &rows)
+}
+
+/// Writes a friends or blocked list to `path`, as json if it ends in .json and csv otherwise.
+pub fn export_users(path: &Path, users: &[UserListEntry]) -> Result<()> {
+    write_rows(path, users)
+}
+
+fn write_rows<T: Serialize>(path: &Path, rows: &[T]) -> Result<()> {
+    let is_json = path
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        let json = serde_json::to_string_pretty(&rows)?;
+        File::create(path)?.write_all(json.as_bytes())?;
+    } else {
+        let mut writer = csv::Writer::from_path(path)?;
+        for row in rows {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// File formats `redelete export` writes a whole history in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Json,
+    Csv,
+    Sqlite,
+}
+
+impl ArchiveFormat {
+    pub fn parse(format: &str) -> Option<ArchiveFormat> {
+        match format {
+            "json" => Some(ArchiveFormat::Json),
+            "csv" => Some(ArchiveFormat::Csv),
+            "sqlite" => Some(ArchiveFormat::Sqlite),
+            _ => None,
+        }
+    }
+}
+
+/// Writes every field of every item in `history` to `path`. Csv files get a column for
+/// each field any item has, with nested values like awards written as json.
+pub fn export_history(
+    path: &Path,
+    history: &[Map<String, Value>],
+    format: ArchiveFormat,
+) -> Result<()> {
+    match format {
+        ArchiveFormat::Json => {
+            let json = serde_json::to_string_pretty(history)?;
+            File::create(path)?.write_all(json.as_bytes())?;
+        }
+        ArchiveFormat::Csv => {
+            let columns: BTreeSet<&String> = history.iter().flat_map(|item| item.keys()).collect();
+            let mut writer = csv::Writer::from_path(path)?;
+            writer.write_record(&columns)?;
+            for item in history {
+                writer.write_record(columns.iter().map(|column| cell(item.get(*column))))?;
+            }
+            writer.flush()?;
+        }
+        ArchiveFormat::Sqlite => export_sqlite(path, history)?,
+    }
+    Ok(())
+}
+
+/// The comments and submissions in a json file from `export_history`.
+pub fn read_history(path: &Path) -> Result<Vec<DeletionInfo>> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    let things: Vec<Thing> = serde_json::from_str(&contents)?;
+    Ok(things.iter().map(|thing| thing.deletion_info()).collect())
+}
+
+/// Comments and submissions get their own tables, keyed by fullname like the audit log,
+/// with every field in the `json` column. Exporting into an existing database adds new
+/// items and updates the ones already in it.
+fn export_sqlite(path: &Path, history: &[Map<String, Value>]) -> Result<()> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS comments (
+            name TEXT PRIMARY KEY,
+            subreddit TEXT NOT NULL,
+            created_utc INTEGER NOT NULL,
+            score INTEGER,
+            body TEXT,
+            link_id TEXT,
+            parent_id TEXT,
+            permalink TEXT,
+            json TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS posts (
+            name TEXT PRIMARY KEY,
+            subreddit TEXT NOT NULL,
+            created_utc INTEGER NOT NULL,
+            score INTEGER,
+            title TEXT,
+            selftext TEXT,
+            url TEXT,
+            num_comments INTEGER,
+            permalink TEXT,
+            json TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS comments_subreddit ON comments (subreddit);
+        CREATE INDEX IF NOT EXISTS comments_created_utc ON comments (created_utc);
+        CREATE INDEX IF NOT EXISTS posts_subreddit ON posts (subreddit);
+        CREATE INDEX IF NOT EXISTS posts_created_utc ON posts (created_utc);",
+    )?;
+    let tx = conn.transaction()?;
+    for item in history {
+        let text = |key: &str| item.get(key).and_then(Value::as_str);
+        let number = |key: &str| item.get(key).and_then(Value::as_f64).map(|n| n as i64);
+        let name = text("name").unwrap_or_default();
+        let subreddit = text("subreddit").unwrap_or_default();
+        let created_utc = number("created_utc").unwrap_or(0);
+        let json = serde_json::to_string(item)?;
+        if name.starts_with("t1_") {
+            tx.execute(
+                "INSERT OR REPLACE INTO comments
+                 (name, subreddit, created_utc, score, body, link_id, parent_id, permalink, json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    name,
+                    subreddit,
+                    created_utc,
+                    number("score"),
+                    text("body"),
+                    text("link_id"),
+                    text("parent_id"),
+                    text("permalink"),
+                    json
+                ],
+            )?;
+        } else {
+            tx.execute(
+                "INSERT OR REPLACE INTO posts
+                 (name, subreddit, created_utc, score, title, selftext, url, num_comments, permalink, json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    name,
+                    subreddit,
+                    created_utc,
+                    number("score"),
+                    text("title"),
+                    text("selftext"),
+                    text("url"),
+                    number("num_comments"),
+                    text("permalink"),
+                    json
+                ],
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => String::from(s),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_data;
+    use super::*;
+
+    fn comment(body: &str) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: "t1_abc".into(),
+            created_utc: 1579506505.0,
+            subreddit: "rust".into(),
+            score: 3,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some(body.into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: "/r/rust/comments/x/y/abc/".into(),
+        }
+    }
+
+    #[test]
+    fn test_export_csv_and_json() {
+        let items = vec![comment("first, with \"quotes\"\nand a newline")];
+        let mut csv_path = std::env::temp_dir();
+        csv_path.push("redelete-test-export.csv");
+        export(&csv_path, &items, Some(5)).unwrap();
+        let mut reader = csv::Reader::from_path(&csv_path).unwrap();
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(&record[0], "t1_abc");
+        assert_eq!(&record[2], "2020-01-20T07:48:25+00:00");
+        assert_eq!(&record[4], "first…");
+        assert_eq!(
+            &record[5],
+            "https://www.reddit.com/r/rust/comments/x/y/abc/"
+        );
+        std::fs::remove_file(&csv_path).unwrap();
+
+        let mut json_path = std::env::temp_dir();
+        json_path.push("redelete-test-export.json");
+        export(&json_path, &items, None).unwrap();
+        let contents = std::fs::read_to_string(&json_path).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(rows[0]["score"], 3);
+        assert_eq!(rows[0]["preview"], "first, with \"quotes\"\nand a newline");
+        std::fs::remove_file(&json_path).unwrap();
+    }
+
+    #[test]
+    fn test_export_history() {
+        let history: Vec<Map<String, Value>> = vec![
+            serde_json::from_str(r#"{"name": "t1_a", "body": "hi", "gildings": {"gid_1": 1}}"#)
+                .unwrap(),
+            serde_json::from_str(r#"{"name": "t3_b", "title": "a post", "edited": null}"#).unwrap(),
+        ];
+        let mut csv_path = std::env::temp_dir();
+        csv_path.push("redelete-test-history.csv");
+        export_history(&csv_path, &history, ArchiveFormat::Csv).unwrap();
+        let mut reader = csv::Reader::from_path(&csv_path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["body", "edited", "gildings", "name", "title"]
+        );
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records[0], vec!["hi", "", r#"{"gid_1":1}"#, "t1_a", ""]);
+        assert_eq!(records[1], vec!["", "", "", "t3_b", "a post"]);
+        std::fs::remove_file(&csv_path).unwrap();
+
+        let mut json_path = std::env::temp_dir();
+        json_path.push("redelete-test-history.json");
+        export_history(&json_path, &history, ArchiveFormat::Json).unwrap();
+        let contents = std::fs::read_to_string(&json_path).unwrap();
+        let written: Vec<Map<String, Value>> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(written, history);
+        std::fs::remove_file(&json_path).unwrap();
+    }
+
+    #[test]
+    fn test_read_history() {
+        let history: Vec<Value> = test_data::comments()
+            .iter()
+            .chain(test_data::posts().iter())
+            .map(|item| serde_json::from_str::<Value>(item).unwrap()["data"].clone())
+            .collect();
+        let mut path = std::env::temp_dir();
+        path.push("redelete-test-read-history.json");
+        std::fs::write(&path, serde_json::to_string(&history).unwrap()).unwrap();
+        let items = read_history(&path).unwrap();
+        assert_eq!(items.len(), history.len());
+        assert_eq!(items[0].name, "t1_ff2hmdi");
+        assert_eq!(items.iter().any(|item| item.title.is_some()), true);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_export_history_sqlite() {
+        let history: Vec<Map<String, Value>> = vec![
+            serde_json::from_str(
+                r#"{"name": "t1_a", "subreddit": "rust", "created_utc": 1579506505.0, "score": 2, "body": "hi"}"#,
+            )
+            .unwrap(),
+            serde_json::from_str(
+                r#"{"name": "t3_b", "subreddit": "rust", "created_utc": 1579506000.0, "title": "a post", "num_comments": 4}"#,
+            )
+            .unwrap(),
+        ];
+        let mut path = std::env::temp_dir();
+        path.push("redelete-test-history.sqlite");
+        let _ = std::fs::remove_file(&path);
+        export_history(&path, &history, ArchiveFormat::Sqlite).unwrap();
+        // Exporting again updates the rows instead of adding more.
+        export_history(&path, &history, ArchiveFormat::Sqlite).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let (body, created): (String, i64) = conn
+            .query_row(
+                "SELECT body, created_utc FROM comments WHERE subreddit = 'rust'",
+                rusqlite::NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(body, "hi");
+        assert_eq!(created, 1579506505);
+        let (title, comments): (String, i64) = conn
+            .query_row(
+                "SELECT title, num_comments FROM posts",
+                rusqlite::NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "a post");
+        assert_eq!(comments, 4);
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
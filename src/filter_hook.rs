@@ -0,0 +1,92 @@
+use super::reddit_api::DeletionInfo;
+use custom_error::custom_error;
+use std::io::prelude::*;
+use std::io::ErrorKind;
+use std::process::{Command, Stdio};
+use std::result;
+
+custom_error! {pub FilterHookError
+    Serde{source: serde_json::Error} = "Serde parsing error",
+    IO{source: std::io::Error} = "IO Error",
+}
+pub type Result<T> = result::Result<T, FilterHookError>;
+
+/// `command` run through the platform's shell.
+#[cfg(not(windows))]
+pub fn shell(command: &str) -> Command {
+    let mut c = Command::new("sh");
+    c.arg("-c").arg(command);
+    c
+}
+
+#[cfg(windows)]
+pub fn shell(command: &str) -> Command {
+    let mut c = Command::new("cmd");
+    c.arg("/C").arg(command);
+    c
+}
+
+/// Runs the user's filter command with the item as json on stdin. The item may only be
+/// deleted if the command exits with status 0.
+pub fn allows(command: &str, info: &DeletionInfo) -> Result<bool> {
+    let json = serde_json::to_vec(info)?;
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    match child
+        .stdin
+        .take()
+        .expect("Unable to open filter command stdin.")
+        .write_all(&json)
+    {
+        // The command decided without reading the whole item.
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => (),
+        Err(e) => return Err(e.into()),
+        Ok(()) => (),
+    }
+    Ok(child.wait()?.success())
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    fn comment(body: &str) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: "t1_abc".into(),
+            created_utc: 0.0,
+            subreddit: "rust".into(),
+            score: 1,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some(body.into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_exit_status_decides() {
+        assert_eq!(allows("true", &comment("")).unwrap(), true);
+        assert_eq!(allows("false", &comment("")).unwrap(), false);
+    }
+
+    #[test]
+    fn test_item_is_piped_as_json() {
+        let command = "grep -q '\"body\":\"delete me\"'";
+        assert_eq!(allows(command, &comment("delete me")).unwrap(), true);
+        assert_eq!(allows(command, &comment("keep me")).unwrap(), false);
+    }
+}
@@ -0,0 +1,115 @@
+use custom_error::custom_error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::result;
+
+custom_error! {pub GdprError
+    Csv{source: csv::Error} = "Unable to read the export's csv",
+    Zip{source: zip::result::ZipError} = "Unable to read the export zip",
+    IO{source: std::io::Error} = "Unable to open the data export",
+    UnknownFile{file: String} = "{file} isn't a reddit data export zip, comments.csv or posts.csv",
+    MissingId{file: String} = "{file} has no id column",
+}
+pub type Result<T> = result::Result<T, GdprError>;
+
+/// Fullnames of every comment and then every submission in a reddit data export, from
+/// https://www.reddit.com/settings/data-request. Takes the zip or its comments.csv or
+/// posts.csv.
+pub fn read_fullnames(path: &Path) -> Result<Vec<String>> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if file_name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let mut fullnames = Vec::new();
+        for (csv_name, prefix) in &[("comments.csv", "t1_"), ("posts.csv", "t3_")] {
+            match archive.by_name(csv_name) {
+                Ok(csv) => fullnames.extend(read_ids(csv, csv_name, prefix)?),
+                Err(zip::result::ZipError::FileNotFound) => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        return Ok(fullnames);
+    }
+    let prefix = if file_name.starts_with("comments") {
+        "t1_"
+    } else if file_name.starts_with("posts") {
+        "t3_"
+    } else {
+        return Err(GdprError::UnknownFile {
+            file: path.display().to_string(),
+        });
+    };
+    read_ids(File::open(path)?, &file_name, prefix)
+}
+
+/// The `id` column of an export csv, turned into fullnames with `prefix`.
+fn read_ids<R: Read>(reader: R, file_name: &str, prefix: &str) -> Result<Vec<String>> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let column = reader
+        .headers()?
+        .iter()
+        .position(|header| header == "id")
+        .ok_or(GdprError::MissingId {
+            file: String::from(file_name),
+        })?;
+    let mut fullnames = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        if let Some(id) = record.get(column).filter(|id| !id.is_empty()) {
+            fullnames.push(format!("{}{}", prefix, id));
+        }
+    }
+    Ok(fullnames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const COMMENTS_CSV: &'static str = "id,permalink,date,ip,subreddit,gildings,link,parent,body,media\n\
+        ff2hmdi,https://www.reddit.com/r/webdev/comments/er8pzu/x/ff2hmdi/,2020-01-20 07:48:25 UTC,,webdev,0,https://www.reddit.com/r/webdev/comments/er8pzu/x/,,\"Webstorm, mostly\",\n\
+        fdo8iyy,https://www.reddit.com/r/webdev/comments/emcnha/x/fdo8iyy/,2020-01-09 20:12:13 UTC,,webdev,0,https://www.reddit.com/r/webdev/comments/emcnha/x/,,I love rust,\n";
+    const POSTS_CSV: &'static str = "id,permalink,date,ip,subreddit,gildings,title,url,body\n\
+        er8pzu,https://www.reddit.com/r/webdev/comments/er8pzu/x/,2020-01-20 01:00:00 UTC,,webdev,0,Tools,,\n";
+
+    #[test]
+    fn test_read_csv() {
+        let mut path = std::env::temp_dir();
+        path.push("comments.csv");
+        std::fs::write(&path, COMMENTS_CSV).unwrap();
+        assert_eq!(
+            read_fullnames(&path).unwrap(),
+            vec![String::from("t1_ff2hmdi"), String::from("t1_fdo8iyy")]
+        );
+        std::fs::remove_file(&path).unwrap();
+        let mut other = std::env::temp_dir();
+        other.push("redelete-test-ips.csv");
+        assert_eq!(read_fullnames(&other).is_err(), true);
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let mut path = std::env::temp_dir();
+        path.push("redelete-test-export.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("posts.csv", options).unwrap();
+        zip.write_all(POSTS_CSV.as_bytes()).unwrap();
+        zip.start_file("comments.csv", options).unwrap();
+        zip.write_all(COMMENTS_CSV.as_bytes()).unwrap();
+        zip.finish().unwrap();
+        assert_eq!(
+            read_fullnames(&path).unwrap(),
+            vec![
+                String::from("t1_ff2hmdi"),
+                String::from("t1_fdo8iyy"),
+                String::from("t3_er8pzu")
+            ]
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use super::config::config_dir;
+use custom_error::custom_error;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+use serial_test::serial;
+
+custom_error! {pub JournalError
+    Serde{source: serde_json::Error} = "Serde parsing error",
+    IO{source: std::io::Error} = "IO Error",
+}
+pub type Result<T> = result::Result<T, JournalError>;
+
+/// A deletion that reddit did not accept, kept around so `redelete retry` can try it again.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FailedDeletion {
+    pub name: String,
+    pub subreddit: String,
+    pub error: String,
+    pub failed_at: u64,
+}
+
+impl FailedDeletion {
+    pub fn new(name: String, subreddit: String, error: String) -> FailedDeletion {
+        FailedDeletion {
+            name,
+            subreddit,
+            error,
+            failed_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Couldn't get systemtime")
+                .as_secs(),
+        }
+    }
+}
+
+fn journal_file_path(username: &str) -> PathBuf {
+    let mut path = config_dir();
+    path.push("journal");
+    std::fs::create_dir_all(&path).expect("Unable to create journal directory.");
+    path.push(format!("{}.json", username));
+    path
+}
+
+pub fn read_failures(username: &str) -> Result<Vec<FailedDeletion>> {
+    let path = journal_file_path(username);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut contents = String::new();
+    File::open(&path)?.read_to_string(&mut contents)?;
+    if contents.trim().is_empty() {
+        Ok(Vec::new())
+    } else {
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// Overwrites the journal for `username`. An empty list removes the journal file.
+pub fn replace_failures(username: &str, failures: Vec<FailedDeletion>) -> Result<()> {
+    let path = journal_file_path(username);
+    if failures.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    let json = serde_json::to_string(&failures)?;
+    File::create(path)?.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Adds failures to the journal, replacing older entries for the same fullname.
+pub fn record_failures(username: &str, failures: Vec<FailedDeletion>) -> Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    let mut journal: Vec<FailedDeletion> = read_failures(username)?
+        .into_iter()
+        .filter(|old| !failures.iter().any(|new| new.name == old.name))
+        .collect();
+    journal.extend(failures);
+    replace_failures(username, journal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TEST_USER: &'static str = "JournalTestUser";
+
+    fn failure(name: &str) -> FailedDeletion {
+        FailedDeletion::new(name.into(), "subreddit".into(), "Reqwest error".into())
+    }
+
+    #[test]
+    #[serial]
+    fn test_record_and_replace_failures() {
+        replace_failures(TEST_USER, vec![]).unwrap();
+        assert_eq!(read_failures(TEST_USER).unwrap().is_empty(), true);
+        record_failures(TEST_USER, vec![failure("t1_a"), failure("t3_b")]).unwrap();
+        record_failures(TEST_USER, vec![failure("t1_a")]).unwrap();
+        let names: Vec<String> = read_failures(TEST_USER)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(names, vec![String::from("t3_b"), String::from("t1_a")]);
+        replace_failures(TEST_USER, vec![]).unwrap();
+        assert_eq!(journal_file_path(TEST_USER).exists(), false);
+    }
+}
@@ -1,22 +1,96 @@
 #[macro_use]
 extern crate clap;
 
-use futures::future::join_all;
-use futures::try_join;
+use futures::stream::StreamExt;
 use std::result;
 use tokio;
+#[macro_use]
+mod output;
+mod archive;
+mod audit;
+mod backup;
 mod config;
+mod cron;
+mod daemon;
+mod desktop;
+mod email;
+mod export;
+mod filter_hook;
+mod gdpr;
+mod journal;
 mod oauth_server;
+mod pacing;
+mod patterns;
+mod plan;
+mod pushshift;
+mod ratelimit;
 mod reddit_api;
-use clap::{App, Arg};
+mod remote_backup;
+mod report;
+mod restore;
+mod rules;
+mod run_hook;
+mod sample;
+mod script;
+mod search;
+mod shred;
+mod state;
+mod stats;
+mod timezone;
+mod webhook;
+use clap::{App, AppSettings, Arg};
+use colored::Colorize;
 use custom_error::custom_error;
+use serde_json::json;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::prelude::*;
+use std::path::Path;
 use std::time;
 
 #[cfg(test)]
 mod test_data;
 
 const MIN_SCORE: &'static str = "min_score";
+const MAX_SCORE: &'static str = "max_score";
+const NO_MAX_SCORE: &'static str = "no_max_score";
 const MAX_HOURS: &'static str = "max_hours";
+const KEEP_LATEST: &'static str = "keep_latest";
+const KEEP_TOP: &'static str = "keep_top";
+const KEEP_TOP_PERCENT: &'static str = "keep_top_percent";
+const KEEP_DISTINGUISHED: &'static str = "keep_distinguished";
+const DELETE_DISTINGUISHED: &'static str = "delete_distinguished";
+const EDITED: &'static str = "edited";
+const ARCHIVED: &'static str = "archived";
+const COMBINE: &'static str = "combine";
+const CONCURRENCY: &'static str = "concurrency";
+const SHRED: &'static str = "shred";
+const FORMAT: &'static str = "format";
+const QUIET: &'static str = "quiet";
+const NO_PAGER: &'static str = "no_pager";
+const NO_COLOR: &'static str = "no_color";
+const PREVIEW_LENGTH: &'static str = "preview_length";
+const EXPORT: &'static str = "export";
+const DEFAULT_PREVIEW_LENGTH: &'static str = "280";
+const VERBOSE: &'static str = "verbose";
+const SHRED_TEXT: &'static str = "shred_text";
+const SHRED_PASSES: &'static str = "shred_passes";
+const SHRED_DELAY: &'static str = "shred_delay";
+const POST_TYPE: &'static str = "post_type";
+const ANY_POST_TYPE: &'static str = "any_post_type";
+const INCLUDE_QUARANTINED: &'static str = "include_quarantined";
+const SKIP_QUARANTINED: &'static str = "skip_quarantined";
+const OVERVIEW: &'static str = "overview";
+const SEPARATE_LISTINGS: &'static str = "separate_listings";
+const CONTROVERSIAL: &'static str = "controversial";
+const RULE: &'static str = "rule";
+const NO_RULE: &'static str = "no_rule";
+const FILTER_COMMAND: &'static str = "filter_command";
+const NO_FILTER_COMMAND: &'static str = "no_filter_command";
+const SCRIPT: &'static str = "script";
+const NO_SCRIPT: &'static str = "no_script";
+const KEEP_STICKIED: &'static str = "keep_stickied";
+const DELETE_STICKIED: &'static str = "delete_stickied";
 const ADD_EXCLUDED_SUBREDDITS: &'static str = "add_excluded";
 const REMOVE_EXCLUDED_SUBREDDITS: &'static str = "remove_excluded";
 const USERNAME: &'static str = "username";
@@ -25,366 +99,7164 @@ const VIEW: &'static str = "view";
 const RUN: &'static str = "run";
 const DRYRUN: &'static str = "dry_run";
 const FORGET_ACCOUNT: &'static str = "forget";
+const CLIENT_ID: &'static str = "client_id";
+const CLIENT_SECRET: &'static str = "client_secret";
+const CLIENT_SECRET_ENV_VAR: &'static str = "REDELETE_CLIENT_SECRET";
+const REDIRECT_URI: &'static str = "redirect_uri";
+const DEFAULT_APP: &'static str = "default_app";
+const REAUTHORIZE: &'static str = "reauthorize";
+const LISTEN: &'static str = "listen";
+const DEFAULT_LISTEN: &'static str = "default_listen";
+// Named after their features in `reddit_api::FEATURE_SCOPES`.
+const WITH_SAVED: &'static str = "saved";
+const WITH_VOTES: &'static str = "votes";
+const WITH_HIDDEN: &'static str = "hidden";
+const WITH_SUBSCRIPTIONS: &'static str = "subscriptions";
+const WITH_MESSAGES: &'static str = "messages";
+const WITH_POSTING: &'static str = "posting";
+const RETRY: &'static str = "retry";
+const PLAN: &'static str = "plan";
+const PLAN_OUTPUT: &'static str = "output";
+const APPLY: &'static str = "apply";
+const PLAN_FILE: &'static str = "plan_file";
+const VERIFY_LOG: &'static str = "verify-log";
+const STATS: &'static str = "stats";
+const PURGE_SAVED: &'static str = "purge-saved";
+const PURGE_VOTES: &'static str = "purge-votes";
+const VOTES: &'static str = "votes";
+const PURGE_HIDDEN: &'static str = "purge-hidden";
+const HIDE_OWN: &'static str = "hide_own";
+const UNSUBSCRIBE_ALL: &'static str = "unsubscribe-all";
+const KEEP_SUBSCRIPTIONS: &'static str = "keep_subscriptions";
+const MULTIS: &'static str = "multis";
+const DELETE_ALL: &'static str = "delete_all";
+const FRIENDS: &'static str = "friends";
+const REMOVE_ALL: &'static str = "remove_all";
+const BLOCKED: &'static str = "blocked";
+const UNBLOCK_ALL: &'static str = "unblock_all";
+const WIPE: &'static str = "wipe";
+const WIPE_BACKUP: &'static str = "backup";
+const ACCOUNTS: &'static str = "accounts";
+const LOGOUT: &'static str = "logout";
+const WHOAMI: &'static str = "whoami";
+const PREVIEW: &'static str = "preview";
+const EDIT: &'static str = "edit";
+const CONFIG_EXPORT: &'static str = "export";
+const CONFIG_IMPORT: &'static str = "import";
+const INCLUDE_TOKENS: &'static str = "include_tokens";
+const IMPORT_FILE: &'static str = "file";
+const SIMULATE: &'static str = "simulate";
+const SIMULATE_INPUT: &'static str = "input";
+const SAMPLE: &'static str = "sample";
+const RESTORE: &'static str = "restore";
+const RESTORE_FROM: &'static str = "from";
+const RESTORE_IDS: &'static str = "ids";
+const TRASH: &'static str = "trash";
+const PURGE_AFTER: &'static str = "purge_after";
+const UNMARK: &'static str = "unmark";
+const OFFLINE: &'static str = "offline";
+const OFFLINE_ARCHIVE: &'static str = "archive";
+const SEARCH: &'static str = "search";
+const EXPORT_COMMAND: &'static str = "export";
+const EXPORT_OUTPUT: &'static str = "output";
+const GROUP_BY: &'static str = "group_by";
+const QUERY: &'static str = "query";
+const SEARCH_REGEX: &'static str = "regex";
+const SEARCH_SUBREDDITS: &'static str = "search_subreddits";
+const AFTER: &'static str = "after";
+const BEFORE: &'static str = "before";
+const KEEP: &'static str = "keep";
+const SCHEDULE: &'static str = "schedule";
+const DAEMON: &'static str = "daemon";
+const FULLNAMES: &'static str = "fullnames";
+const UNKEEP: &'static str = "unkeep";
+const SMTP_SERVER: &'static str = "smtp_server";
+const SMTP_USER: &'static str = "smtp_user";
+const EMAIL_FROM: &'static str = "email_from";
+const EMAIL_TO: &'static str = "email_to";
+const NO_EMAIL: &'static str = "no_email";
+const EMAIL_ON: &'static str = "email_on";
+const DEFAULT_SMTP_PORT: u16 = 587;
+const BACKUP: &'static str = "backup";
+const NO_BACKUP: &'static str = "no_backup";
+const AGE_RECIPIENT: &'static str = "age_recipient";
+const GPG_RECIPIENT: &'static str = "gpg_recipient";
+const NO_ENCRYPTION: &'static str = "no_encryption";
+const S3_ENDPOINT: &'static str = "s3_endpoint";
+const S3_BUCKET: &'static str = "s3_bucket";
+const S3_REGION: &'static str = "s3_region";
+const S3_ACCESS_KEY_ID: &'static str = "s3_access_key_id";
+const NO_S3: &'static str = "no_s3";
+const SET_SCHEDULE: &'static str = "set_schedule";
+const NO_SCHEDULE: &'static str = "no_schedule";
+const DESKTOP_NOTIFY: &'static str = "desktop_notify";
+const NO_DESKTOP_NOTIFY: &'static str = "no_desktop_notify";
+const WEBHOOK_URL: &'static str = "webhook_url";
+const NO_WEBHOOK: &'static str = "no_webhook";
+const WEBHOOK_FORMAT: &'static str = "webhook_format";
+const PRE_RUN_HOOK: &'static str = "pre_run_hook";
+const NO_PRE_RUN_HOOK: &'static str = "no_pre_run_hook";
+const POST_RUN_HOOK: &'static str = "post_run_hook";
+const NO_POST_RUN_HOOK: &'static str = "no_post_run_hook";
+const ARCHIVE_API: &'static str = "archive_api";
+const NO_ARCHIVE_API: &'static str = "no_archive_api";
+const REFRESH_MARGIN: &'static str = "refresh_margin";
+const DEFAULT_S3_REGION: &'static str = "us-east-1";
+const BACKFILL: &'static str = "backfill";
+const GDPR_EXPORT: &'static str = "from_gdpr_export";
+const TIMEZONE: &'static str = "timezone";
+const RUN_AT: &'static str = "at";
+const ALL_ACCOUNTS: &'static str = "all";
+const WATCH: &'static str = "watch";
+const INTERVAL: &'static str = "interval";
+const LIMIT: &'static str = "limit";
+const DELAY: &'static str = "delay";
+const JITTER: &'static str = "jitter";
+const YES: &'static str = "yes";
+const VERIFY: &'static str = "verify";
+const MAX_REQUESTS: &'static str = "max_requests";
+const TIMEOUT: &'static str = "timeout";
+const LINK: &'static str = "link";
+const ADD_PATTERNS: &'static str = "add_patterns";
+const REMOVE_PATTERNS: &'static str = "remove_patterns";
+const LIST_PATTERNS: &'static str = "list_patterns";
+const MATCH_PATTERN: &'static str = "match_pattern";
+const ADD_KEYWORDS: &'static str = "add_keywords";
+const REMOVE_KEYWORDS: &'static str = "remove_keywords";
+const ADD_INCLUDED_SUBREDDITS: &'static str = "add_included";
+const REMOVE_INCLUDED_SUBREDDITS: &'static str = "remove_included";
+const ONLY_SUBREDDITS: &'static str = "only_subreddits";
+const ADD_KEPT_DOMAINS: &'static str = "add_kept_domains";
+const REMOVE_KEPT_DOMAINS: &'static str = "remove_kept_domains";
+const ADD_DELETED_DOMAINS: &'static str = "add_deleted_domains";
+const REMOVE_DELETED_DOMAINS: &'static str = "remove_deleted_domains";
+const ADD_EXCLUDED_FLAIRS: &'static str = "add_excluded_flairs";
+const REMOVE_EXCLUDED_FLAIRS: &'static str = "remove_excluded_flairs";
+const ADD_INCLUDED_FLAIRS: &'static str = "add_included_flairs";
+const REMOVE_INCLUDED_FLAIRS: &'static str = "remove_included_flairs";
 
 custom_error! {pub RedeleteError
     RedditApiError{ source: reddit_api::RedditApiError } = "Reddit API Error",
-    ConfigError{ source: config::ConfigError } = "Config Error",
+    ConfigError{ source: config::ConfigError } = "Config Error: {source}",
+    JournalError{ source: journal::JournalError } = "Failure journal error",
+    AuditError{ source: audit::AuditError } = "Audit log error",
+    BackupError{ source: backup::BackupError } = "Backup error",
+    ScriptError{ source: script::ScriptError } = "Script error",
+    PlanError{ source: plan::PlanError } = "Plan error",
+    ExportError{ source: export::ExportError } = "Export error",
+    ArchiveError{ source: archive::ArchiveError } = "Archive error",
+    RestoreError{ source: restore::RestoreError } = "Restore error",
+    StateError{ source: state::StateError } = "State database error",
+    RunHookError{ source: run_hook::RunHookError } = "Pre run hook failed, nothing was deleted",
+    SignalError{ source: std::io::Error } = "Unable to listen for signals",
+    MissingScopes{ username: String, scopes: String } = "{username} hasn't given redelete these scopes: {scopes}. Run `redelete authorize --reauthorize {username}` to grant them",
+    DeletionsFailed{ count: usize } = "{count} deletions failed. Run `redelete retry` to try them again.",
+    AccountsFailed{ count: usize } = "{count} accounts didn't finish cleanly.",
     RunError = "Unable to run"
 }
 
 pub type Result<T> = result::Result<T, RedeleteError>;
 
-async fn run(username: String, dry: bool) -> Result<()> {
-    let client = reddit_api::RedditClient::new(username);
-    let (mut comments, mut posts) = try_join!(client.comments(), client.posts())?;
-    let mut all = Vec::new();
-    all.append(&mut comments);
-    all.append(&mut posts);
+// Exit codes, so shell scripts and schedulers can tell what went wrong.
+const EXIT_ERROR: i32 = 1;
+const EXIT_AUTH: i32 = 2;
+const EXIT_CONFIG_NOT_FOUND: i32 = 3;
+const EXIT_API: i32 = 4;
+/// The run finished, but some deletions or accounts failed.
+const EXIT_PARTIAL: i32 = 5;
 
-    let (_, ai) = config::get_config_and_account_info(&client.username)?;
-    let mut printed = false;
-    let mut to_delete: Vec<String> = Vec::new();
-    for p in all {
-        if check_should_delete(&ai, &p) {
-            if !printed {
-                printed = true;
-                println!("Deleting comments/submissions:")
-            }
-            match p.body {
-                Some(s) => {
-                    let max = s.len();
-                    println!("comment @ /r/{}:", &p.subreddit);
-                    println!("{}", &s[..max])
-                }
-                None => {
-                    match p.title {
-                        Some(s) => {
-                            let max = s.len();
-                            println!("submission @ /r/{}:", &p.subreddit);
-                            println!("{}", &s[..max])
-                        }
-                        None => (),
-                    }
-                    match p.selftext {
-                        Some(s) => {
-                            let max = s.len();
-                            println!("{}", &s[..max])
-                        }
-                        None => (),
-                    }
-                    match p.url {
-                        Some(s) => {
-                            let max = s.len();
-                            println!("{}", &s[..max])
-                        }
-                        None => (),
-                    }
-                }
+impl RedeleteError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            RedeleteError::RedditApiError { source } => api_exit_code(source),
+            RedeleteError::ConfigError {
+                source: config::ConfigError::NotFound { .. },
+            } => EXIT_CONFIG_NOT_FOUND,
+            RedeleteError::DeletionsFailed { .. } | RedeleteError::AccountsFailed { .. } => {
+                EXIT_PARTIAL
             }
-            let str_name = String::from(p.name.as_str());
-            to_delete.push(str_name);
+            _ => EXIT_ERROR,
         }
     }
-    if !printed {
-        println!("No comments or submissions to delete.");
-    } else {
-        println!("Getting ready to delete {} posts.", to_delete.len());
-    }
-    if !dry {
-        let mut tasks = Vec::new();
-        for name in to_delete.into_iter() {
-            tasks.push(client.delete(name))
+}
+
+fn api_exit_code(e: &reddit_api::RedditApiError) -> i32 {
+    use reddit_api::RedditApiError::*;
+    match e {
+        OAuthValidationError { .. } | RefreshTokenError | ReauthorizeNeeded { .. } => EXIT_AUTH,
+        Reqwest { source } if source.status() == Some(reqwest::StatusCode::UNAUTHORIZED) => {
+            EXIT_AUTH
         }
-        let x = join_all(tasks).await;
-        println!("Deleted {} posts.", x.len())
-    } else {
-        println!("Dry run flag present. Skipping delete operation.");
+        Config {
+            source: config::ConfigError::NotFound { .. },
+        } => EXIT_CONFIG_NOT_FOUND,
+        _ => EXIT_API,
     }
-    Ok(())
 }
 
-fn check_should_delete(ai: &config::AccountInfo, info: &reddit_api::DeletionInfo) -> bool {
-    let age: u64 = time::SystemTime::now()
-        .duration_since(time::UNIX_EPOCH + time::Duration::from_secs_f64(info.created_utc.clone()))
-        .unwrap()
-        .as_secs()
-        / 3600;
-    if ai.max_hours.is_none() && ai.minimum_score.is_none() && ai.excluded_subreddits.is_none() {
-        return true;
+/// Prints the error and exits with its exit code.
+fn fail(e: RedeleteError) -> ! {
+    say!("{}", e);
+    output::event("error", json!({ "message": e.to_string() }));
+    std::process::exit(e.exit_code());
+}
+
+/// Like `fail`, but when reddit stopped accepting the account's token and someone is at the
+/// terminal, offers to authorize it again right away. Always exits.
+async fn fail_or_reauthorize(e: RedeleteError) {
+    let username = match &e {
+        RedeleteError::RedditApiError {
+            source: reddit_api::RedditApiError::ReauthorizeNeeded { username },
+        } => Some(username.clone()),
+        _ => None,
+    };
+    let username = match username {
+        Some(username) => username,
+        None => fail(e),
+    };
+    // The advice is in the source, the wrapping error only says it came from the API.
+    if let RedeleteError::RedditApiError { source } = &e {
+        say!("{}", source);
+        output::event("error", json!({ "message": source.to_string() }));
     }
-    if ai.max_hours.is_some() && ai.max_hours.unwrap() > age {
-        return false;
+    if !output::json() && atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout) {
+        let prompt = format!("Authorize {} again now? [y/N] ", username);
+        let stdin = std::io::stdin();
+        if confirm(&prompt, stdin.lock()) {
+            let scopes = reddit_api::requested_scopes();
+            match reddit_api::reauthorize(&username, &scopes).await {
+                Ok(_) => say!("Authorized {} again, run the command again.", username),
+                Err(e) => say!("Unable to authorize account. {}", e),
+            }
+        }
     }
-    if ai.minimum_score.is_some() && ai.minimum_score.unwrap() < info.score {
-        return false;
+    std::process::exit(e.exit_code());
+}
+
+fn unknown_username(username: &str) -> ! {
+    say!(
+        "{} is not a saved username in your config. Try authorizing that username first.",
+        username
+    );
+    std::process::exit(EXIT_CONFIG_NOT_FOUND);
+}
+
+/// Flags given to a single `run` invocation.
+#[derive(Debug, Default, Clone)]
+struct RunOptions {
+    dry: bool,
+    backfill: bool,
+    /// Article id of the only thread to delete comments from.
+    thread: Option<String>,
+    /// Replaces the saved match patterns for this run only.
+    match_patterns: Option<Vec<String>>,
+    /// Replaces the saved included subreddits for this run only.
+    included_subreddits: Option<Vec<String>>,
+    /// Replaces the saved max score for this run only.
+    max_score: Option<i32>,
+    /// Replaces the saved minimum score for this run only, 0 turns the filter off.
+    minimum_score: Option<i32>,
+    /// Replaces the saved max hours for this run only, 0 turns the filter off.
+    max_hours: Option<u64>,
+    /// Added to the saved excluded subreddits for this run only.
+    add_excluded: Vec<String>,
+    /// Removed from the saved excluded subreddits for this run only.
+    remove_excluded: Vec<String>,
+    /// Only the first this many matching items are deleted.
+    limit: Option<usize>,
+    /// Pause between delete calls.
+    pace: Option<pacing::Pace>,
+    /// Skips the confirmation prompt.
+    yes: bool,
+    /// Re-fetches the history after deleting to check the items are gone.
+    verify: bool,
+    /// Most API calls the run may make, fetching and deleting together.
+    max_requests: Option<usize>,
+    /// Longest the run may take, counted from when it starts rather than from `--at`.
+    timeout: Option<time::Duration>,
+    /// Overwrite comments and self posts before deleting them.
+    shred: Option<shred::Shred>,
+    /// Longest item text shown when listing matches, `None` shows all of it.
+    preview_length: Option<usize>,
+    /// File a dry run writes its matches to, as csv or json.
+    export: Option<String>,
+    /// Fullnames from a reddit data export, looked up instead of reading the listings.
+    fullnames: Option<Vec<String>>,
+    /// Json from `redelete export` to read the history from instead of reddit.
+    archive: Option<String>,
+    /// Put matches in the trash instead of deleting them.
+    trash: bool,
+    /// Delete what has been in the trash at least this long. Implies `trash`.
+    purge_after: Option<time::Duration>,
+}
+
+/// Runs every account, carrying on past ones that fail, then lists how each one went.
+/// Accounts run at the same time, sharing the process-wide rate limiters, unless runs
+/// have to ask for confirmation.
+async fn run_all(usernames: Vec<String>, options: RunOptions) -> Result<()> {
+    let mut results: Vec<(String, Result<()>)> = Vec::new();
+    if options.dry || options.yes {
+        say!(
+            "Running for {} at the same time.",
+            usernames.join(", ").bold()
+        );
+        let runs = usernames
+            .iter()
+            .map(|username| run(username.clone(), options.clone()));
+        let finished = futures::future::join_all(runs).await;
+        results.extend(usernames.into_iter().zip(finished));
+    } else {
+        for username in usernames {
+            say!("{}", format!("Running for {}", username).bold());
+            let result = run(username.clone(), options.clone()).await;
+            if let Err(e) = &result {
+                say!("{}", e);
+            }
+            results.push((username, result));
+        }
     }
-    if ai.excluded_subreddits.is_some()
-        && ai
-            .excluded_subreddits
-            .clone()
-            .unwrap()
-            .contains(&String::from(&*info.subreddit))
-    {
-        return false;
+    say!("{}", accounts_table(&results));
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    if failed > 0 {
+        return Err(RedeleteError::AccountsFailed { count: failed });
     }
-    return true;
+    Ok(())
 }
 
-#[tokio::main]
-async fn main() {
-    let exclude_arg = Arg::with_name(ADD_EXCLUDED_SUBREDDITS)
-        .short("a")
-        .long("add-excluded")
-        .help("Adds subreddits to an exclusion list. Comments and submissions in the subreddits in this list will not be deleted.")
-        .takes_value(true)
-        .multiple(true);
-    let include_arg = Arg::with_name(REMOVE_EXCLUDED_SUBREDDITS)
-        .short("r")
-        .long("remove-excluded")
-        .help("Removes subreddits from the exclusion list.")
-        .takes_value(true)
-        .multiple(true);
-    let score_arg = Arg::with_name(MIN_SCORE)
-        .short("s")
-        .long("min-score")
-        .help("Minimum score required to prevent deletion of comment/submission. Set to 0 to remove filter.")
-        .takes_value(true);
-    let max_hours_arg = Arg::with_name(MAX_HOURS)
-        .short("t")
-        .long("max-hours")
-        .help("Will not delete comments/submissions made within this many hours. Set to 0 to remove filter.")
-        .takes_value(true);
-    let username_arg = Arg::with_name(USERNAME)
-        .help("Username to config/run the app for.")
-        .index(1)
-        .required(true)
-        .takes_value(true);
-    let matches = App::new("Redelete")
-        .version("0.3.1")
-        .author("Justin E. <ardeaf@gmail.com>")
-        .about("Deletes your reddit comments and submissions")
-        .subcommand(
-            App::new("config")
-                .about("Set default configuration options for the app.")
-                .arg(&username_arg)
-                .arg(&exclude_arg)
-                .arg(&include_arg)
-                .arg(&score_arg)
-                .arg(&max_hours_arg),
-        )
-        .subcommand(
-            App::new("run")
-                .about("Run the deletion part of the app.")
-                .arg(Arg::with_name(DRYRUN).short("d").long("dry-run").help(
-                    "Fetches comments and submissions to be deleted, then prompts to delete it.",
-                ))
-                .arg(&username_arg)
-                .arg(&exclude_arg)
-                .arg(&include_arg)
-                .arg(&score_arg)
-                .arg(&max_hours_arg),
-        )
-        .subcommand(
-            App::new(VIEW)
-                .about("View saved configs for given <username>")
-                .arg(&username_arg),
-        )
-        .subcommand(
-            App::new(AUTHORIZE)
-                .about("Authorize this application with your reddit account.")
-                .arg(
-                    Arg::with_name(FORGET_ACCOUNT)
-                        .short("f")
-                        .long("forget-account")
-                        .help("Removes account from the saved config file.")
-                        .takes_value(true),
-                ),
-        )
-        .get_matches();
-    if let Some(matches) = matches.subcommand_matches("config") {
-        let username = matches.value_of(USERNAME).unwrap();
-        if matches.is_present(MIN_SCORE) {
-            let score = value_t!(matches, MIN_SCORE, i32)
-                .expect("Minimum score requires an integer value.");
-            match config::set_minimum_score(username.into(), score.clone()) {
-                Ok(()) => {
-                    if score > 0 {
-                        println!("Set minimum score to {}", score)
-                    } else {
-                        println!("Removed minimum score filter.")
-                    }
-                }
-                Err(e) => println!("Unable to set minimum score: {}", e),
+/// `run` for one account, or `run_all` for every account saved in the config right now.
+async fn run_once(all: bool, username: &str, options: RunOptions) -> Result<()> {
+    if all {
+        run_all(config::read_config_usernames()?, options).await
+    } else {
+        run(String::from(username), options).await
+    }
+}
+
+/// Runs again every `interval` until Ctrl-C. A failed pass is reported and the next one
+/// still happens. Tokens are refreshed as they expire, and with `--all` accounts
+/// authorized in the meantime are picked up.
+async fn watch(
+    all: bool,
+    username: &str,
+    options: RunOptions,
+    interval: time::Duration,
+    zone: &timezone::Zone,
+) {
+    let mut interrupt = Box::pin(tokio::signal::ctrl_c());
+    let pause = pacing::Pace {
+        delay: interval,
+        jitter: time::Duration::from_secs(0),
+    };
+    loop {
+        match run_once(all, username, options.clone()).await {
+            Ok(_) => say!("Done."),
+            Err(e) => {
+                say!("{}", e);
+                output::event("error", json!({ "message": e.to_string() }));
             }
         }
-        if matches.is_present(MAX_HOURS) {
-            let hours = value_t!(matches, MAX_HOURS, u64)
-                .expect("Maximum hours requires an integer value.");
-            match config::set_max_hours(username.into(), hours.clone()) {
-                Ok(()) => {
-                    if hours > 0 {
-                        println!("Max hours set to {}", hours)
-                    } else {
-                        println!("Removed max hours filter.")
-                    }
-                }
-                Err(e) => println!("Unable to set max hours: {}", e),
-            }
+        let next = chrono::Utc::now().timestamp() as f64 + interval.as_secs_f64();
+        say!(
+            "Next run at {}. Press Ctrl-C to stop.",
+            zone.format_timestamp(next)
+        );
+        if !pause.wait(&mut interrupt).await {
+            say!("Stopped watching.");
+            return;
         }
-        if let Some(inputs) = matches.values_of(ADD_EXCLUDED_SUBREDDITS) {
-            let mut to_add = Vec::new();
-            for input in inputs {
-                to_add.push(input);
-            }
-            match config::add_excluded_subreddits(username.into(), to_add) {
-                Ok(_) => (),
-                Err(e) => println!("Unable to set subreddit exclusion: {}", e),
+    }
+}
+
+/// When `schedule` should next run the account, `None` when it has no schedule or its
+/// schedule never comes up.
+fn next_scheduled_run(
+    ai: &config::AccountInfo,
+    now: chrono::DateTime<chrono::Utc>,
+) -> result::Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    let expression = match &ai.schedule {
+        Some(expression) => expression,
+        None => return Ok(None),
+    };
+    let schedule = cron::Schedule::parse(expression)?;
+    let zone = timezone::Zone::from_config(&ai.timezone)?;
+    Ok(zone.next_scheduled(&schedule, now))
+}
+
+/// Runs each account whenever its schedule comes up, until Ctrl-C or SIGTERM. The config
+/// is read again before every wait and on SIGHUP, so changed schedules are picked up.
+/// As a `daemon` it also reports to systemd and keeps waiting for a SIGHUP when no
+/// account has a schedule yet.
+async fn run_scheduled(dry: bool, daemon: bool) -> Result<()> {
+    let mut signals = daemon::Signals::new()?;
+    let mut ready = false;
+    loop {
+        let now = chrono::Utc::now();
+        let mut due: Vec<(chrono::DateTime<chrono::Utc>, String)> = Vec::new();
+        for username in config::read_config_usernames()? {
+            let ai = match config::read_config_account_info(&username) {
+                Some(ai) => ai,
+                None => continue,
+            };
+            match next_scheduled_run(&ai, now) {
+                Ok(Some(at)) => due.push((at, username)),
+                Ok(None) => (),
+                Err(e) => say!("Skipping {}: {}", username, e),
             }
         }
-        if let Some(inputs) = matches.values_of(REMOVE_EXCLUDED_SUBREDDITS) {
-            let mut to_add = Vec::new();
-            for input in inputs {
-                to_add.push(input);
-            }
-            match config::remove_excluded_subreddits(username.into(), to_add) {
-                Ok(_) => (),
-                Err(e) => println!("Unable to set subreddit exclusion: {}", e),
+        let next = due.iter().map(|(at, _)| *at).min();
+        let usernames: Vec<String> = due
+            .into_iter()
+            .filter(|(at, _)| Some(*at) == next)
+            .map(|(_, username)| username)
+            .collect();
+        let status = match next {
+            Some(next) => format!(
+                "Next run for {} at {}.",
+                usernames.join(", "),
+                timezone::Zone::Local.format_timestamp(next.timestamp() as f64)
+            ),
+            None if daemon => String::from("No accounts have a schedule, waiting for SIGHUP."),
+            None => {
+                say!("No accounts have a schedule. Add one with `redelete config <username> --schedule \"0 3 * * *\"`.");
+                return Ok(());
             }
+        };
+        if daemon {
+            say!("{}", status);
+            daemon::notify(&format!(
+                "{}STATUS={}",
+                if ready { "" } else { "READY=1\n" },
+                status
+            ));
+            ready = true;
+        } else {
+            say!("{} Press Ctrl-C to stop.", status);
         }
-    } else if let Some(matches) = matches.subcommand_matches(AUTHORIZE) {
-        if let Some(username) = matches.value_of(FORGET_ACCOUNT) {
-            match config::delete_user(&*username) {
-                Ok(true) => println!("Removed {} from config file", username),
-                Ok(false) => println!("{} was not found in the config file.", username),
-                Err(e) => println!("Unable to delete. {}", e),
+        let delay = next.map(|next| {
+            (next - now)
+                .to_std()
+                .unwrap_or(time::Duration::from_secs(0))
+        });
+        match signals.wait(delay).await {
+            daemon::Wake::Due => (),
+            daemon::Wake::Reload => {
+                say!("Reloading the config.");
+                continue;
             }
-        } else {
-            match reddit_api::authorize().await {
-                Ok(s) => println!("Authorized account {}", s),
-                Err(e) => println!("Unable to authorize account. {}", e),
+            daemon::Wake::Stop => {
+                daemon::notify("STOPPING=1");
+                say!("Stopped the schedule.");
+                return Ok(());
             }
         }
-    } else if let Some(matches) = matches.subcommand_matches(VIEW) {
-        match config::read_config_account_info(matches.value_of(USERNAME).unwrap()) {
-            Some(ai) => {
-                println!("Settings for: {}", &ai.username);
-                if ai.excluded_subreddits.is_some() {
-                    println!(
-                        "Excluded subreddits: {}",
-                        &ai.excluded_subreddits.unwrap().join(", ")
-                    );
-                } else {
-                    println!("Not excluding any subreddits.")
-                }
-                if ai.max_hours.is_some() {
-                    let max_hours = ai.max_hours.unwrap();
-                    println!(
-                        "Not deleting any posts made within {} hour{}.",
-                        max_hours.clone(),
-                        if max_hours.clone() == 1 { "" } else { "s" }
-                    )
-                } else {
-                    println!("No time minimum before deleting posts.")
-                }
-                if ai.minimum_score.is_some() {
-                    println!(
-                        "Only deleting posts with a score less than {}.",
-                        ai.minimum_score.unwrap()
-                    )
-                } else {
-                    println!("No score limit set.")
+        for username in usernames {
+            let options = RunOptions {
+                dry,
+                yes: true,
+                preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+                ..Default::default()
+            };
+            say!("{}", format!("Running for {}", username).bold());
+            match run(username, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => {
+                    say!("{}", e);
+                    output::event("error", json!({ "message": e.to_string() }));
                 }
             }
-            None => println!(
-                "Unable to find username. Did you authorize this app with that reddit account yet?"
-            ),
-        }
-    } else if let Some(matches) = matches.subcommand_matches(RUN) {
-        let dry = matches.is_present(DRYRUN);
-        let username = matches.value_of(USERNAME).unwrap();
-        match config::read_config_account_info(&username) {
-            Some(_) => match run(username.into(), dry).await {
-                Ok(_) => println!("Done."),
-                Err(e) => println!("{}", e),
-            },
-            None => println!(
-                "{} is not a saved username in your config. Try authorizing that username first.",
-                &username
-            ),
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    /// Need to test check_should_delete with more mixed configs to make sure.
-    use super::*;
-    use config::tests::{account_info, fresh_account_info};
-    use reddit_api::RedditPost;
-    const SUBREDDIT: &'static str = "subreddit";
-    fn hours_ago_to_epoch(hours: f64) -> f64 {
-        time::SystemTime::now()
-            .duration_since(time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64()
-            - (3600.0 * hours)
+/// One line per account: its name, then "ok" or what went wrong.
+fn accounts_table(results: &[(String, Result<()>)]) -> String {
+    let width = results.iter().map(|(u, _)| u.len()).max().unwrap_or(0);
+    results
+        .iter()
+        .map(|(username, result)| match result {
+            Ok(_) => format!("{:width$}  {}", username, "ok".green(), width = width),
+            Err(e) => format!(
+                "{:width$}  {}",
+                username,
+                e.to_string().red(),
+                width = width
+            ),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+async fn run(username: String, options: RunOptions) -> Result<()> {
+    let started = time::Instant::now();
+    let (client, ai, filters) = prepare_run(username, &options)?;
+    pre_run_hook(&ai, &options)?;
+    if can_stream(&ai, &options) {
+        return run_streaming(&client, &ai, &options, &filters, started).await;
     }
-    fn test_post(hours_ago: f64, score: i32) -> reddit_api::Post {
-        reddit_api::Post {
-            saved: true,
-            name: "name".into(),
-            created_utc: hours_ago_to_epoch(hours_ago),
-            subreddit: String::from(SUBREDDIT),
-            score,
-            selftext: "".into(),
-            url: "".into(),
-            title: "".into(),
-        }
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let selection = match select(&client, &ai, &options, &filters, &zone).await? {
+        Some(selection) => selection,
+        None => return Ok(()),
+    };
+    delete_selection(&client, &ai, &options, &filters, selection, zone, started).await
+}
+
+/// A client set up for `options` and the account's settings with the run's overrides applied.
+fn prepare_run(
+    username: String,
+    options: &RunOptions,
+) -> Result<(reddit_api::RedditClient, config::AccountInfo, Filters)> {
+    let mut client = reddit_api::RedditClient::new(username);
+    client.backfill = options.backfill;
+    client.max_requests = options.max_requests;
+    client.deadline = options.timeout.map(|t| time::Instant::now() + t);
+    let (_, mut ai) = config::get_config_and_account_info(&client.username)?;
+    apply_filter_overrides(&mut ai, options);
+    config::validate_account_info(&ai)?;
+    client.include_quarantined = ai.include_quarantined;
+    client.overview = ai.use_overview;
+    if let Some(concurrency) = ai.concurrency {
+        client.concurrency = concurrency;
     }
+    let filters = Filters::load(&ai)?;
+    Ok((client, ai, filters))
+}
 
-    #[test]
-    fn test_no_config_delete() {
-        assert_eq!(
-            check_should_delete(&fresh_account_info(), &test_post(0.0, 0).deletion_info()),
-            true
-        )
+/// The account's script and rule, loaded and parsed once per run instead of per item.
+struct Filters {
+    script: Option<script::Script>,
+    rule: Option<rules::Expr>,
+}
+
+impl Filters {
+    fn load(ai: &config::AccountInfo) -> Result<Filters> {
+        let script = match &ai.script {
+            Some(path) => Some(script::Script::load(path)?),
+            None => None,
+        };
+        let rule = match &ai.rule {
+            Some(rule) => Some(
+                rules::parse(rule)
+                    .map_err(|reason| config::ConfigError::InvalidSettings { reason })?,
+            ),
+            None => None,
+        };
+        Ok(Filters { script, rule })
     }
-    #[test]
-    fn test_max_hours_keep() {
-        let mut account = account_info();
-        account.minimum_score = None;
-        account.excluded_subreddits = None;
-        let keep = test_post(23.0, 0).deletion_info();
-        assert_eq!(check_should_delete(&account, &keep), false);
+}
+
+/// Stops before anything is fetched when the account's token wasn't granted all of
+/// `needed`, as happens after `authorize --with-<feature>` left that feature out.
+fn check_scopes(ai: &config::AccountInfo, needed: &[&str]) -> Result<()> {
+    let granted = ai.token.scopes();
+    if granted.iter().any(|scope| scope == "*") {
+        return Ok(());
     }
-    #[test]
-    fn test_max_hours_delete() {
-        let mut account = account_info();
-        account.minimum_score = None;
-        account.excluded_subreddits = None;
-        let delete = test_post(25.0, 0).deletion_info();
-        assert_eq!(check_should_delete(&account, &delete), true);
+    let missing: Vec<&str> = needed
+        .iter()
+        .filter(|scope| !granted.iter().any(|g| g == *scope))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(RedeleteError::MissingScopes {
+            username: ai.username.clone(),
+            scopes: missing.join(", "),
+        })
     }
-    #[test]
-    fn test_minimum_score_keep() {
-        let mut account = fresh_account_info();
-        account.minimum_score = Some(1000);
-        let keep = test_post(0.0, 1001).deletion_info();
-        assert_eq!(check_should_delete(&account, &keep), false);
+}
+
+/// Runs the account's pre run hook, if it has one. A failing hook stops the run.
+fn pre_run_hook(ai: &config::AccountInfo, options: &RunOptions) -> Result<()> {
+    if let Some(command) = &ai.pre_run_hook {
+        verbose!("Running pre run hook: {}", command);
+        run_hook::run(command, &run_hook::pre_run_env(&ai.username, options.dry))?;
     }
-    #[test]
-    fn test_minimum_score_delete() {
+    Ok(())
+}
+
+/// What a run picked to delete.
+struct Selection {
+    /// Items fetched from reddit, before any filtering.
+    scanned: usize,
+    matched: Vec<reddit_api::DeletionInfo>,
+    /// Items that matched but were left alone because of `--limit`.
+    remaining: usize,
+    /// Fullnames `keep_latest`, `keep_top_per_subreddit` or `keep_top_percent` keep.
+    kept_by_settings: HashSet<String>,
+}
+
+/// Fetches the history and picks what to delete, listing each match. `None` when the
+/// request budget or time limit ran out before the whole history was fetched.
+async fn select(
+    client: &reddit_api::RedditClient,
+    ai: &config::AccountInfo,
+    options: &RunOptions,
+    filters: &Filters,
+    zone: &timezone::Zone,
+) -> Result<Option<Selection>> {
+    let all = match &options.archive {
+        Some(path) => read_archive(path)?,
+        None => match fetch_history(client, ai, options).await {
+            Err(e) if e.stops_run() => {
+                say!("{} while fetching your history, nothing was deleted.", e);
+                return Ok(None);
+            }
+            result => result?,
+        },
+    };
+    let scanned = all.len();
+    report_quarantined(ai, all.iter().filter(|p| p.quarantined).count());
+    let (all, kept_by_settings) = split_kept_by_settings(ai, all);
+    let kept = state::kept(&client.username)?;
+    let deleted = state::deleted(&client.username)?;
+    let mut skipped = 0;
+    let mut matched: Vec<reddit_api::DeletionInfo> = Vec::new();
+    let mut remaining = 0;
+    for p in all {
+        if deleted.contains(&p.name) {
+            item_event(&p, "deleted");
+            continue;
+        }
+        if kept.contains(&p.name) {
+            item_event(&p, "kept");
+            skipped += 1;
+            continue;
+        }
+        if should_delete(ai, filters, &p) {
+            if options.limit.map_or(false, |limit| matched.len() >= limit) {
+                item_event(&p, "over_limit");
+                remaining += 1;
+                continue;
+            }
+            if matched.is_empty() {
+                info!("Deleting comments/submissions:")
+            }
+            item_event(&p, "delete");
+            print_item(ai, zone, &p, options.preview_length);
+            matched.push(p);
+        } else {
+            item_event(&p, "keep");
+            verbose!(
+                "{}",
+                item_line("KEEP", now_utc(), &p, options.preview_length)
+            );
+        }
+    }
+    report_kept(skipped);
+    report_remaining(remaining);
+    Ok(Some(Selection {
+        scanned,
+        matched,
+        remaining,
+        kept_by_settings,
+    }))
+}
+
+/// Confirms, backs up and deletes the selected items, or just records them for a dry run.
+async fn delete_selection(
+    client: &reddit_api::RedditClient,
+    ai: &config::AccountInfo,
+    options: &RunOptions,
+    filters: &Filters,
+    selection: Selection,
+    zone: timezone::Zone,
+    started: time::Instant,
+) -> Result<()> {
+    let dry = options.dry;
+    let matched = if options.trash {
+        take_out_trash(
+            ai,
+            options,
+            filters,
+            selection.matched,
+            &selection.kept_by_settings,
+            &zone,
+        )?
+    } else {
+        selection.matched
+    };
+    if matched.is_empty() {
+        say!("No comments or submissions to delete.");
+    } else {
+        say!("Getting ready to delete {} posts.", matched.len());
+    }
+    if let (true, Some(path)) = (dry, &options.export) {
+        export::export(Path::new(path), &matched, options.preview_length)?;
+        say!("Exported {} posts to {}", matched.len(), path);
+    }
+    if !dry && !options.yes && !matched.is_empty() {
+        let prompt = format!(
+            "Delete {} posts? This can't be undone. [y/N] ",
+            matched.len()
+        );
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nothing was deleted.");
+            return Ok(());
+        }
+    }
+    if !dry && ai.backup && !matched.is_empty() {
+        if ai.backup_recipient.is_none() {
+            say!("{} backup is not encrypted. Set a recipient with `redelete config <username> --age-recipient <key>`.", "Warning:".yellow());
+        }
+        let path = backup::write_backup(&client.username, &matched, ai.backup_recipient.as_ref())?;
+        say!("Backed up {} posts to {}", matched.len(), path.display());
+        if let Some(s3) = &ai.s3 {
+            match remote_backup::upload(s3, &path).await {
+                Ok(url) => say!("Uploaded backup to {}", url),
+                Err(e) => say!("Unable to upload backup, it is still saved locally: {}", e),
+            }
+        }
+    }
+    let to_delete: Vec<audit::Item> = matched.into_iter().map(audit::Item::from).collect();
+    let mut summary = report::RunSummary {
+        username: String::from(&client.username),
+        dry_run: dry,
+        scanned: selection.scanned,
+        matched: to_delete.len(),
+        remaining: selection.remaining,
+        zone,
+        ..Default::default()
+    };
+    let mut failed: Vec<journal::FailedDeletion> = Vec::new();
+    if !dry {
+        let mut deleted: HashSet<String> = to_delete.iter().map(|i| i.name.clone()).collect();
+        let outcome = delete_all(client, to_delete, options.pace, options.shred.as_ref()).await?;
+        summary.failed = outcome.failed.len();
+        summary.remaining += outcome.deferred.len();
+        summary.deleted = summary.matched - summary.failed - outcome.deferred.len();
+        outcome.deleted.iter().for_each(|i| summary.tally(i));
+        for f in outcome.failed.iter().chain(&outcome.deferred) {
+            deleted.remove(&f.name);
+        }
+        failed.extend(outcome.failed.iter().cloned());
+        journal::record_failures(&client.username, outcome.journal())?;
+        if options.verify {
+            failed.extend(verify_run(client, deleted, &mut summary).await?);
+        }
+    } else {
+        to_delete.iter().for_each(|i| summary.tally(i));
+        audit::record_deletions(&client.username, &to_delete, true)?;
+        say!("Dry run flag present. Skipping delete operation.");
+    }
+    summary.elapsed = started.elapsed();
+    summary.requests = client.requests_made();
+    finish_run(ai, &summary, &failed).await
+}
+
+/// Puts `matched` in the trash and returns what has been there for `--purge-after`, which
+/// then gets deleted like any other match. Without `--purge-after` nothing is deleted.
+/// Items the current settings would keep are taken back out instead of being purged, so
+/// fixing the settings during the grace period rescues them. `kept_by_settings` comes from
+/// the whole history, since keeping the latest or top items depends on the rest of it.
+fn take_out_trash(
+    ai: &config::AccountInfo,
+    options: &RunOptions,
+    filters: &Filters,
+    matched: Vec<reddit_api::DeletionInfo>,
+    kept_by_settings: &HashSet<String>,
+    zone: &timezone::Zone,
+) -> Result<Vec<reddit_api::DeletionInfo>> {
+    if options.dry {
+        say!("Would put {} posts in the trash.", matched.len());
+    } else {
+        let marked = state::mark_pending(&ai.username, &matched)?;
+        say!(
+            "Put {} posts in the trash, {} were already there.",
+            marked,
+            matched.len() - marked
+        );
+    }
+    let grace = match options.purge_after {
+        Some(grace) => grace,
+        None => {
+            say!("Delete them with `redelete run {} --purge-after <duration>`, or take them out with `redelete trash {} --unmark`.", ai.username, ai.username);
+            return Ok(Vec::new());
+        }
+    };
+    let kept = state::kept(&ai.username)?;
+    let (due, rescued): (Vec<_>, Vec<_>) =
+        due_for_purge(state::pending(&ai.username)?, grace, now_utc() as u64)
+            .into_iter()
+            .partition(|p| {
+                !kept.contains(&p.name)
+                    && !kept_by_settings.contains(&p.name)
+                    && should_delete(ai, filters, p)
+            });
+    if !rescued.is_empty() {
+        if options.dry {
+            say!(
+                "Would take {} posts the current settings keep out of the trash.",
+                rescued.len()
+            );
+        } else {
+            let names: Vec<String> = rescued.into_iter().map(|p| p.name).collect();
+            let unmarked = state::unmark_pending(&ai.username, &names)?;
+            say!(
+                "Took {} posts the current settings keep out of the trash.",
+                unmarked
+            );
+        }
+    }
+    if !due.is_empty() {
+        info!("Purging from the trash:");
+    }
+    for p in &due {
+        print_item(ai, zone, p, options.preview_length);
+    }
+    Ok(due)
+}
+
+/// The items that have been in the trash for at least `grace` by `now`.
+fn due_for_purge(
+    pending: Vec<state::PendingItem>,
+    grace: time::Duration,
+    now: u64,
+) -> Vec<reddit_api::DeletionInfo> {
+    pending
+        .into_iter()
+        .filter(|p| p.marked_at + grace.as_secs() <= now)
+        .map(|p| p.item)
+        .collect()
+}
+
+/// Lists what's in the account's trash and when each item went in.
+fn list_trash(username: &str, preview_length: Option<usize>) -> Result<()> {
+    let ai = config::get_config_and_account_info(username)?.1;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let pending = state::pending(username)?;
+    if pending.is_empty() {
+        say!("The trash is empty.");
+        return Ok(());
+    }
+    say!(
+        "{}",
+        trash_listing(&pending, &zone, now_utc(), preview_length)
+    );
+    Ok(())
+}
+
+fn trash_listing(
+    pending: &[state::PendingItem],
+    zone: &timezone::Zone,
+    now: f64,
+    preview_length: Option<usize>,
+) -> String {
+    pending
+        .iter()
+        .map(|p| {
+            format!(
+                "{} {}\n    {}, marked {}",
+                p.item.name,
+                item_line("TRASH", now, &p.item, preview_length),
+                zone.format_timestamp(p.item.created_utc),
+                zone.format_timestamp(p.marked_at as f64)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Picks what a run would delete and writes it to `path` for `redelete apply`.
+async fn make_plan(username: String, options: RunOptions, path: &Path) -> Result<()> {
+    let (client, ai, filters) = prepare_run(username, &options)?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let selection = match select(&client, &ai, &options, &filters, &zone).await? {
+        Some(selection) => selection,
+        None => return Ok(()),
+    };
+    let count = selection.matched.len();
+    plan::write_plan(
+        path,
+        &plan::Plan::new(String::from(&client.username), selection.matched),
+    )?;
+    say!(
+        "Wrote {} posts to {}. Review it, then delete them with `redelete apply {}`.",
+        count,
+        path.display(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Prints what's in the account's history and how much of it a run would delete now.
+async fn history_stats(username: String, options: RunOptions) -> Result<()> {
+    let (client, ai, filters) = prepare_run(username, &options)?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let all = load_history(&client, &ai, &options).await?;
+    let matching = matching_now(&ai, &filters, &all)?.len();
+    let stats = stats::HistoryStats::new(&client.username, &all, matching, zone);
+    say!("{}", stats.body());
+    Ok(())
+}
+
+/// Counts what a run with the saved settings would delete right now, for `view --preview`.
+async fn preview_matching(username: String) -> Result<()> {
+    let options = RunOptions::default();
+    let (client, ai, filters) = prepare_run(username, &options)?;
+    let all = fetch_history(&client, &ai, &options).await?;
+    let matching = matching_now(&ai, &filters, &all)?;
+    let comments = matching.iter().filter(|p| p.body.is_some()).count();
+    say!(
+        "A run now would delete {} comments and {} submissions, of {} scanned.",
+        comments,
+        matching.len() - comments,
+        all.len()
+    );
+    Ok(())
+}
+
+/// The items in `all` a run would delete right now, leaving out kept and deleted ones.
+fn matching_now(
+    ai: &config::AccountInfo,
+    filters: &Filters,
+    all: &[reddit_api::DeletionInfo],
+) -> Result<Vec<reddit_api::DeletionInfo>> {
+    Ok(matching(ai, filters, all, &decided(&ai.username)?))
+}
+
+/// Fullnames earlier runs already settled, kept with `redelete keep` or deleted.
+fn decided(username: &str) -> Result<HashSet<String>> {
+    let mut decided = state::kept(username)?;
+    decided.extend(state::deleted(username)?);
+    Ok(decided)
+}
+
+/// The items in `all` that the account's filters pick, other than the `decided` fullnames.
+fn matching(
+    ai: &config::AccountInfo,
+    filters: &Filters,
+    all: &[reddit_api::DeletionInfo],
+    decided: &HashSet<String>,
+) -> Vec<reddit_api::DeletionInfo> {
+    without_kept_by_settings(ai, all.to_vec())
+        .into_iter()
+        .filter(|p| !decided.contains(&p.name) && should_delete(ai, filters, p))
+        .collect()
+}
+
+/// Runs the filters over an export or made up items and lists what a run would delete,
+/// without reddit or a token. Starts from the account's saved settings when a username is
+/// given, and from no filters at all otherwise, with `options` layered on top.
+fn simulate(
+    username: Option<&str>,
+    input: Option<&Path>,
+    sample_size: usize,
+    options: &RunOptions,
+) -> Result<()> {
+    let (mut ai, decided) = match username {
+        Some(username) => (
+            config::get_config_and_account_info(username)?.1,
+            decided(username)?,
+        ),
+        None => (
+            config::new_account_info(SIMULATE, config::unauthorized_token(), 0),
+            HashSet::new(),
+        ),
+    };
+    apply_filter_overrides(&mut ai, options);
+    let filters = Filters::load(&ai)?;
+    let items = match input {
+        Some(path) => export::read_history(path)?,
+        None => sample::history(sample_size, now_utc()),
+    };
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let mut matched = matching(&ai, &filters, &items, &decided);
+    if let Some(limit) = options.limit {
+        matched.truncate(limit);
+    }
+    for p in &matched {
+        print_item(&ai, &zone, p, options.preview_length);
+    }
+    let comments = matched.iter().filter(|p| p.body.is_some()).count();
+    say!(
+        "A run would delete {} of {} items: {} comments and {} submissions. Nothing was deleted.",
+        matched.len(),
+        items.len(),
+        comments,
+        matched.len() - comments
+    );
+    Ok(())
+}
+
+/// Downloads the whole history with every field reddit returns and writes it to `path`,
+/// or renders it as pages in the `path` directory for markdown and html.
+async fn archive_history(
+    username: String,
+    format: &str,
+    group_by: archive::GroupBy,
+    path: &Path,
+) -> Result<()> {
+    let client = reddit_api::RedditClient::new(username);
+    let history = client.raw_history().await?;
+    match archive::PageFormat::parse(format) {
+        Some(page_format) => {
+            let (_, ai) = config::get_config_and_account_info(&client.username)?;
+            let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+            let pages = archive::write_archive(path, &history, page_format, group_by, &zone)?;
+            say!(
+                "Archived {} comments/submissions in {} pages, start at {}",
+                history.len(),
+                pages,
+                path.join(format!("index.{}", page_format.extension()))
+                    .display()
+            );
+        }
+        None => {
+            let format = export::ArchiveFormat::parse(format).unwrap();
+            export::export_history(path, &history, format)?;
+            say!(
+                "Exported {} comments/submissions to {}",
+                history.len(),
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints every item in the account's history that `search` matches, newest first.
+async fn search_history(
+    username: String,
+    search: &search::Search,
+    options: RunOptions,
+) -> Result<()> {
+    let mut client = reddit_api::RedditClient::new(username);
+    let (_, ai) = config::get_config_and_account_info(&client.username)?;
+    client.overview = ai.use_overview;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let mut found: Vec<reddit_api::DeletionInfo> = load_history(&client, &ai, &options)
+        .await?
+        .into_iter()
+        .filter(|p| search.matches(p))
+        .collect();
+    found.sort_by(|a, b| {
+        b.created_utc
+            .partial_cmp(&a.created_utc)
+            .unwrap_or(Ordering::Equal)
+    });
+    let now = now_utc();
+    let preview_length = DEFAULT_PREVIEW_LENGTH.parse().ok();
+    for p in &found {
+        say!("{}", item_line("MATCH", now, p, preview_length));
+        say!(
+            "    {} https://www.reddit.com{}",
+            zone.format_timestamp(p.created_utc),
+            &p.permalink
+        );
+    }
+    say!("Found {} posts.", found.len());
+    Ok(())
+}
+
+/// Unsaves the account's saved comments/submissions that pass the filters, or just lists
+/// them for a dry run. Returns how many were unsaved.
+async fn purge_saved(username: String, options: RunOptions) -> Result<usize> {
+    let (client, ai, filters) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["save"])?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let saved = client.saved().await?;
+    let scanned = saved.len();
+    let mut matched: Vec<reddit_api::DeletionInfo> = Vec::new();
+    for p in saved {
+        if !should_delete(&ai, &filters, &p)
+            || options.limit.map_or(false, |limit| matched.len() >= limit)
+        {
+            verbose!(
+                "{}",
+                item_line("KEEP", now_utc(), &p, options.preview_length)
+            );
+            continue;
+        }
+        if matched.is_empty() {
+            info!("Unsaving comments/submissions:")
+        }
+        print_item(&ai, &zone, &p, options.preview_length);
+        matched.push(p);
+    }
+    say!("{} of {} saved posts match.", matched.len(), scanned);
+    if options.dry {
+        say!("Dry run flag present. Skipping unsave operation.");
+        return Ok(0);
+    }
+    if !options.yes && !matched.is_empty() {
+        let prompt = format!("Unsave {} posts? [y/N] ", matched.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nothing was unsaved.");
+            return Ok(0);
+        }
+    }
+    let mut unsaved = 0;
+    for p in &matched {
+        match client.unsave(&p.name).await {
+            Ok(()) => unsaved += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to unsave, run `redelete authorize` again to let redelete unsave posts.");
+                break;
+            }
+            Err(e) => say!("Unable to unsave {}: {}", p.name, e),
+        }
+    }
+    say!("Unsaved {} posts.", unsaved);
+    Ok(unsaved)
+}
+
+/// Takes back the account's upvotes, downvotes or both, or just lists them for a dry run.
+/// Returns how many votes were cleared.
+async fn purge_votes(username: String, votes: &str, options: RunOptions) -> Result<usize> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["vote"])?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let mut voted: Vec<reddit_api::DeletionInfo> = Vec::new();
+    if votes != "down" {
+        voted.extend(client.upvoted().await?);
+    }
+    if votes != "up" {
+        voted.extend(client.downvoted().await?);
+    }
+    if let Some(limit) = options.limit {
+        voted.truncate(limit);
+    }
+    if !voted.is_empty() {
+        info!("Clearing votes on:")
+    }
+    for p in &voted {
+        print_item(&ai, &zone, p, options.preview_length);
+    }
+    say!("Found {} votes.", voted.len());
+    if options.dry {
+        say!("Dry run flag present. Skipping vote clearing.");
+        return Ok(0);
+    }
+    if !options.yes && !voted.is_empty() {
+        let prompt = format!("Clear {} votes? [y/N] ", voted.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, no votes were cleared.");
+            return Ok(0);
+        }
+    }
+    let mut cleared = 0;
+    for p in &voted {
+        match client.clear_vote(&p.name).await {
+            Ok(()) => cleared += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to clear a vote, run `redelete authorize` again to let redelete clear votes.");
+                break;
+            }
+            Err(e) => say!("Unable to clear vote on {}: {}", p.name, e),
+        }
+    }
+    say!("Cleared {} votes.", cleared);
+    Ok(cleared)
+}
+
+/// Unhides every submission the account has hidden, or with `hide_own` hides every one of
+/// its own submissions instead. A dry run just lists them.
+async fn purge_hidden(username: String, hide_own: bool, options: RunOptions) -> Result<()> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["report"])?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let (verb, mut posts) = if hide_own {
+        ("Hide", client.posts().await?)
+    } else {
+        ("Unhide", client.hidden().await?)
+    };
+    if let Some(limit) = options.limit {
+        posts.truncate(limit);
+    }
+    for p in &posts {
+        print_item(&ai, &zone, p, options.preview_length);
+    }
+    say!(
+        "Found {} submissions to {}.",
+        posts.len(),
+        verb.to_lowercase()
+    );
+    if options.dry {
+        say!("Dry run flag present. Skipping {}.", verb.to_lowercase());
+        return Ok(());
+    }
+    if !options.yes && !posts.is_empty() {
+        let prompt = format!("{} {} submissions? [y/N] ", verb, posts.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nothing was changed.");
+            return Ok(());
+        }
+    }
+    let mut done = 0;
+    for p in &posts {
+        let result = if hide_own {
+            client.hide(&p.name).await
+        } else {
+            client.unhide(&p.name).await
+        };
+        match result {
+            Ok(()) => done += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to {}, run `redelete authorize` again to let redelete hide and unhide submissions.", verb.to_lowercase());
+                break;
+            }
+            Err(e) => say!("Unable to {} {}: {}", verb.to_lowercase(), p.name, e),
+        }
+    }
+    say!(
+        "{} {} submissions.",
+        if hide_own { "Hid" } else { "Unhid" },
+        done
+    );
+    Ok(())
+}
+
+/// Unsubscribes from every subreddit, or only those matching `only`, skipping any matching
+/// `keep`. A dry run just lists them. Returns how many were unsubscribed from.
+async fn unsubscribe_all(
+    username: String,
+    only: Option<Vec<String>>,
+    keep: Option<Vec<String>>,
+    options: RunOptions,
+) -> Result<usize> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["mysubreddits", "subscribe"])?;
+    let subscriptions = client.subscriptions().await?;
+    let total = subscriptions.len();
+    let targets = unsubscribe_targets(subscriptions, &only, &keep);
+    for subreddit in &targets {
+        info!("/r/{}", subreddit.display_name);
+    }
+    say!(
+        "Unsubscribing from {} of {} subreddits.",
+        targets.len(),
+        total
+    );
+    if options.dry {
+        say!("Dry run flag present. Skipping unsubscribe.");
+        return Ok(0);
+    }
+    if !options.yes && !targets.is_empty() {
+        let prompt = format!("Unsubscribe from {} subreddits? [y/N] ", targets.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, still subscribed.");
+            return Ok(0);
+        }
+    }
+    let mut unsubscribed = 0;
+    for subreddit in &targets {
+        match client.unsubscribe(subreddit).await {
+            Ok(()) => unsubscribed += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to unsubscribe, run `redelete authorize` again to let redelete manage subscriptions.");
+                break;
+            }
+            Err(e) => say!(
+                "Unable to unsubscribe from /r/{}: {}",
+                subreddit.display_name,
+                e
+            ),
+        }
+    }
+    say!("Unsubscribed from {} subreddits.", unsubscribed);
+    Ok(unsubscribed)
+}
+
+/// The subscriptions matched by `only`, or all of them, minus those matched by `keep`.
+fn unsubscribe_targets(
+    subscriptions: Vec<reddit_api::Subreddit>,
+    only: &Option<Vec<String>>,
+    keep: &Option<Vec<String>>,
+) -> Vec<reddit_api::Subreddit> {
+    subscriptions
+        .into_iter()
+        .filter(|s| {
+            only.as_ref().map_or(true, |only| {
+                patterns::subreddit_matches(only, &s.display_name).unwrap_or(false)
+            })
+        })
+        .filter(|s| {
+            !keep.as_ref().map_or(false, |keep| {
+                patterns::subreddit_matches(keep, &s.display_name).unwrap_or(true)
+            })
+        })
+        .collect()
+}
+
+/// Lists the account's multireddits, and deletes them all when `delete_all` is set and it
+/// isn't a dry run.
+async fn multireddits(username: String, delete_all: bool, options: RunOptions) -> Result<()> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["mysubreddits", "subscribe"])?;
+    let multis = client.multis().await?;
+    for multi in &multis {
+        say!(
+            "{} ({}, {} subreddits)",
+            multi.display_name,
+            multi.path,
+            multi.subreddits.len()
+        );
+    }
+    say!("Found {} multireddits.", multis.len());
+    if !delete_all || multis.is_empty() {
+        return Ok(());
+    }
+    if options.dry {
+        say!("Dry run flag present. Skipping delete operation.");
+        return Ok(());
+    }
+    if !options.yes {
+        let prompt = format!(
+            "Delete {} multireddits? This can't be undone. [y/N] ",
+            multis.len()
+        );
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nothing was deleted.");
+            return Ok(());
+        }
+    }
+    let mut deleted = 0;
+    for multi in &multis {
+        match client.delete_multi(multi).await {
+            Ok(()) => deleted += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to delete a multireddit, run `redelete authorize` again to let redelete manage them.");
+                break;
+            }
+            Err(e) => say!("Unable to delete {}: {}", multi.path, e),
+        }
+    }
+    say!("Deleted {} multireddits.", deleted);
+    Ok(())
+}
+
+/// Lists the account's friends, and removes them all when `remove_all` is set and it isn't
+/// a dry run.
+async fn friends(username: String, remove_all: bool, options: RunOptions) -> Result<()> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["mysubreddits", "subscribe"])?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let friends = client.friends().await?;
+    for friend in &friends {
+        match friend.date {
+            Some(date) => say!("/u/{} (added {})", friend.name, zone.format_timestamp(date)),
+            None => say!("/u/{}", friend.name),
+        }
+    }
+    say!("Found {} friends.", friends.len());
+    if !remove_all || friends.is_empty() {
+        return Ok(());
+    }
+    if options.dry {
+        say!("Dry run flag present. Skipping removal.");
+        return Ok(());
+    }
+    if !options.yes {
+        let prompt = format!("Remove {} friends? [y/N] ", friends.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nobody was removed.");
+            return Ok(());
+        }
+    }
+    let mut removed = 0;
+    for friend in &friends {
+        match client.remove_friend(friend).await {
+            Ok(()) => removed += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to remove a friend, run `redelete authorize` again to let redelete manage them.");
+                break;
+            }
+            Err(e) => say!("Unable to remove /u/{}: {}", friend.name, e),
+        }
+    }
+    say!("Removed {} friends.", removed);
+    Ok(())
+}
+
+/// Lists the users the account has blocked, writes them to `export_path` if given, and unblocks
+/// them all when `unblock_all` is set and it isn't a dry run.
+async fn blocked_users(
+    username: String,
+    export_path: Option<&Path>,
+    unblock_all: bool,
+    options: RunOptions,
+) -> Result<()> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["mysubreddits", "subscribe"])?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let blocked = client.blocked().await?;
+    for user in &blocked {
+        match user.date {
+            Some(date) => say!("/u/{} (blocked {})", user.name, zone.format_timestamp(date)),
+            None => say!("/u/{}", user.name),
+        }
+    }
+    say!("Found {} blocked users.", blocked.len());
+    if let Some(path) = export_path {
+        export::export_users(path, &blocked)?;
+        say!(
+            "Exported {} blocked users to {}",
+            blocked.len(),
+            path.display()
+        );
+    }
+    if !unblock_all || blocked.is_empty() {
+        return Ok(());
+    }
+    if options.dry {
+        say!("Dry run flag present. Skipping unblock.");
+        return Ok(());
+    }
+    if !options.yes {
+        let prompt = format!("Unblock {} users? [y/N] ", blocked.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nobody was unblocked.");
+            return Ok(());
+        }
+    }
+    let own_fullname = client.own_fullname().await?;
+    let mut unblocked = 0;
+    for user in &blocked {
+        match client.unblock(user, &own_fullname).await {
+            Ok(()) => unblocked += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to unblock, run `redelete authorize` again to let redelete manage blocked users.");
+                break;
+            }
+            Err(e) => say!("Unable to unblock /u/{}: {}", user.name, e),
+        }
+    }
+    say!("Unblocked {} users.", unblocked);
+    Ok(())
+}
+
+/// Deletes the account's private messages, inbox and sent, or just lists them for a dry
+/// run. Returns how many were deleted.
+async fn delete_messages(username: String, options: RunOptions) -> Result<usize> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["privatemessages"])?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let messages = client.messages().await?;
+    for message in &messages {
+        info!(
+            "{} {} -> {}: {}",
+            zone.format_timestamp(message.created_utc),
+            message.author.as_deref().unwrap_or("[deleted]"),
+            message.dest.as_deref().unwrap_or("[deleted]"),
+            message.subject
+        );
+    }
+    say!("Found {} messages.", messages.len());
+    if options.dry {
+        say!("Dry run flag present. Skipping delete operation.");
+        return Ok(0);
+    }
+    if !options.yes && !messages.is_empty() {
+        let prompt = format!("Delete {} messages? [y/N] ", messages.len());
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nothing was deleted.");
+            return Ok(0);
+        }
+    }
+    let mut deleted = 0;
+    for message in &messages {
+        match client.delete_message(&message.name).await {
+            Ok(()) => deleted += 1,
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to delete a message, run `redelete authorize` again to let redelete delete messages.");
+                break;
+            }
+            Err(e) => say!("Unable to delete {}: {}", message.name, e),
+        }
+    }
+    say!("Deleted {} messages.", deleted);
+    Ok(deleted)
+}
+
+/// Posts the items in `backup` with the given fullnames again, as new comments and self
+/// posts. Link posts and items the backup has too little of are skipped.
+async fn restore_items(
+    username: String,
+    backup: &Path,
+    fullnames: &[String],
+    options: RunOptions,
+) -> Result<()> {
+    let (client, ai, _) = prepare_run(username, &options)?;
+    check_scopes(&ai, &["submit"])?;
+    let items = restore::read_items(backup)?;
+    let mut reposts = Vec::new();
+    for fullname in fullnames {
+        let item = items
+            .iter()
+            .find(|item| item.get("name").and_then(serde_json::Value::as_str) == Some(fullname));
+        match item.map(restore::repost) {
+            Some(Ok(repost)) => reposts.push((fullname, repost)),
+            Some(Err(reason)) => say!("Skipping {}, {}.", fullname, reason),
+            None => say!("Skipping {}, it isn't in {}.", fullname, backup.display()),
+        }
+    }
+    for (fullname, repost) in &reposts {
+        match repost {
+            restore::Repost::SelfPost {
+                subreddit, title, ..
+            } => info!("{} self post \"{}\" to /r/{}", fullname, title, subreddit),
+            restore::Repost::Comment { parent, text } => info!(
+                "{} comment on {}: {}",
+                fullname,
+                parent,
+                output::preview(text, options.preview_length)
+            ),
+        }
+    }
+    say!("Found {} items to restore.", reposts.len());
+    if options.dry {
+        say!("Dry run flag present. Skipping restore.");
+        return Ok(());
+    }
+    if !options.yes && !reposts.is_empty() {
+        let prompt = format!(
+            "Post {} items again? They'll be new posts under your account. [y/N] ",
+            reposts.len()
+        );
+        let stdin = std::io::stdin();
+        if !confirm(&prompt, stdin.lock()) {
+            say!("Cancelled, nothing was posted.");
+            return Ok(());
+        }
+    }
+    let mut restored = 0;
+    for (fullname, repost) in &reposts {
+        let posted = match repost {
+            restore::Repost::SelfPost {
+                subreddit,
+                title,
+                text,
+            } => client.submit_self(subreddit, title, text).await,
+            restore::Repost::Comment { parent, text } => client.comment(parent, text).await,
+        };
+        match posted {
+            Ok(new) => {
+                restored += 1;
+                verbose!("Restored {} as {}.", fullname, new);
+            }
+            Err(reddit_api::RedditApiError::Forbidden { .. }) => {
+                say!("reddit refused to post, run `redelete authorize` again to let redelete submit posts and comments.");
+                break;
+            }
+            Err(e) => say!("Unable to restore {}: {}", fullname, e),
+        }
+    }
+    say!("Restored {} items.", restored);
+    Ok(())
+}
+
+/// The steps of `redelete wipe`, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WipeStage {
+    Backup,
+    History,
+    Saved,
+    Votes,
+    Subscriptions,
+    Messages,
+}
+
+const WIPE_STAGES: [WipeStage; 6] = [
+    WipeStage::Backup,
+    WipeStage::History,
+    WipeStage::Saved,
+    WipeStage::Votes,
+    WipeStage::Subscriptions,
+    WipeStage::Messages,
+];
+
+impl WipeStage {
+    fn description(&self) -> &'static str {
+        match self {
+            WipeStage::Backup => "Back up your history",
+            WipeStage::History => "Delete comments/submissions",
+            WipeStage::Saved => "Unsave everything",
+            WipeStage::Votes => "Clear your votes",
+            WipeStage::Subscriptions => "Unsubscribe from every subreddit",
+            WipeStage::Messages => "Delete private messages",
+        }
+    }
+}
+
+/// Runs every `WipeStage` in order, asking before each one unless `options.yes` is set,
+/// then reports how each went. Nothing is deleted if the backup fails.
+async fn wipe(username: String, backup: &Path, options: RunOptions) -> Result<()> {
+    let mut results: Vec<(WipeStage, String)> = Vec::new();
+    for (i, stage) in WIPE_STAGES.iter().enumerate() {
+        let heading = format!(
+            "Step {} of {}: {}",
+            i + 1,
+            WIPE_STAGES.len(),
+            stage.description()
+        );
+        if !options.dry && !options.yes {
+            let stdin = std::io::stdin();
+            if !confirm(&format!("{}? [y/N] ", heading), stdin.lock()) {
+                results.push((*stage, String::from("skipped")));
+                continue;
+            }
+        }
+        say!("{}", heading.bold());
+        let stage_options = RunOptions {
+            dry: options.dry,
+            yes: true,
+            preview_length: options.preview_length,
+            ..Default::default()
+        };
+        let outcome = match stage {
+            WipeStage::Backup => archive_history(
+                username.clone(),
+                "json",
+                archive::GroupBy::Subreddit,
+                backup,
+            )
+            .await
+            .map(|()| format!("saved to {}", backup.display())),
+            WipeStage::History => run(username.clone(), stage_options)
+                .await
+                .map(|()| String::from("done")),
+            WipeStage::Saved => purge_saved(username.clone(), stage_options)
+                .await
+                .map(|n| format!("{} unsaved", n)),
+            WipeStage::Votes => purge_votes(username.clone(), "both", stage_options)
+                .await
+                .map(|n| format!("{} cleared", n)),
+            WipeStage::Subscriptions => {
+                unsubscribe_all(username.clone(), None, None, stage_options)
+                    .await
+                    .map(|n| format!("{} unsubscribed", n))
+            }
+            WipeStage::Messages => delete_messages(username.clone(), stage_options)
+                .await
+                .map(|n| format!("{} deleted", n)),
+        };
+        match outcome {
+            Ok(outcome) => results.push((*stage, outcome)),
+            Err(e) if *stage == WipeStage::Backup => {
+                say!("Unable to back up, stopping before anything is deleted.");
+                return Err(e);
+            }
+            Err(e) => {
+                say!("{} failed: {}", stage.description(), e);
+                results.push((*stage, format!("failed: {}", e)));
+            }
+        }
+    }
+    say!("{}", wipe_report(&results, options.dry));
+    Ok(())
+}
+
+fn wipe_report(results: &[(WipeStage, String)], dry: bool) -> String {
+    let mut lines = vec![String::from(if dry {
+        "Wipe finished (dry run, nothing was changed):"
+    } else {
+        "Wipe finished:"
+    })];
+    for (stage, outcome) in results {
+        lines.push(format!("  {:<34} {}", stage.description(), outcome));
+    }
+    lines.join("\n")
+}
+
+/// Revokes the account's tokens with reddit, then removes it from the config. The account
+/// stays saved if reddit can't be reached, so logging out can be tried again.
+async fn logout(ai: config::AccountInfo) -> Result<()> {
+    reddit_api::revoke_tokens(&ai.token).await?;
+    config::delete_user(&ai.username)?;
+    Ok(())
+}
+
+/// Checks the account's token against `/api/v1/me` and reports what it's allowed to do.
+async fn whoami(ai: config::AccountInfo) -> Result<()> {
+    let client = reddit_api::RedditClient::new(ai.username.clone());
+    let verified = client.verified_username().await;
+    // Verifying refreshes an expired token, show the one that's saved now.
+    let ai = config::read_config_account_info(&ai.username).unwrap_or(ai);
+    let now = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    say!(
+        "{}",
+        token_info(&ai, verified.as_ref().ok().map(String::as_str), now)
+    );
+    verified?;
+    Ok(())
+}
+
+/// The token's expiry and scopes, and whether reddit accepted it as `verified`.
+fn token_info(ai: &config::AccountInfo, verified: Option<&str>, now: u64) -> String {
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let mut lines = vec![format!("Account: /u/{}", ai.username)];
+    lines.push(match verified {
+        Some(name) if name == ai.username => String::from("Verified: reddit accepts the token"),
+        Some(name) => format!("Verified: reddit accepts the token, but for /u/{}", name),
+        None => String::from("Verified: no, reddit refused the token"),
+    });
+    lines.push(format!(
+        "Token: {}, expires {}",
+        ai.token_status(now).description(),
+        zone.format_timestamp(ai.token_expires as f64)
+    ));
+    let granted = ai.token.scopes();
+    lines.push(format!("Granted scopes: {}", granted.join(", ")));
+    let missing: Vec<&str> = reddit_api::requested_scopes()
+        .into_iter()
+        .filter(|scope| !granted.iter().any(|g| g == scope))
+        .collect();
+    if !missing.is_empty() {
+        lines.push(format!(
+            "Missing scopes: {} (run `redelete authorize --reauthorize {}` to grant them)",
+            missing.join(", "),
+            ai.username
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Deletes exactly the items in a plan from `redelete plan`, without fetching the history.
+async fn apply_plan(path: &Path, options: RunOptions) -> Result<()> {
+    let started = time::Instant::now();
+    let plan = plan::read_plan(path)?;
+    let (client, ai, filters) = prepare_run(plan.username, &options)?;
+    pre_run_hook(&ai, &options)?;
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    if !plan.items.is_empty() {
+        info!("Deleting comments/submissions:");
+    }
+    for p in &plan.items {
+        print_item(&ai, &zone, p, options.preview_length);
+    }
+    let selection = Selection {
+        scanned: plan.items.len(),
+        matched: plan.items,
+        remaining: 0,
+        kept_by_settings: HashSet::new(),
+    };
+    delete_selection(&client, &ai, &options, &filters, selection, zone, started).await
+}
+
+/// Items can be deleted page by page while the rest of the history downloads, unless
+/// something needs every item first: keep latest/top, backfilling, a single thread, the
+/// confirmation prompt, a backup, an export, or a `--delay` that Ctrl-C should be able to
+/// stop.
+fn can_stream(ai: &config::AccountInfo, options: &RunOptions) -> bool {
+    options.thread.is_none()
+        && options.fullnames.is_none()
+        && ai.archive_api.is_none()
+        && !options.backfill
+        && options.pace.is_none()
+        && options.export.is_none()
+        && !options.trash
+        && (options.dry || (options.yes && !ai.backup))
+        && ai.keep_latest.is_none()
+        && ai.keep_top_per_subreddit.is_none()
+        && ai.keep_top_percent.is_none()
+}
+
+/// The history from `--archive` when working offline, otherwise from reddit.
+async fn load_history(
+    client: &reddit_api::RedditClient,
+    ai: &config::AccountInfo,
+    options: &RunOptions,
+) -> Result<Vec<reddit_api::DeletionInfo>> {
+    match &options.archive {
+        Some(path) => read_archive(path),
+        None => Ok(fetch_history(client, ai, options).await?),
+    }
+}
+
+fn read_archive(path: &str) -> Result<Vec<reddit_api::DeletionInfo>> {
+    let all = export::read_history(Path::new(path))?;
+    info!("Read {} comments/submissions from {}.", all.len(), path);
+    Ok(all)
+}
+
+async fn fetch_history(
+    client: &reddit_api::RedditClient,
+    ai: &config::AccountInfo,
+    options: &RunOptions,
+) -> reddit_api::Result<Vec<reddit_api::DeletionInfo>> {
+    if let Some(fullnames) = &options.fullnames {
+        return client.info(fullnames).await;
+    }
+    match &options.thread {
+        Some(article) => client.thread_comments(article).await,
+        None => {
+            let all = client.history().await?;
+            match &ai.archive_api {
+                Some(api) => with_archived(client, api, all).await,
+                None => Ok(all),
+            }
+        }
+    }
+}
+
+/// Adds the items the archive at `api` knows about that the listings didn't return,
+/// looked up on reddit so ones already deleted drop out and the filters see current
+/// scores. An archive that can't be reached only costs a warning.
+async fn with_archived(
+    client: &reddit_api::RedditClient,
+    api: &str,
+    mut all: Vec<reddit_api::DeletionInfo>,
+) -> reddit_api::Result<Vec<reddit_api::DeletionInfo>> {
+    let fullnames = match pushshift::discover(api, &client.username).await {
+        Ok(fullnames) => fullnames,
+        Err(e) => {
+            say!(
+                "Unable to search {}, only using reddit's listings: {}",
+                api,
+                e
+            );
+            return Ok(all);
+        }
+    };
+    let listed: HashSet<String> = all.iter().map(|p| p.name.clone()).collect();
+    let older: Vec<String> = fullnames
+        .into_iter()
+        .filter(|name| !listed.contains(name))
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect();
+    if !older.is_empty() {
+        say!(
+            "{} knows about {} more comments/submissions, looking them up on reddit.",
+            api,
+            older.len()
+        );
+        all.extend(client.info(&older).await?);
+    }
+    Ok(all)
+}
+
+/// Like `run`, but filters and deletes each page of history as soon as it arrives.
+async fn run_streaming(
+    client: &reddit_api::RedditClient,
+    ai: &config::AccountInfo,
+    options: &RunOptions,
+    filters: &Filters,
+    started: time::Instant,
+) -> Result<()> {
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let mut summary = report::RunSummary {
+        username: String::from(&client.username),
+        dry_run: options.dry,
+        zone: zone.clone(),
+        ..Default::default()
+    };
+    let mut quarantined = 0;
+    let mut deleted: HashSet<String> = HashSet::new();
+    let mut failed: Vec<journal::FailedDeletion> = Vec::new();
+    let kept = state::kept(&client.username)?;
+    let already_deleted = state::deleted(&client.username)?;
+    let mut skipped = 0;
+    let pages = client.history_pages();
+    futures::pin_mut!(pages);
+    let mut next = pages.next().await;
+    while let Some(page) = next {
+        let page = match page {
+            Err(e) if e.stops_run() => {
+                say!("{}, stopping before the rest of your history.", e);
+                break;
+            }
+            page => page?,
+        };
+        // The next page is asked for after this page's last item, which reddit no longer
+        // finds once it's deleted, so fetch it before deleting anything here.
+        next = pages.next().await;
+        summary.scanned += page.len();
+        quarantined += page.iter().filter(|p| p.quarantined).count();
+        let mut to_delete: Vec<audit::Item> = Vec::new();
+        for p in page {
+            if already_deleted.contains(&p.name) {
+                item_event(&p, "deleted");
+                continue;
+            }
+            if kept.contains(&p.name) {
+                item_event(&p, "kept");
+                skipped += 1;
+                continue;
+            }
+            if !should_delete(ai, filters, &p) {
+                item_event(&p, "keep");
+                verbose!(
+                    "{}",
+                    item_line("KEEP", now_utc(), &p, options.preview_length)
+                );
+                continue;
+            }
+            if options
+                .limit
+                .map_or(false, |limit| summary.matched >= limit)
+            {
+                item_event(&p, "over_limit");
+                summary.remaining += 1;
+                continue;
+            }
+            if summary.matched == 0 {
+                info!("Deleting comments/submissions:")
+            }
+            item_event(&p, "delete");
+            print_item(ai, &zone, &p, options.preview_length);
+            summary.matched += 1;
+            to_delete.push(audit::Item::from(p));
+        }
+        if options.dry {
+            to_delete.iter().for_each(|i| summary.tally(i));
+            audit::record_deletions(&client.username, &to_delete, true)?;
+        } else if !to_delete.is_empty() {
+            let mut attempted: HashSet<String> = to_delete.iter().map(|i| i.name.clone()).collect();
+            let outcome = delete_all(client, to_delete, None, options.shred.as_ref()).await?;
+            summary.failed += outcome.failed.len();
+            summary.remaining += outcome.deferred.len();
+            summary.deleted += attempted.len() - outcome.failed.len() - outcome.deferred.len();
+            outcome.deleted.iter().for_each(|i| summary.tally(i));
+            for f in outcome.failed.iter().chain(&outcome.deferred) {
+                attempted.remove(&f.name);
+            }
+            deleted.extend(attempted);
+            failed.extend(outcome.failed.iter().cloned());
+            journal::record_failures(&client.username, outcome.journal())?;
+            if !outcome.deferred.is_empty() {
+                break;
+            }
+        }
+    }
+    if options.verify && !options.dry {
+        failed.extend(verify_run(client, deleted, &mut summary).await?);
+    }
+    report_quarantined(ai, quarantined);
+    report_kept(skipped);
+    if summary.matched == 0 {
+        say!("No comments or submissions to delete.");
+    }
+    report_remaining(summary.remaining);
+    if options.dry {
+        say!("Dry run flag present. Skipping delete operation.");
+    }
+    summary.elapsed = started.elapsed();
+    summary.requests = client.requests_made();
+    finish_run(ai, &summary, &failed).await
+}
+
+/// Reports on a finished run. Failed deletions don't stop a run, but they make it
+/// return an error at the end so the exit status shows something went wrong.
+async fn finish_run(
+    ai: &config::AccountInfo,
+    summary: &report::RunSummary,
+    failed: &Vec<journal::FailedDeletion>,
+) -> Result<()> {
+    say!("{}", summary.body());
+    output::event("summary", summary.to_json());
+    send_report(ai, summary).await;
+    if !summary.dry_run {
+        let finished = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if let Err(e) = config::set_last_run(ai.username.clone(), finished) {
+            verbose!("Unable to record the run in the config: {}", e);
+        }
+    }
+    if let Some(command) = &ai.post_run_hook {
+        verbose!("Running post run hook: {}", command);
+        if let Err(e) = run_hook::run(command, &run_hook::post_run_env(summary)) {
+            say!("{}", e);
+        }
+    }
+    check_failures(failed)
+}
+
+fn check_failures(failed: &Vec<journal::FailedDeletion>) -> Result<()> {
+    if failed.is_empty() {
+        return Ok(());
+    }
+    say!("{}", failure_table(failed));
+    Err(RedeleteError::DeletionsFailed {
+        count: failed.len(),
+    })
+}
+
+/// Lines up the name, subreddit and error of every failed deletion in columns.
+fn failure_table(failed: &Vec<journal::FailedDeletion>) -> String {
+    let name_width = failed
+        .iter()
+        .map(|f| f.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let subreddit_width = failed
+        .iter()
+        .map(|f| f.subreddit.len() + 3)
+        .max()
+        .unwrap_or(0)
+        .max(9);
+    let mut lines = vec![format!(
+        "{:name$}  {:subreddit$}  {}",
+        "NAME",
+        "SUBREDDIT",
+        "ERROR",
+        name = name_width,
+        subreddit = subreddit_width
+    )];
+    for f in failed {
+        lines.push(format!(
+            "{:name$}  {:subreddit$}  {}",
+            f.name,
+            format!("/r/{}", f.subreddit),
+            f.error,
+            name = name_width,
+            subreddit = subreddit_width
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Re-fetches the history and checks every item in `deleted` is gone. Reddit sometimes
+/// accepts a delete call without removing anything, so leftovers are recorded as failures
+/// for `redelete retry`.
+async fn verify_run(
+    client: &reddit_api::RedditClient,
+    deleted: HashSet<String>,
+    summary: &mut report::RunSummary,
+) -> Result<Vec<journal::FailedDeletion>> {
+    if deleted.is_empty() {
+        return Ok(Vec::new());
+    }
+    say!("Verifying {} deletions.", deleted.len());
+    let history = match client.history().await {
+        Err(e) if e.stops_run() => {
+            say!("{}, skipping verification.", e);
+            return Ok(Vec::new());
+        }
+        result => result?,
+    };
+    let leftovers = still_listed(history.into_iter(), &deleted);
+    if leftovers.is_empty() {
+        say!("All deletions verified.");
+        return Ok(leftovers);
+    }
+    say!("Reddit still lists {} deleted posts:", leftovers.len());
+    for f in &leftovers {
+        say!("{} @ /r/{}", &f.name, &f.subreddit);
+        result_event(&f.name, &f.subreddit, "failed", Some(&f.error));
+    }
+    summary.failed += leftovers.len();
+    summary.deleted -= leftovers.len();
+    journal::record_failures(&client.username, leftovers.clone())?;
+    Ok(leftovers)
+}
+
+fn still_listed<I: Iterator<Item = reddit_api::DeletionInfo>>(
+    listed: I,
+    deleted: &HashSet<String>,
+) -> Vec<journal::FailedDeletion> {
+    listed
+        .filter(|p| deleted.contains(&p.name))
+        .map(|p| {
+            journal::FailedDeletion::new(
+                p.name,
+                p.subreddit,
+                String::from("Still listed after deletion"),
+            )
+        })
+        .collect()
+}
+
+fn should_delete(
+    ai: &config::AccountInfo,
+    filters: &Filters,
+    info: &reddit_api::DeletionInfo,
+) -> bool {
+    check_should_delete(ai, &filters.rule, info)
+        && filter_command_allows(ai, info)
+        && script_allows(&filters.script, info)
+}
+
+/// Says what was decided about an item, in JSON output only. The decision is "delete",
+/// "keep", "kept" for items kept with `redelete keep`, "deleted" for items an earlier run
+/// deleted, or "over_limit".
+fn item_event(p: &reddit_api::DeletionInfo, decision: &str) {
+    output::event(
+        "item",
+        json!({
+            "name": p.name,
+            "subreddit": p.subreddit,
+            "score": p.score,
+            "created_utc": p.created_utc,
+            "decision": decision,
+        }),
+    );
+}
+
+/// Says how a deletion went, in JSON output only. `error` is `None` for deletions that worked.
+fn result_event(name: &str, subreddit: &str, status: &str, error: Option<&str>) {
+    output::event(
+        "result",
+        json!({
+            "name": name,
+            "subreddit": subreddit,
+            "status": status,
+            "error": error,
+        }),
+    );
+}
+
+/// e.g. "DELETE comment 3 years ago, score 12, /r/rust: the comment on one line",
+/// colored when the terminal allows it.
+fn item_line(
+    marker: &str,
+    now: f64,
+    p: &reddit_api::DeletionInfo,
+    preview_length: Option<usize>,
+) -> String {
+    let marker = if marker == "DELETE" {
+        marker.red().bold()
+    } else {
+        marker.green().bold()
+    };
+    let score = p.score.to_string();
+    let score = if p.score < 0 {
+        score.red()
+    } else {
+        score.yellow()
+    };
+    format!(
+        "{} {} {}, score {}, {}: {}",
+        marker,
+        p.kind(),
+        p.age(now),
+        score,
+        format!("/r/{}", &p.subreddit).cyan(),
+        output::preview(&p.summary(), preview_length)
+    )
+}
+
+/// One line saying which filters a run would apply, for `redelete accounts`.
+fn filter_summary(ai: &config::AccountInfo) -> String {
+    let count = |list: &Option<Vec<String>>| list.as_ref().map_or(0, |l| l.len());
+    let mut filters = Vec::new();
+    if let Some(hours) = ai.max_hours {
+        filters.push(format!("older than {}h", hours));
+    }
+    if let Some(score) = ai.minimum_score {
+        filters.push(format!("score >= {}", score));
+    }
+    if let Some(score) = ai.max_score {
+        filters.push(format!("score <= {}", score));
+    }
+    if count(&ai.included_subreddits) > 0 {
+        filters.push(format!(
+            "only {} subreddits",
+            count(&ai.included_subreddits)
+        ));
+    }
+    if count(&ai.excluded_subreddits) > 0 {
+        filters.push(format!(
+            "{} excluded subreddits",
+            count(&ai.excluded_subreddits)
+        ));
+    }
+    if count(&ai.keywords) > 0 {
+        filters.push(format!("{} keywords", count(&ai.keywords)));
+    }
+    if count(&ai.match_patterns) > 0 {
+        filters.push(format!("{} patterns", count(&ai.match_patterns)));
+    }
+    if let Some(latest) = ai.keep_latest {
+        filters.push(format!("keep latest {}", latest));
+    }
+    if let Some(top) = ai.keep_top_per_subreddit {
+        filters.push(format!("keep top {} per subreddit", top));
+    }
+    if let Some(percent) = ai.keep_top_percent {
+        filters.push(format!("keep top {}%", percent));
+    }
+    if ai.rule.is_some() {
+        filters.push(String::from("rule"));
+    }
+    if ai.filter_command.is_some() {
+        filters.push(String::from("filter command"));
+    }
+    if ai.script.is_some() {
+        filters.push(String::from("script"));
+    }
+    if filters.is_empty() {
+        String::from("none, deletes everything")
+    } else {
+        filters.join(", ")
+    }
+}
+
+/// A row per saved account with its token status, last run and filters.
+fn saved_accounts_table(accounts: &[config::AccountInfo], now: u64) -> String {
+    let rows: Vec<(String, &'static str, String, String)> = accounts
+        .iter()
+        .map(|ai| {
+            let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+            let last_run = match ai.last_run {
+                Some(last_run) => zone.format_timestamp(last_run as f64),
+                None => String::from("never"),
+            };
+            (
+                format!("/u/{}", ai.username),
+                ai.token_status(now).description(),
+                last_run,
+                filter_summary(ai),
+            )
+        })
+        .collect();
+    let user_width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0).max(8);
+    let token_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(5);
+    let run_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(8);
+    let mut lines = vec![format!(
+        "{:user$}  {:token$}  {:run$}  {}",
+        "USERNAME",
+        "TOKEN",
+        "LAST RUN",
+        "FILTERS",
+        user = user_width,
+        token = token_width,
+        run = run_width
+    )];
+    for (username, token, last_run, filters) in rows {
+        lines.push(format!(
+            "{:user$}  {:token$}  {:run$}  {}",
+            username,
+            token,
+            last_run,
+            filters,
+            user = user_width,
+            token = token_width,
+            run = run_width
+        ));
+    }
+    lines.join("\n")
+}
+
+fn now_utc() -> f64 {
+    chrono::Utc::now().timestamp() as f64
+}
+
+/// One line per item, with the exact date and link added at `-v`.
+fn print_item(
+    ai: &config::AccountInfo,
+    zone: &timezone::Zone,
+    p: &reddit_api::DeletionInfo,
+    preview_length: Option<usize>,
+) {
+    if p.archived && ai.archived == Some(config::ArchivedPolicy::Warn) {
+        info!(
+            "{} the next item is archived, reddit won't allow editing it.",
+            "Warning:".yellow()
+        );
+    }
+    info!("{}", item_line("DELETE", now_utc(), p, preview_length));
+    verbose!(
+        "    {} https://www.reddit.com{}",
+        zone.format_timestamp(p.created_utc),
+        &p.permalink
+    );
+}
+
+fn report_quarantined(ai: &config::AccountInfo, quarantined: usize) {
+    if quarantined > 0 && !ai.include_quarantined {
+        say!("Skipping {} posts in quarantined subreddits. Use `redelete config <username> --include-quarantined` to delete them too.", quarantined);
+    }
+}
+
+fn report_kept(skipped: usize) {
+    if skipped > 0 {
+        say!(
+            "Skipping {} posts you chose to keep with `redelete keep`.",
+            skipped
+        );
+    }
+}
+
+fn report_remaining(remaining: usize) {
+    if remaining > 0 {
+        say!(
+            "Limit reached, {} more posts still match and will be left for a later run.",
+            remaining
+        );
+    }
+}
+
+async fn send_report(ai: &config::AccountInfo, summary: &report::RunSummary) {
+    if let Some(smtp) = ai
+        .smtp
+        .as_ref()
+        .filter(|smtp| !smtp.failures_only || summary.failed > 0)
+    {
+        match email::send_report(smtp, summary).await {
+            Ok(()) => say!("Emailed run report to {}.", &smtp.to),
+            Err(e) => say!("Unable to email run report: {}", e),
+        }
+    }
+    if let Some(url) = &ai.webhook_url {
+        let payload = webhook::payload(summary, ai.webhook_format);
+        if let Err(e) = webhook::post(url, &payload).await {
+            say!("Unable to post the run summary to the webhook: {}", e);
+        }
+    }
+    if ai.desktop_notifications {
+        if let Err(e) = desktop::notify(summary) {
+            say!("Unable to show a desktop notification: {}", e);
+        }
+    }
+}
+
+async fn retry(username: String, dry: bool) -> Result<()> {
+    let failures = journal::read_failures(&username)?;
+    if failures.is_empty() {
+        say!("No failed deletions recorded for {}.", &username);
+        return Ok(());
+    }
+    say!("Failed deletions from previous runs:");
+    for f in &failures {
+        say!("{} @ /r/{}: {}", &f.name, &f.subreddit, &f.error);
+    }
+    if dry {
+        say!("Dry run flag present. Skipping delete operation.");
+        return Ok(());
+    }
+    let mut client = reddit_api::RedditClient::new(username);
+    if let Some(ai) = config::read_config_account_info(&client.username) {
+        client.include_quarantined = ai.include_quarantined;
+        if let Some(concurrency) = ai.concurrency {
+            client.concurrency = concurrency;
+        }
+    }
+    let to_delete = failures
+        .into_iter()
+        .map(|f| audit::Item::new(f.name, f.subreddit))
+        .collect();
+    let outcome = delete_all(&client, to_delete, None, None).await?;
+    journal::replace_failures(&client.username, outcome.journal())?;
+    check_failures(&outcome.failed)
+}
+
+/// Deletions from `delete_all` that didn't happen. Both kinds are saved in the journal
+/// for `redelete retry`, but only `failed` counts as an error.
+struct Outcome {
+    deleted: Vec<audit::Item>,
+    /// Reddit rejected these.
+    failed: Vec<journal::FailedDeletion>,
+    /// Never attempted, because the run was interrupted or ran out of requests or time.
+    deferred: Vec<journal::FailedDeletion>,
+}
+
+impl Outcome {
+    fn journal(&self) -> Vec<journal::FailedDeletion> {
+        self.failed.iter().chain(&self.deferred).cloned().collect()
+    }
+}
+
+/// Deletes `to_delete` one request at a time, waiting for `pace` before each. With `shred`,
+/// the editable items get every overwrite pass first, then everything is deleted. Items are
+/// `None` when Ctrl-C or a failure that stops the run came before they were deleted.
+async fn delete_paced(
+    client: &reddit_api::RedditClient,
+    to_delete: &[audit::Item],
+    pace: pacing::Pace,
+    shred: Option<&shred::Shred>,
+) -> Vec<Option<reddit_api::Result<()>>> {
+    let mut interrupt = Box::pin(tokio::signal::ctrl_c());
+    let mut results: Vec<Option<reddit_api::Result<()>>> = to_delete.iter().map(|_| None).collect();
+    let passes = shred.map_or(0, |shred| shred.passes.max(1));
+    let mut first = true;
+    // The passes over every item, with deleting as the last one.
+    for pass in 1..=passes + 1 {
+        let overwriting = pass <= passes;
+        if overwriting && pass > 1 {
+            tokio::time::delay_for(shred.unwrap().delay).await;
+        }
+        for (i, item) in to_delete.iter().enumerate() {
+            if results[i].is_some() || (overwriting && !item.editable) {
+                continue;
+            }
+            if !first && !pace.wait(&mut interrupt).await {
+                say!("Interrupted.");
+                return results;
+            }
+            first = false;
+            let result = match shred {
+                Some(shred) if overwriting => {
+                    let result = client.edit(&item.name, &shred.replacement()).await;
+                    if result.is_ok() && passes > 1 {
+                        info!("Overwrote {} ({}/{})", &item.name, pass, passes);
+                    }
+                    result
+                }
+                _ => client.delete_from(item.name.clone(), &item.subreddit).await,
+            };
+            let stop = match &result {
+                Err(e) => e.stops_run(),
+                Ok(_) => false,
+            };
+            if result.is_err() || !overwriting {
+                results[i] = Some(result);
+            }
+            if stop {
+                return results;
+            }
+        }
+    }
+    results
+}
+
+/// Deletes every item and records the successful ones in the audit log. Several deletions run at once unless there's a `pace`, then they are made one at a
+/// time, and Ctrl-C during a pause stops before the next one.
+async fn delete_all(
+    client: &reddit_api::RedditClient,
+    to_delete: Vec<audit::Item>,
+    pace: Option<pacing::Pace>,
+    shred: Option<&shred::Shred>,
+) -> Result<Outcome> {
+    let results: Vec<Option<reddit_api::Result<()>>> = match pace {
+        None => {
+            let items: Vec<(String, String, bool)> = to_delete
+                .iter()
+                .map(|i| (i.name.clone(), i.subreddit.clone(), i.editable))
+                .collect();
+            client
+                .delete_many(&items, shred)
+                .await
+                .into_iter()
+                .map(Some)
+                .collect()
+        }
+        Some(pace) => delete_paced(client, &to_delete, pace, shred).await,
+    };
+    let mut deleted = Vec::new();
+    let mut failures = Vec::new();
+    let mut deferred = Vec::new();
+    let mut refused = Vec::new();
+    let mut results = results.into_iter();
+    for item in to_delete {
+        match results.next().flatten() {
+            Some(Ok(())) => deleted.push(item),
+            None => deferred.push(journal::FailedDeletion::new(
+                item.name,
+                item.subreddit,
+                String::from("Interrupted before deleting"),
+            )),
+            Some(Err(e)) if e.stops_run() => deferred.push(journal::FailedDeletion::new(
+                item.name,
+                item.subreddit,
+                e.to_string(),
+            )),
+            Some(Err(e)) => {
+                if let reddit_api::RedditApiError::Forbidden { reason } = &e {
+                    refused.push(format!(
+                        "{} @ /r/{} ({})",
+                        &item.name, &item.subreddit, reason
+                    ));
+                }
+                failures.push(journal::FailedDeletion::new(
+                    item.name,
+                    item.subreddit,
+                    e.to_string(),
+                ))
+            }
+        }
+    }
+    for i in &deleted {
+        result_event(&i.name, &i.subreddit, "deleted", None);
+    }
+    for f in &failures {
+        result_event(&f.name, &f.subreddit, "failed", Some(&f.error));
+    }
+    for f in &deferred {
+        result_event(&f.name, &f.subreddit, "deferred", Some(&f.error));
+    }
+    if !refused.is_empty() {
+        say!(
+            "Reddit refused access to {} posts in quarantined, banned or private subreddits:",
+            refused.len()
+        );
+        for r in &refused {
+            say!("{}", r);
+        }
+    }
+    // The posts are gone either way, so a log that can't be written mustn't keep the
+    // caller from journaling the failures.
+    if let Err(e) = audit::record_deletions(&client.username, &deleted, false) {
+        say!(
+            "{} unable to write the audit log: {}",
+            "Warning:".yellow(),
+            e
+        );
+    }
+    let deleted_count = deleted.len();
+    let deleted_pairs: Vec<(String, String)> = deleted
+        .iter()
+        .map(|i| (i.name.clone(), i.subreddit.clone()))
+        .collect();
+    if let Err(e) = state::record(&client.username, &deleted_pairs, state::Decision::Deleted) {
+        say!(
+            "{} unable to record deleted posts in the state database: {}",
+            "Warning:".yellow(),
+            e
+        );
+    }
+    say!("Deleted {} posts.", deleted_count);
+    if !failures.is_empty() {
+        say!(
+            "{}",
+            format!("Failed to delete {} posts.", failures.len()).red()
+        );
+    }
+    if !deferred.is_empty() {
+        say!(
+            "Left {} posts for `redelete retry` to pick up later.",
+            deferred.len()
+        );
+    }
+    Ok(Outcome {
+        deleted,
+        failed: failures,
+        deferred,
+    })
+}
+
+/// Reads the filter overrides that `run` and `plan` share.
+fn filter_options(matches: &clap::ArgMatches) -> result::Result<RunOptions, String> {
+    let thread = match matches.value_of(LINK) {
+        Some(link) => match reddit_api::parse_thread_id(link) {
+            Some(id) => Some(id),
+            None => return Err(format!("Unable to find a thread id in {}.", link)),
+        },
+        None => None,
+    };
+    let match_patterns: Option<Vec<String>> = matches
+        .values_of(MATCH_PATTERN)
+        .map(|patterns| patterns.map(String::from).collect());
+    if let Some(match_patterns) = &match_patterns {
+        let to_check = match_patterns.iter().map(|p| p.as_str());
+        if let Err(e) = patterns::validate_patterns(to_check) {
+            return Err(e.to_string());
+        }
+    }
+    let included_subreddits: Option<Vec<String>> = matches
+        .values_of(ONLY_SUBREDDITS)
+        .map(|subreddits| subreddits.map(String::from).collect());
+    let max_score = if matches.is_present(MAX_SCORE) {
+        match value_t!(matches, MAX_SCORE, i32) {
+            Ok(score) => Some(score),
+            Err(_) => return Err(String::from("Max score requires an integer value.")),
+        }
+    } else {
+        None
+    };
+    let minimum_score = if matches.is_present(MIN_SCORE) {
+        match value_t!(matches, MIN_SCORE, i32) {
+            Ok(score) => Some(score),
+            Err(_) => return Err(String::from("Minimum score requires an integer value.")),
+        }
+    } else {
+        None
+    };
+    let max_hours = if matches.is_present(MAX_HOURS) {
+        match value_t!(matches, MAX_HOURS, u64) {
+            Ok(hours) => Some(hours),
+            Err(_) => return Err(String::from("Maximum hours requires an integer value.")),
+        }
+    } else {
+        None
+    };
+    let limit = if matches.is_present(LIMIT) {
+        match value_t!(matches, LIMIT, usize) {
+            Ok(limit) => Some(limit),
+            Err(_) => return Err(String::from("Limit requires a positive integer value.")),
+        }
+    } else {
+        None
+    };
+    let add_excluded: Vec<String> = matches
+        .values_of(ADD_EXCLUDED_SUBREDDITS)
+        .map(|subreddits| subreddits.map(String::from).collect())
+        .unwrap_or(Vec::new());
+    if let Err(e) = patterns::validate_subreddit_patterns(add_excluded.iter().map(|p| p.as_str())) {
+        return Err(e.to_string());
+    }
+    let remove_excluded: Vec<String> = matches
+        .values_of(REMOVE_EXCLUDED_SUBREDDITS)
+        .map(|subreddits| subreddits.map(String::from).collect())
+        .unwrap_or(Vec::new());
+    Ok(RunOptions {
+        backfill: matches.is_present(BACKFILL),
+        thread,
+        match_patterns,
+        included_subreddits,
+        max_score,
+        minimum_score,
+        max_hours,
+        add_excluded,
+        remove_excluded,
+        limit,
+        ..Default::default()
+    })
+}
+
+/// Global flags can come before or after the subcommand, so both places are checked. clap
+/// copies flags given before the subcommand down into it, hence `max` rather than a sum.
+fn output_level(matches: &clap::ArgMatches) -> output::Level {
+    let sub = matches.subcommand().1;
+    let quiet = matches.is_present(QUIET) || sub.map_or(false, |m| m.is_present(QUIET));
+    let verbose = matches
+        .occurrences_of(VERBOSE)
+        .max(sub.map_or(0, |m| m.occurrences_of(VERBOSE)));
+    output::Level::from_flags(quiet, verbose)
+}
+
+/// The account's settings for `view --format json`, without the oauth token.
+fn settings_json(ai: &config::AccountInfo) -> serde_json::Value {
+    let mut settings = serde_json::to_value(ai).expect("Unable to serialize account settings.");
+    if let Some(object) = settings.as_object_mut() {
+        object.remove("token");
+        object.remove("token_expires");
+        // Webhook urls carry their own credentials.
+        if ai.webhook_url.is_some() {
+            object.insert("webhook_url".into(), json!("<hidden>"));
+        }
+    }
+    settings
+}
+
+/// The same settings as `settings_json`, as toml. Toml has no null, so unset settings are
+/// left out.
+fn settings_toml(ai: &config::AccountInfo) -> result::Result<String, toml::ser::Error> {
+    let mut settings = settings_json(ai);
+    if let Some(object) = settings.as_object_mut() {
+        object.retain(|_, value| !value.is_null());
+    }
+    toml::to_string_pretty(&toml::Value::try_from(settings)?)
+}
+
+/// Opens the account's settings in the user's editor, saving them if they changed. Settings
+/// that don't parse can be edited again before anything is saved.
+fn edit_settings(username: &str) -> result::Result<bool, String> {
+    let original = config::editable_settings(username).map_err(|e| e.to_string())?;
+    // Other users can read the temp directory, so the name isn't guessable and the file is
+    // only readable by its owner.
+    let path = std::env::temp_dir().join(format!("redelete-{}.toml", nanoid::simple()));
+    config::create_private_file(&path)
+        .and_then(|mut file| file.write_all(original.as_bytes()))
+        .map_err(|e| e.to_string())?;
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let args: Vec<&str> = parts.collect();
+    let result = loop {
+        match std::process::Command::new(program)
+            .args(&args)
+            .arg(&path)
+            .status()
+        {
+            Ok(status) if status.success() => (),
+            Ok(status) => break Err(format!("{} exited with {}.", editor, status)),
+            Err(e) => break Err(format!("Unable to start {}. {}", editor, e)),
+        }
+        let edited = match std::fs::read_to_string(&path) {
+            Ok(edited) => edited,
+            Err(e) => break Err(e.to_string()),
+        };
+        if edited == original {
+            break Ok(false);
+        }
+        match config::set_edited_settings(username, &edited) {
+            Ok(()) => break Ok(true),
+            Err(e) => {
+                say!("{}", e);
+                let stdin = std::io::stdin();
+                if !confirm("Edit again? [y/N] ", stdin.lock()) {
+                    break Err(String::from("Settings weren't changed."));
+                }
+            }
+        }
+    };
+    std::fs::remove_file(&path).ok();
+    result
+}
+
+/// The secret of the user's own reddit app, from `REDELETE_CLIENT_SECRET` or typed in, so
+/// it isn't visible in the process list like an argument would be.
+fn read_client_secret<R: BufRead>(mut input: R) -> Option<String> {
+    if let Ok(secret) = std::env::var(CLIENT_SECRET_ENV_VAR) {
+        return Some(secret);
+    }
+    print!("Client secret: ");
+    std::io::stdout().flush().ok();
+    let mut secret = String::new();
+    input.read_line(&mut secret).ok()?;
+    match secret.trim() {
+        "" => None,
+        secret => Some(String::from(secret)),
+    }
+}
+
+/// Asks a yes/no question, anything but y or yes counts as no.
+fn confirm<R: BufRead>(prompt: &str, mut input: R) -> bool {
+    print!("{}", prompt);
+    std::io::stdout().flush().ok();
+    let mut answer = String::new();
+    if input.read_line(&mut answer).is_err() {
+        return false;
+    }
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        _ => false,
+    }
+}
+
+/// Sleeps until the next time the account's local clock reads `at` (HH:MM).
+async fn wait_until(at: &str, ai: &config::AccountInfo) -> result::Result<(), String> {
+    let time = timezone::parse_time_of_day(at)?;
+    let zone = timezone::Zone::from_config(&ai.timezone)?;
+    let now = chrono::Utc::now();
+    let next = zone.next_occurrence(time, now);
+    say!(
+        "Waiting until {} to run.",
+        zone.format_timestamp(next.timestamp() as f64)
+    );
+    let wait = (next - now)
+        .to_std()
+        .unwrap_or(time::Duration::from_secs(0));
+    tokio::time::delay_for(wait).await;
+    Ok(())
+}
+
+/// Builds a search from `search` flags. Dates are read in the account's time zone.
+fn search_options(
+    matches: &clap::ArgMatches,
+    ai: &config::AccountInfo,
+) -> result::Result<search::Search, String> {
+    let zone = timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+    let mut search = search::Search::new(
+        matches.value_of(QUERY).unwrap(),
+        matches.is_present(SEARCH_REGEX),
+    )?;
+    search.subreddits = matches
+        .values_of(SEARCH_SUBREDDITS)
+        .map(|subreddits| subreddits.map(String::from).collect());
+    if let Some(date) = matches.value_of(AFTER) {
+        search.after = Some(zone.start_of_day(timezone::parse_date(date)?));
+    }
+    if let Some(date) = matches.value_of(BEFORE) {
+        search.before = Some(zone.start_of_day(timezone::parse_date(date)?));
+    }
+    Ok(search)
+}
+
+/// Builds smtp settings from `config` flags, layered over whatever is already saved.
+/// Splits `host`, `host:port`, `[ipv6]` or `[ipv6]:port`. A bare IPv6 address is all host,
+/// since its last group can't be told apart from a port.
+fn parse_smtp_server(s: &str) -> result::Result<(String, u16), String> {
+    let invalid = || format!("Invalid SMTP server {}", s);
+    let (host, port) = if s.starts_with('[') {
+        let end = s.find(']').ok_or_else(invalid)?;
+        let port = match &s[end + 1..] {
+            "" => None,
+            rest if rest.starts_with(':') => Some(&rest[1..]),
+            _ => return Err(invalid()),
+        };
+        (&s[1..end], port)
+    } else {
+        match s.find(':') {
+            Some(i) if s[i + 1..].find(':').is_none() => (&s[..i], Some(&s[i + 1..])),
+            _ => (s, None),
+        }
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let port = match port {
+        Some(p) => p
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid SMTP port in {}", s))?,
+        None => DEFAULT_SMTP_PORT,
+    };
+    Ok((String::from(host), port))
+}
+
+fn smtp_settings(
+    matches: &clap::ArgMatches,
+    current: Option<config::SmtpSettings>,
+) -> result::Result<config::SmtpSettings, String> {
+    let (server, port) = match matches.value_of(SMTP_SERVER) {
+        Some(s) => parse_smtp_server(s)?,
+        None => match &current {
+            Some(c) => (String::from(&c.server), c.port),
+            None => {
+                return Err(String::from(
+                    "--smtp-server is required to enable email reports.",
+                ))
+            }
+        },
+    };
+    let username = match (matches.value_of(SMTP_USER), &current) {
+        (Some(u), _) => String::from(u),
+        (None, Some(c)) => String::from(&c.username),
+        (None, None) => {
+            return Err(String::from(
+                "--smtp-user is required to enable email reports.",
+            ))
+        }
+    };
+    let to = match (matches.value_of(EMAIL_TO), &current) {
+        (Some(t), _) => String::from(t),
+        (None, Some(c)) => String::from(&c.to),
+        (None, None) => {
+            return Err(String::from(
+                "--email-to is required to enable email reports.",
+            ))
+        }
+    };
+    let from = match (matches.value_of(EMAIL_FROM), &current) {
+        (Some(f), _) => String::from(f),
+        (None, Some(c)) => String::from(&c.from),
+        (None, None) => String::from(&username),
+    };
+    let failures_only = match matches.value_of(EMAIL_ON) {
+        Some(on) => on == "failure",
+        None => current.map_or(false, |c| c.failures_only),
+    };
+    Ok(config::SmtpSettings {
+        server,
+        port,
+        username,
+        from,
+        to,
+        failures_only,
+    })
+}
+
+/// Builds s3 settings from `config` flags, layered over whatever is already saved.
+fn s3_settings(
+    matches: &clap::ArgMatches,
+    current: Option<config::S3Settings>,
+) -> result::Result<config::S3Settings, String> {
+    let endpoint = match (matches.value_of(S3_ENDPOINT), &current) {
+        (Some(e), _) => String::from(e),
+        (None, Some(c)) => String::from(&c.endpoint),
+        (None, None) => return Err(String::from("--s3-endpoint is required to upload backups.")),
+    };
+    let bucket = match (matches.value_of(S3_BUCKET), &current) {
+        (Some(b), _) => String::from(b),
+        (None, Some(c)) => String::from(&c.bucket),
+        (None, None) => return Err(String::from("--s3-bucket is required to upload backups.")),
+    };
+    let region = match (matches.value_of(S3_REGION), &current) {
+        (Some(r), _) => String::from(r),
+        (None, Some(c)) => String::from(&c.region),
+        (None, None) => String::from(DEFAULT_S3_REGION),
+    };
+    let access_key_id = match (matches.value_of(S3_ACCESS_KEY_ID), current) {
+        (Some(k), _) => Some(String::from(k)),
+        (None, Some(c)) => c.access_key_id,
+        (None, None) => None,
+    };
+    Ok(config::S3Settings {
+        endpoint,
+        bucket,
+        region,
+        access_key_id,
+    })
+}
+
+/// Applies the filter flags given to `run` to the loaded account without saving them.
+fn apply_filter_overrides(ai: &mut config::AccountInfo, options: &RunOptions) {
+    if let Some(patterns) = &options.match_patterns {
+        ai.match_patterns = Some(patterns.clone());
+    }
+    if let Some(subreddits) = &options.included_subreddits {
+        ai.included_subreddits = Some(subreddits.clone());
+    }
+    if let Some(max_score) = options.max_score {
+        ai.max_score = Some(max_score);
+    }
+    if let Some(score) = options.minimum_score {
+        ai.minimum_score = if score > 0 { Some(score) } else { None };
+    }
+    if let Some(hours) = options.max_hours {
+        ai.max_hours = if hours > 0 { Some(hours) } else { None };
+    }
+    if !options.add_excluded.is_empty() || !options.remove_excluded.is_empty() {
+        let mut excluded = ai.excluded_subreddits.take().unwrap_or(Vec::new());
+        for sr in &options.add_excluded {
+            if !excluded.iter().any(|e| e.eq_ignore_ascii_case(sr)) {
+                excluded.push(String::from(sr));
+            }
+        }
+        excluded.retain(|e| {
+            !options
+                .remove_excluded
+                .iter()
+                .any(|sr| sr.eq_ignore_ascii_case(e))
+        });
+        ai.excluded_subreddits = if excluded.is_empty() {
+            None
+        } else {
+            Some(excluded)
+        };
+    }
+}
+
+/// Like `without_kept_by_settings`, also returning the fullnames it dropped.
+fn split_kept_by_settings(
+    ai: &config::AccountInfo,
+    all: Vec<reddit_api::DeletionInfo>,
+) -> (Vec<reddit_api::DeletionInfo>, HashSet<String>) {
+    let mut dropped: HashSet<String> = all.iter().map(|p| p.name.clone()).collect();
+    let left = without_kept_by_settings(ai, all);
+    for p in &left {
+        dropped.remove(&p.name);
+    }
+    (left, dropped)
+}
+
+/// Drops the items the account keeps by recency or score, whatever the other filters say.
+fn without_kept_by_settings(
+    ai: &config::AccountInfo,
+    all: Vec<reddit_api::DeletionInfo>,
+) -> Vec<reddit_api::DeletionInfo> {
+    let all = match ai.keep_latest {
+        Some(n) => {
+            say!("Keeping your {} most recent comments/submissions.", n);
+            without_latest(all, n)
+        }
+        None => all,
+    };
+    let all = match ai.keep_top_per_subreddit {
+        Some(n) => {
+            say!(
+                "Keeping your {} highest scoring comments/submissions in each subreddit.",
+                n
+            );
+            without_top_per_subreddit(all, n)
+        }
+        None => all,
+    };
+    match ai.keep_top_percent {
+        Some(percent) => {
+            say!(
+                "Keeping the top {}% of your comments/submissions by score.",
+                percent
+            );
+            without_top_percent(all, percent)
+        }
+        None => all,
+    }
+}
+
+/// Sorts newest first and drops the `keep` most recent items, which are never deleted.
+fn without_latest(
+    mut all: Vec<reddit_api::DeletionInfo>,
+    keep: usize,
+) -> Vec<reddit_api::DeletionInfo> {
+    all.sort_by(|a, b| {
+        b.created_utc
+            .partial_cmp(&a.created_utc)
+            .unwrap_or(Ordering::Equal)
+    });
+    all.into_iter().skip(keep).collect()
+}
+
+/// Drops the highest scoring `percent`% of all items, rounding up, leaving the rest in their
+/// original order. Ties at the cut-off are broken by fetch order.
+fn without_top_percent(
+    all: Vec<reddit_api::DeletionInfo>,
+    percent: u8,
+) -> Vec<reddit_api::DeletionInfo> {
+    let keep = (all.len() * percent.min(100) as usize + 99) / 100;
+    let mut ranked: Vec<&reddit_api::DeletionInfo> = all.iter().collect();
+    ranked.sort_by(|a, b| b.score.cmp(&a.score));
+    let kept: HashSet<String> = ranked
+        .into_iter()
+        .take(keep)
+        .map(|p| String::from(&p.name))
+        .collect();
+    all.into_iter()
+        .filter(|p| !kept.contains(&p.name))
+        .collect()
+}
+
+/// Drops the `keep` highest scoring items in every subreddit, leaving the rest in their original order.
+fn without_top_per_subreddit(
+    all: Vec<reddit_api::DeletionInfo>,
+    keep: usize,
+) -> Vec<reddit_api::DeletionInfo> {
+    let mut by_subreddit: HashMap<String, Vec<&reddit_api::DeletionInfo>> = HashMap::new();
+    for p in &all {
+        by_subreddit
+            .entry(p.subreddit.to_lowercase())
+            .or_insert(Vec::new())
+            .push(p);
+    }
+    let mut kept: HashSet<String> = HashSet::new();
+    for (_, mut posts) in by_subreddit {
+        posts.sort_by(|a, b| b.score.cmp(&a.score));
+        kept.extend(posts.into_iter().take(keep).map(|p| String::from(&p.name)));
+    }
+    all.into_iter()
+        .filter(|p| !kept.contains(&p.name))
+        .collect()
+}
+
+/// True when `domain` is one of `domains` or a subdomain of one, so imgur.com also covers i.imgur.com.
+fn domain_matches(domains: &Vec<String>, domain: &str) -> bool {
+    domains.iter().any(|d| {
+        domain.eq_ignore_ascii_case(d)
+            || domain
+                .to_lowercase()
+                .ends_with(&format!(".{}", d.to_lowercase()))
+    })
+}
+
+/// Asks the configured filter command about `info`. Items are kept if the command can't be run.
+fn filter_command_allows(ai: &config::AccountInfo, info: &reddit_api::DeletionInfo) -> bool {
+    match &ai.filter_command {
+        None => true,
+        Some(command) => match filter_hook::allows(command, info) {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                say!("Unable to run filter command, keeping {}: {}", info.name, e);
+                false
+            }
+        },
+    }
+}
+
+/// Asks the account's script about `info`. Items are kept if the script fails.
+fn script_allows(script: &Option<script::Script>, info: &reddit_api::DeletionInfo) -> bool {
+    match script {
+        None => true,
+        Some(script) => match script.should_delete(info) {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                say!("{}, keeping {}", e, info.name);
+                false
+            }
+        },
+    }
+}
+
+/// Options that limit which items can be deleted at all, whichever way the other filters
+/// are combined, so `--combine any` can't reach outside the included subreddits.
+fn is_out_of_scope(ai: &config::AccountInfo, info: &reddit_api::DeletionInfo) -> bool {
+    if let Some(included) = &ai.included_subreddits {
+        if !included
+            .iter()
+            .any(|sr| sr.eq_ignore_ascii_case(&info.subreddit))
+        {
+            return true;
+        }
+    }
+    if let (Some(post_types), Some(post_type)) = (&ai.post_types, &info.post_type) {
+        if !post_types.contains(post_type) {
+            return true;
+        }
+    }
+    if let (Some(deleted), Some(domain)) = (&ai.deleted_domains, &info.domain) {
+        if !domain_matches(deleted, domain) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Options that always keep an item, whichever way the other filters are combined.
+fn is_protected(ai: &config::AccountInfo, info: &reddit_api::DeletionInfo) -> bool {
+    if ai.keep_distinguished && info.distinguished.is_some() {
+        return true;
+    }
+    if ai.keep_stickied && info.stickied {
+        return true;
+    }
+    if ai.archived == Some(config::ArchivedPolicy::Skip) && info.archived {
+        return true;
+    }
+    if info.quarantined && !ai.include_quarantined {
+        return true;
+    }
+    if ai.edited == Some(config::EditedFilter::Keep) && info.edited {
+        return true;
+    }
+    if ai.controversial == Some(config::ControversialFilter::Keep) && info.controversial {
+        return true;
+    }
+    if let Some(excluded) = &ai.excluded_subreddits {
+        if patterns::subreddit_matches(excluded, &info.subreddit).unwrap_or(true) {
+            return true;
+        }
+    }
+    if let (Some(kept), Some(domain)) = (&ai.kept_domains, &info.domain) {
+        if domain_matches(kept, domain) {
+            return true;
+        }
+    }
+    if let Some(excluded) = &ai.excluded_flairs {
+        if flairs(info)
+            .iter()
+            .any(|f| patterns::flair_matches(excluded, f).unwrap_or(true))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn flairs(info: &reddit_api::DeletionInfo) -> Vec<&String> {
+    [&info.link_flair_text, &info.author_flair_text]
+        .iter()
+        .filter_map(|f| match f {
+            Some(flair) => Some(flair),
+            None => None,
+        })
+        .collect()
+}
+
+/// One verdict per configured filter, true when that filter alone would delete the item.
+/// Filters that aren't configured don't get a verdict.
+fn filter_verdicts(
+    ai: &config::AccountInfo,
+    rule: &Option<rules::Expr>,
+    info: &reddit_api::DeletionInfo,
+) -> Vec<bool> {
+    let age: u64 = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH + time::Duration::from_secs_f64(info.created_utc.clone()))
+        .unwrap()
+        .as_secs()
+        / 3600;
+    let mut verdicts = Vec::new();
+    if ai.edited == Some(config::EditedFilter::Only) {
+        verdicts.push(info.edited);
+    }
+    if ai.controversial == Some(config::ControversialFilter::Only) {
+        verdicts.push(info.controversial);
+    }
+    if let Some(max_hours) = ai.max_hours {
+        verdicts.push(age >= max_hours);
+    }
+    if let Some(minimum_score) = ai.minimum_score {
+        verdicts.push(info.score <= minimum_score);
+    }
+    if let Some(max_score) = ai.max_score {
+        verdicts.push(info.score <= max_score);
+    }
+    if let Some(included) = &ai.included_flairs {
+        verdicts.push(
+            flairs(info)
+                .iter()
+                .any(|f| patterns::flair_matches(included, f).unwrap_or(false)),
+        );
+    }
+    if let Some(match_patterns) = &ai.match_patterns {
+        let content = [&info.body, &info.title, &info.selftext];
+        verdicts.push(content.iter().any(|text| match text {
+            Some(t) => patterns::matches_any(match_patterns, t).unwrap_or(false),
+            None => false,
+        }));
+    }
+    if let Some(keywords) = &ai.keywords {
+        let content = [&info.body, &info.title, &info.selftext];
+        let lowercase: Vec<String> = content
+            .iter()
+            .filter_map(|text| match text {
+                Some(t) => Some(t.to_lowercase()),
+                None => None,
+            })
+            .collect();
+        verdicts.push(keywords.iter().any(|k| {
+            let k = k.to_lowercase();
+            lowercase.iter().any(|text| text.contains(&k))
+        }));
+    }
+    if let Some(rule) = rule {
+        verdicts.push(rules::matches(rule, info));
+    }
+    verdicts
+}
+
+fn check_should_delete(
+    ai: &config::AccountInfo,
+    rule: &Option<rules::Expr>,
+    info: &reddit_api::DeletionInfo,
+) -> bool {
+    if is_protected(ai, info) || is_out_of_scope(ai, info) {
+        return false;
+    }
+    let verdicts = filter_verdicts(ai, rule, info);
+    match ai.combine {
+        Some(config::CombineMode::Any) if !verdicts.is_empty() => verdicts.into_iter().any(|v| v),
+        _ => verdicts.into_iter().all(|v| v),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let exclude_arg = Arg::with_name(ADD_EXCLUDED_SUBREDDITS)
+        .short("a")
+        .long("add-excluded")
+        .help("Adds subreddits to an exclusion list. Comments and submissions in the subreddits in this list will not be deleted. Accepts globs like Ask* and regular expressions between slashes like /^r?programming$/.")
+        .takes_value(true)
+        .multiple(true);
+    let include_arg = Arg::with_name(REMOVE_EXCLUDED_SUBREDDITS)
+        .short("r")
+        .long("remove-excluded")
+        .help("Removes subreddits from the exclusion list.")
+        .takes_value(true)
+        .multiple(true);
+    let score_arg = Arg::with_name(MIN_SCORE)
+        .short("s")
+        .long("min-score")
+        .help("Minimum score required to prevent deletion of comment/submission. Set to 0 to remove filter.")
+        .takes_value(true);
+    let max_score_arg = Arg::with_name(MAX_SCORE)
+        .long("max-score")
+        .help("Only delete comments/submissions with a score at or below this, e.g. 0 to only delete downvoted content.")
+        .takes_value(true)
+        .allow_hyphen_values(true);
+    let max_hours_arg = Arg::with_name(MAX_HOURS)
+        .short("t")
+        .long("max-hours")
+        .help("Will not delete comments/submissions made within this many hours. Set to 0 to remove filter.")
+        .takes_value(true);
+    let username_arg = Arg::with_name(USERNAME)
+        .help("Username to config/run the app for.")
+        .index(1)
+        .required(true)
+        .takes_value(true);
+    let only_subreddits_arg = Arg::with_name(ONLY_SUBREDDITS)
+        .long("only-subreddits")
+        .help("Only delete comments and submissions in these subreddits. Replaces the saved inclusion list for this run.")
+        .takes_value(true)
+        .multiple(true);
+    let match_pattern_arg = Arg::with_name(MATCH_PATTERN)
+        .long("match-pattern")
+        .help("Only delete comments/submissions whose text matches one of these regular expressions. Replaces the saved patterns for this run.")
+        .takes_value(true)
+        .multiple(true);
+    let link_arg = Arg::with_name(LINK)
+        .long("link")
+        .help("Only delete your comments in this thread (permalink or id). Fetches just that thread instead of your whole history.")
+        .takes_value(true)
+        .conflicts_with(BACKFILL);
+    let limit_arg = Arg::with_name(LIMIT)
+        .long("limit")
+        .help("Only delete the first <limit> matching comments/submissions, to try out new filters on a small batch.")
+        .takes_value(true);
+    let offline_arg = Arg::with_name(OFFLINE)
+        .long("offline")
+        .help("Work from an exported archive instead of reddit, without any API calls.")
+        .requires(OFFLINE_ARCHIVE)
+        .conflicts_with_all(&[LINK, BACKFILL]);
+    let offline_archive_arg = Arg::with_name(OFFLINE_ARCHIVE)
+        .long("archive")
+        .value_name("FILE")
+        .help("Json file from `redelete export --format json` to read with --offline.")
+        .takes_value(true)
+        .requires(OFFLINE);
+    let backfill_arg = Arg::with_name(BACKFILL).long("backfill").help(
+        "Also fetches the hot, top and controversial listings to find items past reddit's ~1000 item limit. Uses many more API calls.",
+    );
+    let format_arg = Arg::with_name(FORMAT)
+        .long("format")
+        .help("Output format. json writes one JSON object per line for scripts.")
+        .takes_value(true)
+        .possible_values(&["text", "json"])
+        .default_value("text");
+    let matches = App::new("Redelete")
+        .version("0.3.1")
+        .author("Justin E. <ardeaf@gmail.com>")
+        .about("Deletes your reddit comments and submissions")
+        .arg(
+            Arg::with_name(QUIET)
+                .short("q")
+                .long("quiet")
+                .help("Only print counts and problems, not the contents of every post.")
+                .global(true)
+                .conflicts_with(VERBOSE),
+        )
+        .arg(
+            Arg::with_name(NO_COLOR)
+                .long("no-color")
+                .help("Don't color the output. Colors are also off when it isn't going to a terminal.")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name(VERBOSE)
+                .short("v")
+                .long("verbose")
+                .help("Print request URLs and pagination cursors. Use -vv to add rate limit details.")
+                .global(true)
+                .multiple(true),
+        )
+        .subcommand(
+            App::new("config")
+                .about("Set default configuration options for the app.")
+                .setting(AppSettings::SubcommandsNegateReqs)
+                .subcommand(
+                    App::new(CONFIG_EXPORT)
+                        .about("Write every saved account and its settings to a json file, to import on another machine.")
+                        .arg(
+                            Arg::with_name(EXPORT_OUTPUT)
+                                .short("o")
+                                .long("output")
+                                .value_name("FILE")
+                                .help("File to write the accounts to.")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name(INCLUDE_TOKENS)
+                                .long("include-tokens")
+                                .help("Also export each account's tokens so it doesn't need authorizing again. Anyone with the file can use your accounts, keep it private."),
+                        ),
+                )
+                .subcommand(
+                    App::new(CONFIG_IMPORT)
+                        .about("Add the accounts from a `config export` file, replacing saved accounts with the same username.")
+                        .arg(
+                            Arg::with_name(IMPORT_FILE)
+                                .value_name("FILE")
+                                .help("File written by `redelete config export`.")
+                                .required(true),
+                        ),
+                )
+                .arg(&username_arg)
+                .arg(&exclude_arg)
+                .arg(&include_arg)
+                .arg(&score_arg)
+                .arg(&max_score_arg)
+                .arg(
+                    Arg::with_name(NO_MAX_SCORE)
+                        .long("no-max-score")
+                        .help("Removes the max score filter.")
+                        .conflicts_with(MAX_SCORE),
+                )
+                .arg(&max_hours_arg)
+                .arg(
+                    Arg::with_name(KEEP_DISTINGUISHED)
+                        .long("keep-distinguished")
+                        .help("Never delete comments/submissions you distinguished as a moderator.")
+                        .conflicts_with(DELETE_DISTINGUISHED),
+                )
+                .arg(
+                    Arg::with_name(DELETE_DISTINGUISHED)
+                        .long("delete-distinguished")
+                        .help("Delete distinguished comments/submissions like any other."),
+                )
+                .arg(
+                    Arg::with_name(INCLUDE_QUARANTINED)
+                        .long("include-quarantined")
+                        .help("Delete comments/submissions in quarantined subreddits too, opting in to the subreddit if reddit requires it.")
+                        .conflicts_with(SKIP_QUARANTINED),
+                )
+                .arg(
+                    Arg::with_name(SKIP_QUARANTINED)
+                        .long("skip-quarantined")
+                        .help("Leave comments/submissions in quarantined subreddits alone. This is the default."),
+                )
+                .arg(
+                    Arg::with_name(OVERVIEW)
+                        .long("overview")
+                        .help("Fetch comments and submissions together from your overview, which takes half the requests but only reaches your newest 1000 items in total.")
+                        .conflicts_with(SEPARATE_LISTINGS),
+                )
+                .arg(
+                    Arg::with_name(SEPARATE_LISTINGS)
+                        .long("separate-listings")
+                        .help("Fetch comments and submissions separately, up to 1000 of each. This is the default."),
+                )
+                .arg(
+                    Arg::with_name(POST_TYPE)
+                        .long("post-type")
+                        .help("Only delete submissions of these types. Comments are not affected.")
+                        .takes_value(true)
+                        .multiple(true)
+                        .possible_values(&["self", "link", "image", "video", "gallery"])
+                        .conflicts_with(ANY_POST_TYPE),
+                )
+                .arg(
+                    Arg::with_name(ANY_POST_TYPE)
+                        .long("any-post-type")
+                        .help("Delete submissions of every type again."),
+                )
+                .arg(
+                    Arg::with_name(CONCURRENCY)
+                        .long("concurrency")
+                        .help("How many deletions to run at once. They still share the rate limit. Set to 0 to use the default of 8.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(COMBINE)
+                        .long("combine")
+                        .help("Delete comments/submissions that pass all of the filters (the default), or any one of them. Exclusions like excluded subreddits and --keep-stickied, and limits like included subreddits, post types and deleted domains, always apply.")
+                        .takes_value(true)
+                        .possible_values(&["all", "any"]),
+                )
+                .arg(
+                    Arg::with_name(ARCHIVED)
+                        .long("archived")
+                        .help("What to do with archived comments/submissions (older than six months): skip them, warn about them while deleting, or delete them like any other.")
+                        .takes_value(true)
+                        .possible_values(&["skip", "warn", "delete"]),
+                )
+                .arg(
+                    Arg::with_name(EDITED)
+                        .long("edited")
+                        .help("What to do with comments/submissions you edited: keep them, only delete them, or treat them like any other.")
+                        .takes_value(true)
+                        .possible_values(&["keep", "only", "any"]),
+                )
+                .arg(
+                    Arg::with_name(RULE)
+                        .long("rule")
+                        .help("Only delete comments/submissions matching this rule as well as the other filters, e.g. 'score < 5 && age > 30d && subreddit != \"rust\"'. Fields: score, age, subreddit, body, title, text, domain, flair, kind, edited, stickied, distinguished, controversial, archived, saved.")
+                        .takes_value(true)
+                        .conflicts_with(NO_RULE),
+                )
+                .arg(
+                    Arg::with_name(NO_RULE)
+                        .long("no-rule")
+                        .help("Removes the rule."),
+                )
+                .arg(
+                    Arg::with_name(FILTER_COMMAND)
+                        .long("filter-command")
+                        .help("Command that gets every matched comment/submission as json on stdin. It is only deleted if the command exits with status 0.")
+                        .takes_value(true)
+                        .conflicts_with(NO_FILTER_COMMAND),
+                )
+                .arg(
+                    Arg::with_name(NO_FILTER_COMMAND)
+                        .long("no-filter-command")
+                        .help("Removes the filter command."),
+                )
+                .arg(
+                    Arg::with_name(SCRIPT)
+                        .long("script")
+                        .help("Rhai script defining `fn should_delete(item)`. It is copied to the config directory and every matched comment/submission is only deleted if it returns true.")
+                        .takes_value(true)
+                        .conflicts_with(NO_SCRIPT),
+                )
+                .arg(
+                    Arg::with_name(NO_SCRIPT)
+                        .long("no-script")
+                        .help("Removes the script."),
+                )
+                .arg(
+                    Arg::with_name(CONTROVERSIAL)
+                        .long("controversial")
+                        .help("What to do with comments reddit marked as controversial: keep them, only delete them, or treat them like any other.")
+                        .takes_value(true)
+                        .possible_values(&["keep", "only", "any"]),
+                )
+                .arg(
+                    Arg::with_name(KEEP_STICKIED)
+                        .long("keep-stickied")
+                        .help("Never delete submissions that are pinned to the top of a subreddit.")
+                        .conflicts_with(DELETE_STICKIED),
+                )
+                .arg(
+                    Arg::with_name(DELETE_STICKIED)
+                        .long("delete-stickied")
+                        .help("Delete stickied submissions like any other."),
+                )
+                .arg(
+                    Arg::with_name(KEEP_TOP)
+                        .long("keep-top")
+                        .help("Never delete your N highest scoring comments/submissions in each subreddit. Set to 0 to remove filter.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(KEEP_TOP_PERCENT)
+                        .long("keep-top-percent")
+                        .help("Never delete the top X% of your comments/submissions by score. Set to 0 to remove filter.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(KEEP_LATEST)
+                        .long("keep-latest")
+                        .help("Never delete your N most recent comments/submissions, regardless of other filters. Set to 0 to remove filter.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_INCLUDED_SUBREDDITS)
+                        .long("add-included")
+                        .help("Adds subreddits to an inclusion list. When the list isn't empty, only comments and submissions in these subreddits are deleted. The exclusion list still wins for a subreddit on both lists.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_INCLUDED_SUBREDDITS)
+                        .long("remove-included")
+                        .help("Removes subreddits from the inclusion list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_PATTERNS)
+                        .long("add-pattern")
+                        .help("Adds regular expressions to the match list. When the list isn't empty, only comments/submissions whose text matches one of them are deleted.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_PATTERNS)
+                        .long("remove-pattern")
+                        .help("Removes regular expressions from the match list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_KEYWORDS)
+                        .long("add-keyword")
+                        .help("Adds keywords to the keyword list. When the list isn't empty, only comments/submissions containing one of them (ignoring case) are deleted.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_KEYWORDS)
+                        .long("remove-keyword")
+                        .help("Removes keywords from the keyword list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_KEPT_DOMAINS)
+                        .long("add-kept-domain")
+                        .help("Never delete link submissions pointing at these domains or their subdomains, e.g. github.com.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_KEPT_DOMAINS)
+                        .long("remove-kept-domain")
+                        .help("Removes domains from the kept domain list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_DELETED_DOMAINS)
+                        .long("add-deleted-domain")
+                        .help("When this list isn't empty, link submissions are only deleted if they point at one of these domains. Comments and self posts are not affected.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_DELETED_DOMAINS)
+                        .long("remove-deleted-domain")
+                        .help("Removes domains from the deleted domain list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_EXCLUDED_FLAIRS)
+                        .long("add-excluded-flair")
+                        .help("Never delete comments/submissions whose post or user flair matches one of these. Accepts plain text, globs and regular expressions between slashes, like subreddits.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_EXCLUDED_FLAIRS)
+                        .long("remove-excluded-flair")
+                        .help("Removes flairs from the excluded flair list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(ADD_INCLUDED_FLAIRS)
+                        .long("add-included-flair")
+                        .help("When this list isn't empty, only comments/submissions whose post or user flair matches one of these are deleted.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(REMOVE_INCLUDED_FLAIRS)
+                        .long("remove-included-flair")
+                        .help("Removes flairs from the included flair list.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(LIST_PATTERNS)
+                        .long("list-patterns")
+                        .help("Lists the saved match patterns."),
+                )
+                .arg(
+                    Arg::with_name(SMTP_SERVER)
+                        .long("smtp-server")
+                        .help("SMTP server (host, host:port or [ipv6]:port) used to email a report after each run. The password is read from REDELETE_SMTP_PASSWORD or the system keyring (service redelete-smtp).")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(SMTP_USER)
+                        .long("smtp-user")
+                        .help("Username to log in to the SMTP server with.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(EMAIL_FROM)
+                        .long("email-from")
+                        .help("Sender address for run reports. Defaults to the SMTP username.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(EMAIL_TO)
+                        .long("email-to")
+                        .help("Address to send run reports to.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(EMAIL_ON)
+                        .long("email-on")
+                        .help("Email the run report after every run, or only when some deletions failed.")
+                        .takes_value(true)
+                        .possible_values(&["always", "failure"]),
+                )
+                .arg(
+                    Arg::with_name(NO_EMAIL)
+                        .long("no-email")
+                        .help("Stop emailing run reports.")
+                        .conflicts_with_all(&[
+                            SMTP_SERVER,
+                            SMTP_USER,
+                            EMAIL_FROM,
+                            EMAIL_TO,
+                            EMAIL_ON,
+                        ]),
+                )
+                .arg(
+                    Arg::with_name(BACKUP)
+                        .long("backup")
+                        .help("Save a copy of everything a run deletes to the backups folder in the config directory.")
+                        .conflicts_with(NO_BACKUP),
+                )
+                .arg(
+                    Arg::with_name(NO_BACKUP)
+                        .long("no-backup")
+                        .help("Stop saving backups before deleting."),
+                )
+                .arg(
+                    Arg::with_name(AGE_RECIPIENT)
+                        .long("age-recipient")
+                        .help("Encrypt backups to this age public key. Requires `age` on your PATH.")
+                        .takes_value(true)
+                        .conflicts_with_all(&[GPG_RECIPIENT, NO_ENCRYPTION]),
+                )
+                .arg(
+                    Arg::with_name(GPG_RECIPIENT)
+                        .long("gpg-recipient")
+                        .help("Encrypt backups to this GPG key id. Requires `gpg` on your PATH.")
+                        .takes_value(true)
+                        .conflicts_with(NO_ENCRYPTION),
+                )
+                .arg(
+                    Arg::with_name(NO_ENCRYPTION)
+                        .long("no-encryption")
+                        .help("Write backups as plain text json."),
+                )
+                .arg(
+                    Arg::with_name(S3_ENDPOINT)
+                        .long("s3-endpoint")
+                        .help("Upload backups to this S3-compatible endpoint, e.g. https://s3.us-east-1.amazonaws.com. The secret key is read from REDELETE_S3_SECRET_ACCESS_KEY.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(S3_BUCKET)
+                        .long("s3-bucket")
+                        .help("Bucket to upload backups to.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(S3_REGION)
+                        .long("s3-region")
+                        .help("Region of the bucket. Defaults to us-east-1.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(S3_ACCESS_KEY_ID)
+                        .long("s3-access-key-id")
+                        .help("Access key id for the bucket. Falls back to REDELETE_S3_ACCESS_KEY_ID.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(TIMEZONE)
+                        .long("timezone")
+                        .help("Time zone to show dates and interpret `run --at` in, e.g. America/New_York. Use `local` for the system time zone.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(NO_S3)
+                        .long("no-s3")
+                        .help("Stop uploading backups.")
+                        .conflicts_with_all(&[S3_ENDPOINT, S3_BUCKET, S3_REGION, S3_ACCESS_KEY_ID]),
+                )
+                .arg(
+                    Arg::with_name(SET_SCHEDULE)
+                        .long("schedule")
+                        .help("Cron expression for when `redelete schedule` runs this account, e.g. \"0 3 * * *\" for 03:00 every day. Read in the account's time zone.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(NO_SCHEDULE)
+                        .long("no-schedule")
+                        .help("Stop running this account from `redelete schedule`.")
+                        .conflicts_with(SET_SCHEDULE),
+                )
+                .arg(
+                    Arg::with_name(DESKTOP_NOTIFY)
+                        .long("desktop-notify")
+                        .help("Show a desktop notification with the outcome when a run finishes.")
+                        .conflicts_with(NO_DESKTOP_NOTIFY),
+                )
+                .arg(
+                    Arg::with_name(NO_DESKTOP_NOTIFY)
+                        .long("no-desktop-notify")
+                        .help("Stop showing desktop notifications."),
+                )
+                .arg(
+                    Arg::with_name(WEBHOOK_URL)
+                        .long("webhook-url")
+                        .help("POST a json summary of every run (account, deleted, failed, duration) to this URL.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(WEBHOOK_FORMAT)
+                        .long("webhook-format")
+                        .help("Post run summaries as plain json (the default), or as a message for a Discord or Slack incoming webhook.")
+                        .takes_value(true)
+                        .possible_values(&["json", "discord", "slack"]),
+                )
+                .arg(
+                    Arg::with_name(NO_WEBHOOK)
+                        .long("no-webhook")
+                        .help("Stop posting run summaries to the webhook.")
+                        .conflicts_with(WEBHOOK_URL),
+                )
+                .arg(
+                    Arg::with_name(PRE_RUN_HOOK)
+                        .long("pre-run-hook")
+                        .help("Shell command to run before each run, with REDELETE_USERNAME and REDELETE_DRY_RUN set. The run is aborted if it exits with a non-zero status.")
+                        .takes_value(true)
+                        .conflicts_with(NO_PRE_RUN_HOOK),
+                )
+                .arg(
+                    Arg::with_name(NO_PRE_RUN_HOOK)
+                        .long("no-pre-run-hook")
+                        .help("Removes the pre run hook."),
+                )
+                .arg(
+                    Arg::with_name(POST_RUN_HOOK)
+                        .long("post-run-hook")
+                        .help("Shell command to run after each run, with its results in REDELETE_SCANNED, REDELETE_MATCHED, REDELETE_DELETED, REDELETE_FAILED, REDELETE_REMAINING, REDELETE_REQUESTS and REDELETE_ELAPSED_SECS.")
+                        .takes_value(true)
+                        .conflicts_with(NO_POST_RUN_HOOK),
+                )
+                .arg(
+                    Arg::with_name(NO_POST_RUN_HOOK)
+                        .long("no-post-run-hook")
+                        .help("Removes the post run hook."),
+                )
+                .arg(
+                    Arg::with_name(ARCHIVE_API)
+                        .long("archive-api")
+                        .value_name("URL")
+                        .help("Also search a Pushshift style archive at URL for comments/submissions older than reddit's listings show. Each one is looked up on reddit before it's deleted.")
+                        .takes_value(true)
+                        .conflicts_with(NO_ARCHIVE_API),
+                )
+                .arg(
+                    Arg::with_name(NO_ARCHIVE_API)
+                        .long("no-archive-api")
+                        .help("Stop searching the archive."),
+                )
+                .arg(
+                    Arg::with_name(REFRESH_MARGIN)
+                        .long("refresh-margin")
+                        .value_name("DURATION")
+                        .help("Refresh the access token this long before it expires, e.g. 2m. Defaults to 60s.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(EDIT)
+                        .long("edit")
+                        .help("Open the account's settings as toml in $VISUAL or $EDITOR and save them when you're done."),
+                ),
+        )
+        .subcommand(
+            App::new("run")
+                .about("Run the deletion part of the app.")
+                .arg(Arg::with_name(DRYRUN).short("d").long("dry-run").help(
+                    "Fetches comments and submissions to be deleted, then prompts to delete it.",
+                ))
+                .arg(
+                    Arg::with_name(RUN_AT)
+                        .long("at")
+                        .help("Wait until this time of day (HH:MM, in the account's time zone) before running.")
+                        .takes_value(true),
+                )
+                .arg(&only_subreddits_arg)
+                .arg(&match_pattern_arg)
+                .arg(&link_arg)
+                .arg(&limit_arg)
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Delete without asking for confirmation first, for scripts and scheduled runs."),
+                )
+                .arg(
+                    Arg::with_name(VERIFY)
+                        .long("verify")
+                        .help("After deleting, fetch your comments/submissions again and report any that reddit still lists. They're saved for `redelete retry`.")
+                        .conflicts_with(DRYRUN),
+                )
+                .arg(
+                    Arg::with_name(MAX_REQUESTS)
+                        .long("max-requests")
+                        .help("Make at most this many API calls, fetching and deleting together. Posts left over when the budget runs out are saved for `redelete retry`.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(WATCH)
+                        .long("watch")
+                        .help("Keep running, starting another run every --interval until Ctrl-C. Needs --yes or --dry-run.")
+                        .requires(INTERVAL),
+                )
+                .arg(
+                    Arg::with_name(INTERVAL)
+                        .long("interval")
+                        .help("Time between two runs with --watch, e.g. 6h or 30m.")
+                        .takes_value(true)
+                        .requires(WATCH),
+                )
+                .arg(
+                    Arg::with_name(TIMEOUT)
+                        .long("timeout")
+                        .help("Stop after this long, e.g. 30m or 2h. Posts left over are saved for `redelete retry`.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(DELAY)
+                        .long("delay")
+                        .help("Wait this long between deletions, e.g. 2s or 500ms. Deletes one at a time instead of all at once.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(JITTER)
+                        .long("jitter")
+                        .help("Add up to this much random extra time to every --delay pause, e.g. 1s.")
+                        .takes_value(true)
+                        .requires(DELAY),
+                )
+                .arg(
+                    Arg::with_name(SHRED)
+                        .long("shred")
+                        .help("Overwrite comments and self posts with random text before deleting them, so sites that archive reddit don't keep the original. Links and archived posts are only deleted.")
+                        .conflicts_with(DRYRUN),
+                )
+                .arg(
+                    Arg::with_name(SHRED_TEXT)
+                        .long("shred-text")
+                        .help("Overwrite with this text instead of random text.")
+                        .takes_value(true)
+                        .requires(SHRED),
+                )
+                .arg(
+                    Arg::with_name(SHRED_PASSES)
+                        .long("shred-passes")
+                        .help("Overwrite every post this many times before deleting it. Defaults to 1.")
+                        .takes_value(true)
+                        .requires(SHRED),
+                )
+                .arg(
+                    Arg::with_name(SHRED_DELAY)
+                        .long("shred-delay")
+                        .help("Wait this long between two passes over all the posts, e.g. 30s or 5m.")
+                        .takes_value(true)
+                        .requires(SHRED_PASSES),
+                )
+                .arg(
+                    Arg::with_name(TRASH)
+                        .long("trash")
+                        .help("Put matching comments/submissions in the trash instead of deleting them, so you can take back mistakes with `redelete trash`."),
+                )
+                .arg(
+                    Arg::with_name(PURGE_AFTER)
+                        .long("purge-after")
+                        .value_name("DURATION")
+                        .help("Put matches in the trash and delete whatever has been in it at least this long, e.g. 7d or 12h.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(PREVIEW_LENGTH)
+                        .long("preview-length")
+                        .help("Show at most this many characters of each post's text when listing matches. 0 shows all of it.")
+                        .takes_value(true)
+                        .default_value(DEFAULT_PREVIEW_LENGTH),
+                )
+                .arg(
+                    Arg::with_name(NO_PAGER)
+                        .long("no-pager")
+                        .help("Print dry run listings straight to the terminal instead of through $PAGER."),
+                )
+                .arg(
+                    Arg::with_name(EXPORT)
+                        .long("export")
+                        .value_name("FILE")
+                        .help("Write the posts a dry run matched to FILE, as json if it ends in .json and csv otherwise.")
+                        .takes_value(true)
+                        .requires(DRYRUN),
+                )
+                .arg(&backfill_arg)
+                .arg(username_arg.clone().required_unless(ALL_ACCOUNTS))
+                .arg(
+                    Arg::with_name(ALL_ACCOUNTS)
+                        .long("all")
+                        .help("Run for every saved username, each with its own saved filters. With --yes or --dry-run the accounts run at the same time.")
+                        .conflicts_with_all(&[USERNAME, EXPORT, GDPR_EXPORT]),
+                )
+                .arg(
+                    Arg::with_name(GDPR_EXPORT)
+                        .long("from-gdpr-export")
+                        .value_name("FILE")
+                        .help("Delete from the zip (or its comments.csv or posts.csv) reddit sends when you request your data, reaching posts older than the ~1000 the listings show. Each post is looked up first so the filters still apply.")
+                        .takes_value(true)
+                        .conflicts_with_all(&[LINK, BACKFILL]),
+                )
+                .arg(&format_arg)
+                .arg(&exclude_arg)
+                .arg(&include_arg)
+                .arg(&score_arg)
+                .arg(&max_score_arg)
+                .arg(&max_hours_arg),
+        )
+        .subcommand(
+            App::new(RETRY)
+                .about("Retry deletions that failed during previous runs.")
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("Lists the failed deletions without retrying them."),
+                )
+                .arg(&username_arg),
+        )
+        .subcommand(
+            App::new(PLAN)
+                .about("Pick what a run would delete and write it to a file, so it can be reviewed before `redelete apply` deletes exactly those posts.")
+                .arg(&username_arg)
+                .arg(&offline_arg)
+                .arg(&offline_archive_arg)
+                .arg(
+                    Arg::with_name(PLAN_OUTPUT)
+                        .short("o")
+                        .long("output")
+                        .help("File to write the plan to.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(&only_subreddits_arg)
+                .arg(&match_pattern_arg)
+                .arg(&link_arg)
+                .arg(&limit_arg)
+                .arg(&backfill_arg)
+                .arg(&exclude_arg)
+                .arg(&include_arg)
+                .arg(&score_arg)
+                .arg(&max_score_arg)
+                .arg(&max_hours_arg),
+        )
+        .subcommand(
+            App::new(APPLY)
+                .about("Delete the posts in a plan written by `redelete plan`, without fetching your history again.")
+                .arg(
+                    Arg::with_name(PLAN_FILE)
+                        .help("Plan file written by `redelete plan`.")
+                        .index(1)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Delete without asking for confirmation first."),
+                )
+                .arg(
+                    Arg::with_name(VERIFY)
+                        .long("verify")
+                        .help("After deleting, fetch your comments/submissions again and report any that reddit still lists."),
+                ),
+        )
+        .subcommand(
+            App::new(STATS)
+                .about("Show what's in your history and how much of it your filters match, without deleting anything.")
+                .arg(&username_arg)
+                .arg(&offline_arg)
+                .arg(&offline_archive_arg)
+                .arg(&only_subreddits_arg)
+                .arg(&match_pattern_arg)
+                .arg(&exclude_arg)
+                .arg(&include_arg)
+                .arg(&score_arg)
+                .arg(&max_score_arg)
+                .arg(&max_hours_arg),
+        )
+        .subcommand(
+            App::new(PURGE_SAVED)
+                .about("Unsave the comments/submissions you've saved that pass the filters.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List what would be unsaved without unsaving it."),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Unsave without asking for confirmation first."),
+                )
+                .arg(&only_subreddits_arg)
+                .arg(&match_pattern_arg)
+                .arg(&limit_arg)
+                .arg(&exclude_arg)
+                .arg(&include_arg)
+                .arg(&score_arg)
+                .arg(&max_score_arg)
+                .arg(&max_hours_arg),
+        )
+        .subcommand(
+            App::new(PURGE_VOTES)
+                .about("Clear your upvotes and downvotes, so they stop showing on your profile.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(VOTES)
+                        .long("votes")
+                        .help("Which votes to clear.")
+                        .takes_value(true)
+                        .possible_values(&["up", "down", "both"])
+                        .default_value("both"),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List the votes without clearing them."),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Clear votes without asking for confirmation first."),
+                )
+                .arg(
+                    Arg::with_name(LIMIT)
+                        .long("limit")
+                        .help("Only clear the first <limit> votes.")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new(PURGE_HIDDEN)
+                .about("Unhide every submission you've hidden.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(HIDE_OWN)
+                        .long("hide-own")
+                        .help("Do the reverse: hide every one of your own submissions."),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List the submissions without changing them."),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Go ahead without asking for confirmation first."),
+                )
+                .arg(
+                    Arg::with_name(LIMIT)
+                        .long("limit")
+                        .help("Only change the first <limit> submissions.")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new(UNSUBSCRIBE_ALL)
+                .about("Unsubscribe from all of your subreddits, or some of them.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(SEARCH_SUBREDDITS)
+                        .short("r")
+                        .long("subreddits")
+                        .help("Only unsubscribe from these subreddits. Entries can be names, globs like Ask* or /regex/.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(KEEP_SUBSCRIPTIONS)
+                        .long("keep")
+                        .help("Stay subscribed to these subreddits. Same syntax as --subreddits.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List the subreddits without unsubscribing."),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Unsubscribe without asking for confirmation first."),
+                ),
+        )
+        .subcommand(
+            App::new(MULTIS)
+                .about("List your multireddits, or delete them all.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(DELETE_ALL)
+                        .long("delete-all")
+                        .help("Delete every one of your multireddits."),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List the multireddits without deleting them.")
+                        .requires(DELETE_ALL),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Delete without asking for confirmation first.")
+                        .requires(DELETE_ALL),
+                ),
+        )
+        .subcommand(
+            App::new(FRIENDS)
+                .about("List your friends, or remove them all.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(REMOVE_ALL)
+                        .long("remove-all")
+                        .help("Remove everyone from your friends list."),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List your friends without removing them.")
+                        .requires(REMOVE_ALL),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Remove without asking for confirmation first.")
+                        .requires(REMOVE_ALL),
+                ),
+        )
+        .subcommand(
+            App::new(BLOCKED)
+                .about("List the users you've blocked, export the list or unblock them all.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(EXPORT)
+                        .long("export")
+                        .value_name("FILE")
+                        .help("Write the list to FILE, as json if it ends in .json and csv otherwise.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(UNBLOCK_ALL)
+                        .long("unblock-all")
+                        .help("Unblock everyone."),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List blocked users without unblocking them.")
+                        .requires(UNBLOCK_ALL),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Unblock without asking for confirmation first.")
+                        .requires(UNBLOCK_ALL),
+                ),
+        )
+        .subcommand(
+            App::new(WIPE)
+                .about("Clean up the whole account: back up your history, delete comments/submissions (with your saved filters), unsave, clear votes, unsubscribe and delete private messages, asking before each step.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(WIPE_BACKUP)
+                        .long("backup")
+                        .value_name("FILE")
+                        .help("Where to write the json backup taken first. Defaults to <username>-backup.json.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("Go through every step listing what it would change, without changing anything. The backup is still written."),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Run every step without asking first."),
+                ),
+        )
+        .subcommand(
+            App::new(ACCOUNTS).about(
+                "List your saved accounts with their token status, last run and filters.",
+            ),
+        )
+        .subcommand(
+            App::new(LOGOUT)
+                .about("Revoke redelete's access to your reddit account and remove it from the config.")
+                .arg(&username_arg),
+        )
+        .subcommand(
+            App::new(WHOAMI)
+                .alias("token-info")
+                .about("Check the saved token with reddit and show its scopes and expiry.")
+                .arg(&username_arg),
+        )
+        .subcommand(
+            App::new(SIMULATE)
+                .about("Try filters on an export from `redelete export` or on made up items, listing what a run would delete. Doesn't connect to reddit.")
+                .arg(
+                    Arg::with_name(USERNAME)
+                        .help("Start from this account's saved filters instead of none.")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name(SIMULATE_INPUT)
+                        .short("i")
+                        .long("input")
+                        .value_name("FILE")
+                        .help("Json file written by `redelete export`.")
+                        .takes_value(true)
+                        .conflicts_with(SAMPLE),
+                )
+                .arg(
+                    Arg::with_name(SAMPLE)
+                        .long("sample")
+                        .value_name("COUNT")
+                        .help("How many made up comments/submissions to use when there's no --input.")
+                        .takes_value(true)
+                        .default_value("200"),
+                )
+                .arg(&only_subreddits_arg)
+                .arg(&match_pattern_arg)
+                .arg(&limit_arg)
+                .arg(&exclude_arg)
+                .arg(&include_arg)
+                .arg(&score_arg)
+                .arg(&max_score_arg)
+                .arg(&max_hours_arg),
+        )
+        .subcommand(
+            App::new(RESTORE)
+                .about("Post comments and self posts from an export or backup again, as new posts, after deleting more than you meant to.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(RESTORE_FROM)
+                        .long("from")
+                        .value_name("FILE")
+                        .help("Json file from `redelete export` or a run's backup. Exports keep which comment a comment replied to, backups only the thread.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(RESTORE_IDS)
+                        .long("ids")
+                        .value_name("FULLNAME")
+                        .help("Fullnames of the comments/submissions to restore, like t1_abc123 or t3_def456.")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List what would be posted without posting it."),
+                )
+                .arg(
+                    Arg::with_name(YES)
+                        .short("y")
+                        .long("yes")
+                        .help("Post without asking for confirmation first."),
+                ),
+        )
+        .subcommand(
+            App::new(TRASH)
+                .about("List the comments/submissions `run --trash` is holding back, or take some out again.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(UNMARK)
+                        .long("unmark")
+                        .value_name("FULLNAME")
+                        .help("Take these out of the trash so they aren't deleted. Runs will pick them again unless you also `redelete keep` them.")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true),
+                ),
+        )
+        .subcommand(
+            App::new(EXPORT_COMMAND)
+                .about("Download all your comments and submissions, with every field reddit returns, to a local file.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(FORMAT)
+                        .long("format")
+                        .help("File format of the backup. markdown and html write a browsable archive, a directory of pages with an index.")
+                        .takes_value(true)
+                        .possible_values(&["json", "csv", "sqlite", "markdown", "html"])
+                        .default_value("json"),
+                )
+                .arg(
+                    Arg::with_name(EXPORT_OUTPUT)
+                        .short("o")
+                        .long("output")
+                        .help("File to write the backup to, or directory for markdown and html.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(GROUP_BY)
+                        .long("group-by")
+                        .help("Split a markdown or html archive into a page per subreddit or per month.")
+                        .takes_value(true)
+                        .possible_values(&["subreddit", "month"])
+                        .default_value("subreddit"),
+                ),
+        )
+        .subcommand(
+            App::new(SEARCH)
+                .about("Find comments/submissions in your history and print them with their links.")
+                .arg(&username_arg)
+                .arg(&offline_arg)
+                .arg(&offline_archive_arg)
+                .arg(
+                    Arg::with_name(QUERY)
+                        .help("Text to look for in comment bodies and submission titles, text and links. Case is ignored.")
+                        .index(2)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(SEARCH_REGEX)
+                        .long("regex")
+                        .help("Treat the query as a regular expression."),
+                )
+                .arg(
+                    Arg::with_name(SEARCH_SUBREDDITS)
+                        .short("r")
+                        .long("subreddits")
+                        .help("Only search these subreddits. Entries can be names, globs like Ask* or /regex/.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(AFTER)
+                        .long("after")
+                        .help("Only show posts made on or after this date (YYYY-MM-DD, in the account's time zone).")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(BEFORE)
+                        .long("before")
+                        .help("Only show posts made before this date (YYYY-MM-DD, in the account's time zone).")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new(KEEP)
+                .about("Always keep these comments/submissions, whatever the filters say. Runs skip them without checking them again.")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(FULLNAMES)
+                        .help("Fullnames of the comments/submissions, like t1_abc123 or t3_def456.")
+                        .index(2)
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name(UNKEEP)
+                        .long("unkeep")
+                        .help("Stop keeping these, so runs check them against the filters again."),
+                ),
+        )
+        .subcommand(
+            App::new(SCHEDULE)
+                .about("Keep running, deleting for each account whenever its `--schedule` comes up, until Ctrl-C.")
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List what each scheduled run would delete without deleting it."),
+                ),
+        )
+        .subcommand(
+            App::new(DAEMON)
+                .about("Run the account schedules as a service under systemd or launchd. Never prompts, logs to stdout, reports readiness to systemd and reloads the config on SIGHUP.")
+                .arg(
+                    Arg::with_name(DRYRUN)
+                        .short("d")
+                        .long("dry-run")
+                        .help("List what each scheduled run would delete without deleting it."),
+                ),
+        )
+        .subcommand(
+            App::new(VERIFY_LOG)
+                .about("Verify that the deletion audit log for <username> has not been altered.")
+                .arg(&username_arg),
+        )
+        .subcommand(
+            App::new(VIEW)
+                .about("View saved configs for given <username>")
+                .arg(&username_arg)
+                .arg(
+                    Arg::with_name(FORMAT)
+                        .long("format")
+                        .help("Output format. json and toml print every setting, leaving out the token, for scripts and diffing accounts.")
+                        .takes_value(true)
+                        .possible_values(&["text", "json", "toml"])
+                        .default_value("text"),
+                )
+                .arg(
+                    Arg::with_name(PREVIEW)
+                        .long("preview")
+                        .help("Also fetch your history and count how many comments/submissions a run would delete right now. Nothing is deleted."),
+                ),
+        )
+        .subcommand(
+            App::new(AUTHORIZE)
+                .about("Authorize this application with your reddit account.")
+                .arg(
+                    Arg::with_name(FORGET_ACCOUNT)
+                        .short("f")
+                        .long("forget-account")
+                        .help("Revokes the account's tokens at reddit and removes it from the saved config file. The account is removed even when reddit can't be reached.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name(REAUTHORIZE)
+                        .long("reauthorize")
+                        .value_name("USERNAME")
+                        .help("Authorize a saved account again to grant the scopes newer features need. Its settings are kept.")
+                        .takes_value(true)
+                        .conflicts_with(FORGET_ACCOUNT),
+                )
+                .arg(
+                    Arg::with_name(WITH_SAVED)
+                        .long("with-saved")
+                        .help("Only grant what deleting needs, plus unsaving for `purge-saved`. Without any --with flags everything is granted."),
+                )
+                .arg(
+                    Arg::with_name(WITH_VOTES)
+                        .long("with-votes")
+                        .help("Also grant clearing votes for `purge-votes`."),
+                )
+                .arg(
+                    Arg::with_name(WITH_HIDDEN)
+                        .long("with-hidden")
+                        .help("Also grant hiding and unhiding for `purge-hidden`."),
+                )
+                .arg(
+                    Arg::with_name(WITH_SUBSCRIPTIONS)
+                        .long("with-subscriptions")
+                        .help("Also grant managing subscriptions, multireddits, friends and blocked users."),
+                )
+                .arg(
+                    Arg::with_name(WITH_MESSAGES)
+                        .long("with-messages")
+                        .help("Also grant deleting private messages."),
+                )
+                .arg(
+                    Arg::with_name(WITH_POSTING)
+                        .long("with-posting")
+                        .help("Also grant posting for `restore`."),
+                )
+                .arg(
+                    Arg::with_name(CLIENT_ID)
+                        .long("client-id")
+                        .help("Authorize with your own reddit app from https://www.reddit.com/prefs/apps instead of redelete's, and keep using it. Accounts authorized with another app need authorizing again.")
+                        .takes_value(true)
+                        .conflicts_with_all(&[FORGET_ACCOUNT, DEFAULT_APP]),
+                )
+                .arg(
+                    Arg::with_name(CLIENT_SECRET)
+                        .long("client-secret")
+                        .help("Your app has a secret, which is read from REDELETE_CLIENT_SECRET or asked for so it doesn't end up in your shell history. Installed apps don't have one.")
+                        .requires(CLIENT_ID),
+                )
+                .arg(
+                    Arg::with_name(REDIRECT_URI)
+                        .long("redirect-uri")
+                        .help("The redirect uri your app was registered with. Defaults to http://localhost:8000.")
+                        .takes_value(true)
+                        .requires(CLIENT_ID),
+                )
+                .arg(
+                    Arg::with_name(LISTEN)
+                        .long("listen")
+                        .value_name("HOST[:PORT]")
+                        .help("Wait for reddit's redirect here instead of on every interface at the redirect uri's port, e.g. 127.0.0.1 when ssh forwards the port, or 0.0.0.0:8080 behind a Docker port mapping. Saved for later authorizations.")
+                        .takes_value(true)
+                        .conflicts_with_all(&[FORGET_ACCOUNT, DEFAULT_LISTEN]),
+                )
+                .arg(
+                    Arg::with_name(DEFAULT_LISTEN)
+                        .long("default-listen")
+                        .help("Go back to waiting on every interface at the redirect uri's port.")
+                        .conflicts_with(FORGET_ACCOUNT),
+                )
+                .arg(
+                    Arg::with_name(DEFAULT_APP)
+                        .long("default-app")
+                        .help("Go back to authorizing with redelete's own reddit app.")
+                        .conflicts_with(FORGET_ACCOUNT),
+                ),
+        )
+        .get_matches();
+    output::set_level(output_level(&matches));
+    output::set_color(
+        matches.is_present(NO_COLOR)
+            || matches
+                .subcommand()
+                .1
+                .map_or(false, |m| m.is_present(NO_COLOR)),
+    );
+    if let Some(matches) = matches
+        .subcommand_matches("config")
+        .and_then(|m| m.subcommand_matches(CONFIG_EXPORT))
+    {
+        let path = Path::new(matches.value_of(EXPORT_OUTPUT).unwrap());
+        match config::export_accounts(path, matches.is_present(INCLUDE_TOKENS)) {
+            Ok(count) => say!("Exported {} accounts to {}.", count, path.display()),
+            Err(e) => {
+                say!("Unable to export the config. {}", e);
+                std::process::exit(EXIT_ERROR);
+            }
+        }
+    } else if let Some(matches) = matches
+        .subcommand_matches("config")
+        .and_then(|m| m.subcommand_matches(CONFIG_IMPORT))
+    {
+        let path = Path::new(matches.value_of(IMPORT_FILE).unwrap());
+        match config::import_accounts(path) {
+            Ok(imported) => {
+                for (username, needs_authorizing) in imported {
+                    if needs_authorizing {
+                        say!(
+                            "Imported {}, run `redelete authorize` and log in as {} before using it.",
+                            username,
+                            username
+                        );
+                    } else {
+                        say!("Imported {}.", username);
+                    }
+                }
+            }
+            Err(e) => {
+                say!("Unable to import {}. {}", path.display(), e);
+                std::process::exit(EXIT_ERROR);
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("config") {
+        let username = matches.value_of(USERNAME).unwrap();
+        if config::read_config_account_info(&username).is_none() {
+            unknown_username(&username);
+        }
+        if matches.is_present(EDIT) {
+            match edit_settings(&username) {
+                Ok(true) => say!("Saved settings for {}.", username),
+                Ok(false) => say!("No changes made."),
+                Err(e) => {
+                    say!("{}", e);
+                    std::process::exit(EXIT_ERROR);
+                }
+            }
+        }
+        if matches.is_present(MIN_SCORE) {
+            let score = value_t!(matches, MIN_SCORE, i32)
+                .expect("Minimum score requires an integer value.");
+            match config::set_minimum_score(username.into(), score.clone()) {
+                Ok(()) => {
+                    if score > 0 {
+                        say!("Set minimum score to {}", score)
+                    } else {
+                        say!("Removed minimum score filter.")
+                    }
+                }
+                Err(e) => say!("Unable to set minimum score: {}", e),
+            }
+        }
+        if matches.is_present(MAX_SCORE) || matches.is_present(NO_MAX_SCORE) {
+            let max_score = if matches.is_present(MAX_SCORE) {
+                Some(
+                    value_t!(matches, MAX_SCORE, i32)
+                        .expect("Max score requires an integer value."),
+                )
+            } else {
+                None
+            };
+            match config::set_max_score(username.into(), max_score) {
+                Ok(()) => match max_score {
+                    Some(score) => say!("Set max score to {}", score),
+                    None => say!("Removed max score filter."),
+                },
+                Err(e) => say!("Unable to set max score: {}", e),
+            }
+        }
+        if matches.is_present(KEEP_DISTINGUISHED) || matches.is_present(DELETE_DISTINGUISHED) {
+            let keep = matches.is_present(KEEP_DISTINGUISHED);
+            match config::set_keep_distinguished(username.into(), keep) {
+                Ok(()) if keep => say!("Keeping distinguished posts."),
+                Ok(()) => say!("Distinguished posts will be deleted."),
+                Err(e) => say!("Unable to set distinguished filter: {}", e),
+            }
+        }
+        if matches.is_present(INCLUDE_QUARANTINED) || matches.is_present(SKIP_QUARANTINED) {
+            let include = matches.is_present(INCLUDE_QUARANTINED);
+            match config::set_include_quarantined(username.into(), include) {
+                Ok(()) if include => say!("Deleting posts in quarantined subreddits."),
+                Ok(()) => say!("Skipping posts in quarantined subreddits."),
+                Err(e) => say!("Unable to set quarantine option: {}", e),
+            }
+        }
+        if matches.is_present(OVERVIEW) || matches.is_present(SEPARATE_LISTINGS) {
+            let overview = matches.is_present(OVERVIEW);
+            match config::set_use_overview(username.into(), overview) {
+                Ok(()) if overview => say!("Fetching comments and submissions together."),
+                Ok(()) => say!("Fetching comments and submissions separately."),
+                Err(e) => say!("Unable to set how history is fetched: {}", e),
+            }
+        }
+        if let Some(types) = matches.values_of(POST_TYPE) {
+            let types: Vec<String> = types.map(String::from).collect();
+            let joined = types.join(", ");
+            match config::set_post_types(username.into(), types) {
+                Ok(()) => say!("Only deleting {} submissions.", joined),
+                Err(e) => say!("Unable to set post types: {}", e),
+            }
+        }
+        if matches.is_present(ANY_POST_TYPE) {
+            match config::set_post_types(username.into(), vec![]) {
+                Ok(()) => say!("Deleting submissions of every type."),
+                Err(e) => say!("Unable to set post types: {}", e),
+            }
+        }
+        if matches.is_present(CONCURRENCY) {
+            let concurrency = value_t!(matches, CONCURRENCY, usize)
+                .expect("Concurrency requires a positive integer value.");
+            match config::set_concurrency(username.into(), concurrency) {
+                Ok(()) => {
+                    if concurrency > 0 {
+                        say!("Running up to {} deletions at once.", concurrency)
+                    } else {
+                        say!(
+                            "Running up to {} deletions at once.",
+                            reddit_api::DEFAULT_CONCURRENCY
+                        )
+                    }
+                }
+                Err(e) => say!("Unable to set concurrency: {}", e),
+            }
+        }
+        if let Some(combine) = matches.value_of(COMBINE) {
+            let mode = match combine {
+                "any" => Some(config::CombineMode::Any),
+                _ => None,
+            };
+            match config::set_combine_mode(username.into(), mode) {
+                Ok(()) if mode.is_some() => {
+                    say!("Deleting posts that pass any one of the filters.")
+                }
+                Ok(()) => say!("Deleting posts that pass all of the filters."),
+                Err(e) => say!("Unable to set how filters combine: {}", e),
+            }
+        }
+        if let Some(archived) = matches.value_of(ARCHIVED) {
+            let policy = match archived {
+                "skip" => Some(config::ArchivedPolicy::Skip),
+                "warn" => Some(config::ArchivedPolicy::Warn),
+                _ => None,
+            };
+            match config::set_archived_policy(username.into(), policy) {
+                Ok(()) => match policy {
+                    Some(config::ArchivedPolicy::Skip) => say!("Skipping archived posts."),
+                    Some(config::ArchivedPolicy::Warn) => {
+                        say!("Warning about archived posts while deleting them.")
+                    }
+                    None => say!("Archived posts are treated like any other."),
+                },
+                Err(e) => say!("Unable to set archived policy: {}", e),
+            }
+        }
+        if let Some(edited) = matches.value_of(EDITED) {
+            let filter = match edited {
+                "keep" => Some(config::EditedFilter::Keep),
+                "only" => Some(config::EditedFilter::Only),
+                _ => None,
+            };
+            match config::set_edited_filter(username.into(), filter) {
+                Ok(()) => match filter {
+                    Some(config::EditedFilter::Keep) => say!("Keeping edited posts."),
+                    Some(config::EditedFilter::Only) => say!("Only deleting edited posts."),
+                    None => say!("Edited posts are treated like any other."),
+                },
+                Err(e) => say!("Unable to set edited filter: {}", e),
+            }
+        }
+        if let Some(rule) = matches.value_of(RULE) {
+            match rules::parse(rule) {
+                Ok(_) => match config::set_rule(username.into(), Some(rule.into())) {
+                    Ok(()) => say!("Only deleting posts matching {}.", rule),
+                    Err(e) => say!("Unable to set rule: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if matches.is_present(NO_RULE) {
+            match config::set_rule(username.into(), None) {
+                Ok(()) => say!("Removed rule."),
+                Err(e) => say!("Unable to remove rule: {}", e),
+            }
+        }
+        if let Some(command) = matches.value_of(FILTER_COMMAND) {
+            match config::set_filter_command(username.into(), Some(command.into())) {
+                Ok(()) => say!("Asking {} before deleting each post.", command),
+                Err(e) => say!("Unable to set filter command: {}", e),
+            }
+        }
+        if matches.is_present(NO_FILTER_COMMAND) {
+            match config::set_filter_command(username.into(), None) {
+                Ok(()) => say!("Removed filter command."),
+                Err(e) => say!("Unable to remove filter command: {}", e),
+            }
+        }
+        if let Some(source) = matches.value_of(SCRIPT) {
+            match script::install(username, &std::path::PathBuf::from(source)) {
+                Ok(path) => {
+                    let path = path.to_string_lossy().to_string();
+                    match config::set_script(username.into(), Some(String::from(&path))) {
+                        Ok(()) => say!("Asking {} before deleting each post.", path),
+                        Err(e) => say!("Unable to set script: {}", e),
+                    }
+                }
+                Err(e) => say!("{}", e),
+            }
+        }
+        if matches.is_present(NO_SCRIPT) {
+            match config::set_script(username.into(), None) {
+                Ok(()) => say!("Removed script."),
+                Err(e) => say!("Unable to remove script: {}", e),
+            }
+        }
+        if let Some(controversial) = matches.value_of(CONTROVERSIAL) {
+            let filter = match controversial {
+                "keep" => Some(config::ControversialFilter::Keep),
+                "only" => Some(config::ControversialFilter::Only),
+                _ => None,
+            };
+            match config::set_controversial_filter(username.into(), filter) {
+                Ok(()) => match filter {
+                    Some(config::ControversialFilter::Keep) => {
+                        say!("Keeping controversial comments.")
+                    }
+                    Some(config::ControversialFilter::Only) => {
+                        say!("Only deleting controversial comments.")
+                    }
+                    None => say!("Controversial comments are treated like any other."),
+                },
+                Err(e) => say!("Unable to set controversial filter: {}", e),
+            }
+        }
+        if matches.is_present(KEEP_STICKIED) || matches.is_present(DELETE_STICKIED) {
+            let keep = matches.is_present(KEEP_STICKIED);
+            match config::set_keep_stickied(username.into(), keep) {
+                Ok(()) if keep => say!("Keeping stickied submissions."),
+                Ok(()) => say!("Stickied submissions will be deleted."),
+                Err(e) => say!("Unable to set stickied filter: {}", e),
+            }
+        }
+        if matches.is_present(KEEP_TOP) {
+            let keep = value_t!(matches, KEEP_TOP, usize)
+                .expect("Keep top requires a positive integer value.");
+            match config::set_keep_top_per_subreddit(username.into(), keep) {
+                Ok(()) => {
+                    if keep > 0 {
+                        say!("Keeping your {} best posts in each subreddit.", keep)
+                    } else {
+                        say!("Removed keep top filter.")
+                    }
+                }
+                Err(e) => say!("Unable to set keep top: {}", e),
+            }
+        }
+        if matches.is_present(KEEP_TOP_PERCENT) {
+            let percent = value_t!(matches, KEEP_TOP_PERCENT, u8)
+                .ok()
+                .filter(|p| *p <= 100)
+                .expect("Keep top percent requires a value between 0 and 100.");
+            match config::set_keep_top_percent(username.into(), percent) {
+                Ok(()) => {
+                    if percent > 0 {
+                        say!("Keeping the top {}% of your posts by score.", percent)
+                    } else {
+                        say!("Removed keep top percent filter.")
+                    }
+                }
+                Err(e) => say!("Unable to set keep top percent: {}", e),
+            }
+        }
+        if matches.is_present(KEEP_LATEST) {
+            let keep = value_t!(matches, KEEP_LATEST, usize)
+                .expect("Keep latest requires a positive integer value.");
+            match config::set_keep_latest(username.into(), keep) {
+                Ok(()) => {
+                    if keep > 0 {
+                        say!("Keeping your {} most recent posts.", keep)
+                    } else {
+                        say!("Removed keep latest filter.")
+                    }
+                }
+                Err(e) => say!("Unable to set keep latest: {}", e),
+            }
+        }
+        if matches.is_present(MAX_HOURS) {
+            let hours = value_t!(matches, MAX_HOURS, u64)
+                .expect("Maximum hours requires an integer value.");
+            match config::set_max_hours(username.into(), hours.clone()) {
+                Ok(()) => {
+                    if hours > 0 {
+                        say!("Max hours set to {}", hours)
+                    } else {
+                        say!("Removed max hours filter.")
+                    }
+                }
+                Err(e) => say!("Unable to set max hours: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_EXCLUDED_SUBREDDITS) {
+            let mut to_add = Vec::new();
+            for input in inputs {
+                to_add.push(input);
+            }
+            match patterns::validate_subreddit_patterns(to_add.iter().cloned()) {
+                Ok(()) => match config::add_excluded_subreddits(username.into(), to_add) {
+                    Ok(_) => (),
+                    Err(e) => say!("Unable to set subreddit exclusion: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_EXCLUDED_SUBREDDITS) {
+            let mut to_add = Vec::new();
+            for input in inputs {
+                to_add.push(input);
+            }
+            match config::remove_excluded_subreddits(username.into(), to_add) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to set subreddit exclusion: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_INCLUDED_SUBREDDITS) {
+            match config::add_included_subreddits(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to set subreddit inclusion: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_INCLUDED_SUBREDDITS) {
+            match config::remove_included_subreddits(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to set subreddit inclusion: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_PATTERNS) {
+            let to_add: Vec<&str> = inputs.collect();
+            match patterns::validate_patterns(to_add.iter().cloned()) {
+                Ok(()) => match config::add_match_patterns(username.into(), to_add) {
+                    Ok(_) => (),
+                    Err(e) => say!("Unable to add match patterns: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_PATTERNS) {
+            match config::remove_match_patterns(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to remove match patterns: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_KEYWORDS) {
+            match config::add_keywords(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to add keywords: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_KEYWORDS) {
+            match config::remove_keywords(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to remove keywords: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_KEPT_DOMAINS) {
+            match config::add_kept_domains(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to add kept domains: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_KEPT_DOMAINS) {
+            match config::remove_kept_domains(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to remove kept domains: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_DELETED_DOMAINS) {
+            match config::add_deleted_domains(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to add deleted domains: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_DELETED_DOMAINS) {
+            match config::remove_deleted_domains(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to remove deleted domains: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_EXCLUDED_FLAIRS) {
+            let to_add: Vec<&str> = inputs.collect();
+            match patterns::validate_flair_patterns(to_add.iter().cloned()) {
+                Ok(()) => match config::add_excluded_flairs(username.into(), to_add) {
+                    Ok(_) => (),
+                    Err(e) => say!("Unable to add excluded flairs: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_EXCLUDED_FLAIRS) {
+            match config::remove_excluded_flairs(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to remove excluded flairs: {}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(ADD_INCLUDED_FLAIRS) {
+            let to_add: Vec<&str> = inputs.collect();
+            match patterns::validate_flair_patterns(to_add.iter().cloned()) {
+                Ok(()) => match config::add_included_flairs(username.into(), to_add) {
+                    Ok(_) => (),
+                    Err(e) => say!("Unable to add included flairs: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if let Some(inputs) = matches.values_of(REMOVE_INCLUDED_FLAIRS) {
+            match config::remove_included_flairs(username.into(), inputs.collect()) {
+                Ok(_) => (),
+                Err(e) => say!("Unable to remove included flairs: {}", e),
+            }
+        }
+        if matches.is_present(LIST_PATTERNS) {
+            match config::read_config_account_info(username).and_then(|ai| ai.match_patterns) {
+                Some(patterns) => {
+                    for p in patterns {
+                        say!("{}", p)
+                    }
+                }
+                None => say!("No match patterns saved."),
+            }
+        }
+        if matches.is_present(NO_EMAIL) {
+            match config::set_smtp(username.into(), None) {
+                Ok(()) => say!("Email reports disabled."),
+                Err(e) => say!("Unable to disable email reports: {}", e),
+            }
+        } else if matches.is_present(SMTP_SERVER)
+            || matches.is_present(SMTP_USER)
+            || matches.is_present(EMAIL_FROM)
+            || matches.is_present(EMAIL_TO)
+            || matches.is_present(EMAIL_ON)
+        {
+            let current = config::read_config_account_info(username).and_then(|ai| ai.smtp);
+            match smtp_settings(matches, current) {
+                Ok(smtp) => {
+                    let to = String::from(&smtp.to);
+                    match config::set_smtp(username.into(), Some(smtp)) {
+                        Ok(()) => say!("Run reports will be emailed to {}.", to),
+                        Err(e) => say!("Unable to set email settings: {}", e),
+                    }
+                }
+                Err(e) => say!("{}", e),
+            }
+        }
+        if matches.is_present(BACKUP) || matches.is_present(NO_BACKUP) {
+            let enabled = matches.is_present(BACKUP);
+            match config::set_backup(username.into(), enabled) {
+                Ok(()) if enabled => say!("Backups enabled."),
+                Ok(()) => say!("Backups disabled."),
+                Err(e) => say!("Unable to set backups: {}", e),
+            }
+        }
+        let recipient = if let Some(key) = matches.value_of(AGE_RECIPIENT) {
+            Some(Some(config::BackupRecipient::Age(key.into())))
+        } else if let Some(key) = matches.value_of(GPG_RECIPIENT) {
+            Some(Some(config::BackupRecipient::Gpg(key.into())))
+        } else if matches.is_present(NO_ENCRYPTION) {
+            Some(None)
+        } else {
+            None
+        };
+        if let Some(recipient) = recipient {
+            let encrypted = recipient.is_some();
+            match config::set_backup_recipient(username.into(), recipient) {
+                Ok(()) if encrypted => say!("Backups will be encrypted."),
+                Ok(()) => say!("Backups will not be encrypted."),
+                Err(e) => say!("Unable to set backup encryption: {}", e),
+            }
+        }
+        if let Some(tz) = matches.value_of(TIMEZONE) {
+            let timezone = if tz == "local" {
+                Ok(None)
+            } else {
+                timezone::parse_timezone(tz).map(|_| Some(String::from(tz)))
+            };
+            match timezone {
+                Ok(timezone) => match config::set_timezone(username.into(), timezone) {
+                    Ok(()) => say!("Time zone set to {}.", tz),
+                    Err(e) => say!("Unable to set time zone: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if matches.is_present(NO_SCHEDULE) {
+            match config::set_schedule(username.into(), None) {
+                Ok(()) => say!("Removed the schedule."),
+                Err(e) => say!("Unable to remove the schedule: {}", e),
+            }
+        } else if let Some(expression) = matches.value_of(SET_SCHEDULE) {
+            match cron::Schedule::parse(expression) {
+                Ok(_) => match config::set_schedule(username.into(), Some(expression.into())) {
+                    Ok(()) => say!("Scheduled runs set to {}.", expression),
+                    Err(e) => say!("Unable to set the schedule: {}", e),
+                },
+                Err(e) => say!("{}", e),
+            }
+        }
+        if matches.is_present(DESKTOP_NOTIFY) || matches.is_present(NO_DESKTOP_NOTIFY) {
+            let enabled = matches.is_present(DESKTOP_NOTIFY);
+            match config::set_desktop_notifications(username.into(), enabled) {
+                Ok(()) if enabled => {
+                    say!("Runs will show a desktop notification when they finish.")
+                }
+                Ok(()) => say!("Runs will no longer show desktop notifications."),
+                Err(e) => say!("Unable to set desktop notifications: {}", e),
+            }
+        }
+        if matches.is_present(NO_WEBHOOK) {
+            match config::set_webhook_url(username.into(), None) {
+                Ok(()) => say!("Run summaries will no longer be posted to a webhook."),
+                Err(e) => say!("Unable to remove the webhook: {}", e),
+            }
+        } else if let Some(webhook) = matches.value_of(WEBHOOK_URL) {
+            match url::Url::parse(webhook) {
+                Ok(_) => match config::set_webhook_url(username.into(), Some(webhook.into())) {
+                    Ok(()) => say!("Run summaries will be posted to {}.", webhook),
+                    Err(e) => say!("Unable to set the webhook: {}", e),
+                },
+                Err(e) => say!("Invalid webhook URL {}: {}", webhook, e),
+            }
+        }
+        if let Some(format) = matches.value_of(WEBHOOK_FORMAT) {
+            let format = match format {
+                "discord" => Some(config::WebhookFormat::Discord),
+                "slack" => Some(config::WebhookFormat::Slack),
+                _ => None,
+            };
+            match config::set_webhook_format(username.into(), format) {
+                Ok(()) if format.is_some() => {
+                    say!("Run summaries will be posted as chat messages.")
+                }
+                Ok(()) => say!("Run summaries will be posted as plain json."),
+                Err(e) => say!("Unable to set the webhook format: {}", e),
+            }
+        }
+        if let Some(command) = matches.value_of(PRE_RUN_HOOK) {
+            match config::set_pre_run_hook(username.into(), Some(command.into())) {
+                Ok(()) => say!("Running {} before each run.", command),
+                Err(e) => say!("Unable to set pre run hook: {}", e),
+            }
+        }
+        if matches.is_present(NO_PRE_RUN_HOOK) {
+            match config::set_pre_run_hook(username.into(), None) {
+                Ok(()) => say!("Removed pre run hook."),
+                Err(e) => say!("Unable to remove pre run hook: {}", e),
+            }
+        }
+        if let Some(command) = matches.value_of(POST_RUN_HOOK) {
+            match config::set_post_run_hook(username.into(), Some(command.into())) {
+                Ok(()) => say!("Running {} after each run.", command),
+                Err(e) => say!("Unable to set post run hook: {}", e),
+            }
+        }
+        if matches.is_present(NO_POST_RUN_HOOK) {
+            match config::set_post_run_hook(username.into(), None) {
+                Ok(()) => say!("Removed post run hook."),
+                Err(e) => say!("Unable to remove post run hook: {}", e),
+            }
+        }
+        if let Some(api) = matches.value_of(ARCHIVE_API) {
+            match url::Url::parse(api) {
+                Ok(_) => match config::set_archive_api(username.into(), Some(api.into())) {
+                    Ok(()) => say!("Searching {} for older posts.", api),
+                    Err(e) => say!("Unable to set the archive API: {}", e),
+                },
+                Err(e) => say!("Invalid archive API URL {}: {}", api, e),
+            }
+        }
+        if matches.is_present(NO_ARCHIVE_API) {
+            match config::set_archive_api(username.into(), None) {
+                Ok(()) => say!("No longer searching an archive for older posts."),
+                Err(e) => say!("Unable to remove the archive API: {}", e),
+            }
+        }
+        if let Some(margin) = matches.value_of(REFRESH_MARGIN) {
+            match pacing::parse_duration(margin) {
+                Ok(margin) => {
+                    match config::set_refresh_margin(username.into(), Some(margin.as_secs())) {
+                        Ok(()) => say!(
+                            "Refreshing the access token {}s before it expires.",
+                            margin.as_secs()
+                        ),
+                        Err(e) => say!("Unable to set the refresh margin: {}", e),
+                    }
+                }
+                Err(e) => say!("{}", e),
+            }
+        }
+        if matches.is_present(NO_S3) {
+            match config::set_s3(username.into(), None) {
+                Ok(()) => say!("Backups will no longer be uploaded."),
+                Err(e) => say!("Unable to disable backup uploads: {}", e),
+            }
+        } else if matches.is_present(S3_ENDPOINT)
+            || matches.is_present(S3_BUCKET)
+            || matches.is_present(S3_REGION)
+            || matches.is_present(S3_ACCESS_KEY_ID)
+        {
+            let current = config::read_config_account_info(username).and_then(|ai| ai.s3);
+            match s3_settings(matches, current) {
+                Ok(s3) => {
+                    let bucket = String::from(&s3.bucket);
+                    match config::set_s3(username.into(), Some(s3)) {
+                        Ok(()) => say!("Backups will be uploaded to bucket {}.", bucket),
+                        Err(e) => say!("Unable to set backup uploads: {}", e),
+                    }
+                }
+                Err(e) => say!("{}", e),
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches(AUTHORIZE) {
+        if let Some(username) = matches.value_of(FORGET_ACCOUNT) {
+            let ai = match config::read_config_account_info(&username) {
+                Some(ai) => ai,
+                None => {
+                    say!("{} was not found in the config file.", username);
+                    std::process::exit(EXIT_CONFIG_NOT_FOUND);
+                }
+            };
+            // Forgetting an account shouldn't depend on reddit being reachable.
+            match reddit_api::revoke_tokens(&ai.token).await {
+                Ok(()) => say!("Revoked redelete's access to {}.", username),
+                Err(e) => say!(
+                    "{} Unable to revoke the tokens at reddit ({}). Remove redelete at https://www.reddit.com/prefs/apps to revoke them.",
+                    "Warning:".yellow(),
+                    e
+                ),
+            }
+            match config::delete_user(&username) {
+                Ok(_) => say!("Removed {} from config file", username),
+                Err(e) => fail(e.into()),
+            }
+        } else {
+            let app = if let Some(client_id) = matches.value_of(CLIENT_ID) {
+                let client_secret = if matches.is_present(CLIENT_SECRET) {
+                    let stdin = std::io::stdin();
+                    match read_client_secret(stdin.lock()) {
+                        Some(secret) => Some(secret),
+                        None => {
+                            say!("No client secret given, nothing was authorized.");
+                            std::process::exit(EXIT_ERROR);
+                        }
+                    }
+                } else {
+                    None
+                };
+                Some(Some(config::RedditApp {
+                    client_id: String::from(client_id),
+                    client_secret,
+                    redirect_uri: matches.value_of(REDIRECT_URI).map(String::from),
+                }))
+            } else if matches.is_present(DEFAULT_APP) {
+                Some(None)
+            } else {
+                None
+            };
+            if let Some(app) = app {
+                if let Err(e) = config::set_reddit_app(app) {
+                    say!("Unable to save the reddit app. {}", e);
+                    std::process::exit(EXIT_ERROR);
+                }
+            }
+            let listen = match matches.value_of(LISTEN) {
+                Some(listen) => Some(Some(String::from(listen))),
+                None if matches.is_present(DEFAULT_LISTEN) => Some(None),
+                None => None,
+            };
+            if let Some(listen) = listen {
+                if let Err(e) = config::set_redirect_listener(listen) {
+                    say!("Unable to save where to wait for the redirect. {}", e);
+                    std::process::exit(EXIT_ERROR);
+                }
+            }
+            let features: Vec<&str> = reddit_api::FEATURE_SCOPES
+                .iter()
+                .map(|(feature, _)| *feature)
+                .filter(|feature| matches.is_present(feature))
+                .collect();
+            let scopes = reddit_api::scopes_for(&features);
+            if let Some(username) = matches.value_of(REAUTHORIZE) {
+                let ai = match config::read_config_account_info(&username) {
+                    Some(ai) => ai,
+                    None => unknown_username(&username),
+                };
+                match reddit_api::reauthorize(&ai.username, &scopes).await {
+                    Ok(scopes) => say!(
+                        "Authorized account {} again. Granted scopes: {}",
+                        ai.username,
+                        scopes.join(", ")
+                    ),
+                    Err(e) => {
+                        say!("Unable to authorize account. {}", e);
+                        std::process::exit(EXIT_AUTH);
+                    }
+                }
+                return;
+            }
+            match reddit_api::authorize(&scopes).await {
+                Ok(s) => say!("Authorized account {}", s),
+                Err(e) => {
+                    say!("Unable to authorize account. {}", e);
+                    std::process::exit(EXIT_AUTH);
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches(VIEW) {
+        match config::read_config_account_info(matches.value_of(USERNAME).unwrap()) {
+            Some(ai) if matches.value_of(FORMAT) == Some("json") => {
+                println!("{}", settings_json(&ai));
+            }
+            Some(ai) if matches.value_of(FORMAT) == Some("toml") => match settings_toml(&ai) {
+                Ok(settings) => print!("{}", settings),
+                Err(e) => {
+                    say!("Unable to write the settings as toml. {}", e);
+                    std::process::exit(EXIT_ERROR);
+                }
+            },
+            Some(ai) => {
+                say!("Settings for: {}", &ai.username);
+                if ai.excluded_subreddits.is_some() {
+                    say!(
+                        "Excluded subreddits: {}",
+                        &ai.excluded_subreddits.unwrap().join(", ")
+                    );
+                } else {
+                    say!("Not excluding any subreddits.")
+                }
+                match ai.included_subreddits {
+                    Some(included) => say!(
+                        "Only deleting posts in: {} (exclusions take precedence)",
+                        included.join(", ")
+                    ),
+                    None => say!("Deleting posts in every subreddit."),
+                }
+                if ai.max_hours.is_some() {
+                    let max_hours = ai.max_hours.unwrap();
+                    say!(
+                        "Not deleting any posts made within {} hour{}.",
+                        max_hours.clone(),
+                        if max_hours.clone() == 1 { "" } else { "s" }
+                    )
+                } else {
+                    say!("No time minimum before deleting posts.")
+                }
+                match ai.keep_latest {
+                    Some(n) => say!("Never deleting your {} most recent posts.", n),
+                    None => say!("Not keeping any recent posts."),
+                }
+                match ai.keep_top_per_subreddit {
+                    Some(n) => say!(
+                        "Never deleting your {} highest scoring posts in each subreddit.",
+                        n
+                    ),
+                    None => say!("Not keeping top posts per subreddit."),
+                }
+                match ai.keep_top_percent {
+                    Some(percent) => {
+                        say!(
+                            "Never deleting the top {}% of your posts by score.",
+                            percent
+                        )
+                    }
+                    None => say!("Not keeping a top percentage of posts."),
+                }
+                if ai.keep_distinguished {
+                    say!("Never deleting distinguished posts.")
+                } else {
+                    say!("Deleting distinguished posts like any other.")
+                }
+                say!(
+                    "Running up to {} deletions at once.",
+                    ai.concurrency.unwrap_or(reddit_api::DEFAULT_CONCURRENCY)
+                );
+                match ai.combine {
+                    Some(config::CombineMode::Any) => {
+                        say!("Deleting posts that pass any one of the filters.")
+                    }
+                    _ => say!("Deleting posts that pass all of the filters."),
+                }
+                match ai.post_types {
+                    Some(types) => say!("Only deleting {} submissions.", types.join(", ")),
+                    None => say!("Deleting submissions of every type."),
+                }
+                if ai.include_quarantined {
+                    say!("Deleting posts in quarantined subreddits.")
+                } else {
+                    say!("Skipping posts in quarantined subreddits.")
+                }
+                if ai.use_overview {
+                    say!("Fetching comments and submissions together.")
+                } else {
+                    say!("Fetching comments and submissions separately.")
+                }
+                match ai.archived {
+                    Some(config::ArchivedPolicy::Skip) => {
+                        say!("Never deleting archived posts.")
+                    }
+                    Some(config::ArchivedPolicy::Warn) => {
+                        say!("Deleting archived posts with a warning.")
+                    }
+                    None => say!("Deleting archived posts like any other."),
+                }
+                match ai.edited {
+                    Some(config::EditedFilter::Keep) => say!("Never deleting edited posts."),
+                    Some(config::EditedFilter::Only) => say!("Only deleting edited posts."),
+                    None => say!("Deleting edited posts like any other."),
+                }
+                match ai.rule {
+                    Some(rule) => say!("Only deleting posts matching the rule: {}", rule),
+                    None => say!("No rule set."),
+                }
+                match ai.filter_command {
+                    Some(command) => say!("Asking {} before deleting each post.", command),
+                    None => say!("No filter command set."),
+                }
+                match ai.script {
+                    Some(path) => say!("Asking {} before deleting each post.", path),
+                    None => say!("No script set."),
+                }
+                match ai.controversial {
+                    Some(config::ControversialFilter::Keep) => {
+                        say!("Never deleting controversial comments.")
+                    }
+                    Some(config::ControversialFilter::Only) => {
+                        say!("Only deleting controversial comments.")
+                    }
+                    None => say!("Deleting controversial comments like any other."),
+                }
+                if ai.keep_stickied {
+                    say!("Never deleting stickied submissions.")
+                } else {
+                    say!("Deleting stickied submissions like any other.")
+                }
+                if ai.minimum_score.is_some() {
+                    say!(
+                        "Only deleting posts with a score less than {}.",
+                        ai.minimum_score.unwrap()
+                    )
+                } else {
+                    say!("No score limit set.")
+                }
+                match ai.max_score {
+                    Some(max_score) => {
+                        say!("Only deleting posts with a score of {} or less.", max_score)
+                    }
+                    None => say!("No max score set."),
+                }
+                match ai.match_patterns {
+                    Some(patterns) => {
+                        say!("Only deleting posts matching: {}", patterns.join(", "))
+                    }
+                    None => say!("Not filtering posts by content."),
+                }
+                match ai.keywords {
+                    Some(keywords) => {
+                        say!("Only deleting posts containing: {}", keywords.join(", "))
+                    }
+                    None => say!("Not filtering posts by keyword."),
+                }
+                match ai.kept_domains {
+                    Some(domains) => {
+                        say!(
+                            "Never deleting submissions linking to: {}",
+                            domains.join(", ")
+                        )
+                    }
+                    None => say!("No kept domains."),
+                }
+                match ai.deleted_domains {
+                    Some(domains) => {
+                        say!(
+                            "Only deleting submissions linking to: {}",
+                            domains.join(", ")
+                        )
+                    }
+                    None => say!("Not filtering submissions by domain."),
+                }
+                match ai.excluded_flairs {
+                    Some(flairs) => say!("Never deleting posts flaired: {}", flairs.join(", ")),
+                    None => say!("No excluded flairs."),
+                }
+                match ai.included_flairs {
+                    Some(flairs) => say!("Only deleting posts flaired: {}", flairs.join(", ")),
+                    None => say!("Not filtering posts by flair."),
+                }
+                match ai.smtp {
+                    Some(smtp) => say!(
+                        "Emailing run reports to {} via {}:{}{}.",
+                        smtp.to,
+                        smtp.server,
+                        smtp.port,
+                        if smtp.failures_only {
+                            ", only when deletions fail"
+                        } else {
+                            ""
+                        }
+                    ),
+                    None => say!("Not emailing run reports."),
+                }
+                match (ai.backup, ai.backup_recipient) {
+                    (false, _) => say!("Not backing up posts before deleting them."),
+                    (true, None) => say!("Backing up posts before deleting them, unencrypted."),
+                    (true, Some(config::BackupRecipient::Age(key))) => {
+                        say!(
+                            "Backing up posts before deleting them, encrypted to age key {}.",
+                            key
+                        )
+                    }
+                    (true, Some(config::BackupRecipient::Gpg(key))) => {
+                        say!(
+                            "Backing up posts before deleting them, encrypted to GPG key {}.",
+                            key
+                        )
+                    }
+                }
+                if let Some(s3) = ai.s3 {
+                    say!("Uploading backups to {}/{}.", s3.endpoint, s3.bucket)
+                }
+                match ai.timezone {
+                    Some(tz) => say!("Showing dates in {}.", tz),
+                    None => say!("Showing dates in the system time zone."),
+                }
+                if let Some(schedule) = ai.schedule {
+                    say!("`redelete schedule` runs this account at {}.", schedule)
+                }
+                if ai.desktop_notifications {
+                    say!("Showing a desktop notification when runs finish.")
+                }
+                match (ai.webhook_url, ai.webhook_format) {
+                    (Some(url), Some(format)) => {
+                        say!("Posting run summaries to {} as {:?} messages.", url, format)
+                    }
+                    (Some(url), None) => say!("Posting run summaries to {}.", url),
+                    (None, _) => (),
+                }
+                if let Some(command) = ai.pre_run_hook {
+                    say!("Running {} before each run.", command)
+                }
+                if let Some(command) = ai.post_run_hook {
+                    say!("Running {} after each run.", command)
+                }
+                if let Some(api) = ai.archive_api {
+                    say!("Searching {} for older posts.", api)
+                }
+                if let Some(margin) = ai.refresh_margin {
+                    say!("Refreshing the access token {}s before it expires.", margin)
+                }
+                if matches.is_present(PREVIEW) {
+                    let username = matches.value_of(USERNAME).unwrap();
+                    if let Err(e) = preview_matching(username.into()).await {
+                        fail(e)
+                    }
+                }
+            }
+            None => {
+                say!("Unable to find username. Did you authorize this app with that reddit account yet?");
+                std::process::exit(EXIT_CONFIG_NOT_FOUND);
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches(RUN) {
+        let format = output::Format::parse(matches.value_of(FORMAT).unwrap()).unwrap();
+        if format == output::Format::Json && !matches.is_present(DRYRUN) && !matches.is_present(YES)
+        {
+            say!("--format json can't ask for confirmation, add --yes or --dry-run.");
+            return;
+        }
+        let max_requests = if matches.is_present(MAX_REQUESTS) {
+            match value_t!(matches, MAX_REQUESTS, usize) {
+                Ok(max) => Some(max),
+                Err(_) => {
+                    say!("Max requests requires a positive integer value.");
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let timeout = match matches.value_of(TIMEOUT).map(pacing::parse_duration) {
+            Some(Ok(timeout)) => Some(timeout),
+            Some(Err(e)) => {
+                say!("{}", e);
+                return;
+            }
+            None => None,
+        };
+        let pace = match matches.value_of(DELAY) {
+            Some(delay) => {
+                let delay = match pacing::parse_duration(delay) {
+                    Ok(delay) => delay,
+                    Err(e) => {
+                        say!("{}", e);
+                        return;
+                    }
+                };
+                let jitter = match matches.value_of(JITTER).map(pacing::parse_duration) {
+                    Some(Ok(jitter)) => jitter,
+                    Some(Err(e)) => {
+                        say!("{}", e);
+                        return;
+                    }
+                    None => time::Duration::from_secs(0),
+                };
+                Some(pacing::Pace { delay, jitter })
+            }
+            None => None,
+        };
+        let shred = if matches.is_present(SHRED) {
+            let passes = if matches.is_present(SHRED_PASSES) {
+                match value_t!(matches, SHRED_PASSES, usize) {
+                    Ok(passes) if passes > 0 => passes,
+                    _ => {
+                        say!("Shred passes requires a positive integer value.");
+                        return;
+                    }
+                }
+            } else {
+                1
+            };
+            let delay = match matches.value_of(SHRED_DELAY).map(pacing::parse_duration) {
+                Some(Ok(delay)) => delay,
+                Some(Err(e)) => {
+                    say!("{}", e);
+                    return;
+                }
+                None => time::Duration::from_secs(0),
+            };
+            Some(shred::Shred {
+                text: matches.value_of(SHRED_TEXT).map(String::from),
+                passes,
+                delay,
+            })
+        } else {
+            None
+        };
+        let filters = match filter_options(matches) {
+            Ok(filters) => filters,
+            Err(e) => {
+                say!("{}", e);
+                return;
+            }
+        };
+        let fullnames = match matches.value_of(GDPR_EXPORT) {
+            Some(path) => match gdpr::read_fullnames(Path::new(path)) {
+                Ok(fullnames) => {
+                    say!(
+                        "Found {} comments/submissions in the data export.",
+                        fullnames.len()
+                    );
+                    Some(fullnames)
+                }
+                Err(e) => {
+                    say!("{}", e);
+                    return;
+                }
+            },
+            None => None,
+        };
+        let preview_length = match value_t!(matches, PREVIEW_LENGTH, usize) {
+            Ok(0) => None,
+            Ok(length) => Some(length),
+            Err(_) => {
+                say!("Preview length requires a positive integer value.");
+                return;
+            }
+        };
+        let purge_after = match matches.value_of(PURGE_AFTER).map(pacing::parse_duration) {
+            Some(Ok(grace)) => Some(grace),
+            Some(Err(e)) => {
+                say!("{}", e);
+                return;
+            }
+            None => None,
+        };
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            pace,
+            yes: matches.is_present(YES),
+            verify: matches.is_present(VERIFY),
+            max_requests,
+            timeout,
+            shred,
+            preview_length,
+            export: matches.value_of(EXPORT).map(String::from),
+            fullnames,
+            trash: matches.is_present(TRASH) || purge_after.is_some(),
+            purge_after,
+            ..filters
+        };
+        let interval = match matches.value_of(INTERVAL).map(pacing::parse_duration) {
+            _ if !matches.is_present(WATCH) => None,
+            Some(Ok(interval)) if interval.as_secs() > 0 => Some(interval),
+            Some(Err(e)) => {
+                say!("{}", e);
+                return;
+            }
+            _ => {
+                say!("--watch needs an --interval of at least a second, e.g. 6h.");
+                return;
+            }
+        };
+        if interval.is_some() && !options.dry && !options.yes {
+            say!("--watch can't ask for confirmation, add --yes or --dry-run.");
+            return;
+        }
+        let all = matches.is_present(ALL_ACCOUNTS);
+        let usernames = if all {
+            match config::read_config_usernames() {
+                Ok(usernames) => usernames,
+                Err(e) => fail(e.into()),
+            }
+        } else {
+            vec![String::from(matches.value_of(USERNAME).unwrap())]
+        };
+        let username = match usernames.first() {
+            Some(username) => username.clone(),
+            None => {
+                say!("There are no saved usernames in your config. Try authorizing a username first.");
+                std::process::exit(EXIT_CONFIG_NOT_FOUND);
+            }
+        };
+        match config::read_config_account_info(&username) {
+            Some(ai) => {
+                output::set_format(format);
+                // With --all, the time is read in the first account's time zone.
+                if let Some(at) = matches.value_of(RUN_AT) {
+                    if let Err(e) = wait_until(at, &ai).await {
+                        say!("{}", e);
+                        return;
+                    }
+                }
+                if let Some(interval) = interval {
+                    let zone =
+                        timezone::Zone::from_config(&ai.timezone).unwrap_or(timezone::Zone::Local);
+                    watch(all, &username, options, interval, &zone).await;
+                    return;
+                }
+                let paged = options.dry && !matches.is_present(NO_PAGER);
+                if paged {
+                    output::start_paging();
+                }
+                let result = run_once(all, &username, options).await;
+                if paged {
+                    output::end_paging();
+                }
+                match result {
+                    Ok(_) => say!("Done."),
+                    Err(e) => fail_or_reauthorize(e).await,
+                }
+            }
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(RETRY) {
+        let dry = matches.is_present(DRYRUN);
+        let username = matches.value_of(USERNAME).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(_) => match retry(username.into(), dry).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(PLAN) {
+        let options = match filter_options(matches) {
+            Ok(options) => RunOptions {
+                dry: true,
+                preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+                archive: matches.value_of(OFFLINE_ARCHIVE).map(String::from),
+                ..options
+            },
+            Err(e) => {
+                say!("{}", e);
+                return;
+            }
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let path = Path::new(matches.value_of(PLAN_OUTPUT).unwrap());
+        match config::read_config_account_info(&username) {
+            Some(_) => {
+                if let Err(e) = make_plan(username.into(), options, path).await {
+                    fail(e);
+                }
+            }
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(APPLY) {
+        let options = RunOptions {
+            yes: matches.is_present(YES),
+            verify: matches.is_present(VERIFY),
+            preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+            ..Default::default()
+        };
+        let path = Path::new(matches.value_of(PLAN_FILE).unwrap());
+        match apply_plan(path, options).await {
+            Ok(_) => say!("Done."),
+            Err(e) => fail(e),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(STATS) {
+        let options = match filter_options(matches) {
+            Ok(options) => RunOptions {
+                dry: true,
+                archive: matches.value_of(OFFLINE_ARCHIVE).map(String::from),
+                ..options
+            },
+            Err(e) => {
+                say!("{}", e);
+                return;
+            }
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(_) => {
+                if let Err(e) = history_stats(username.into(), options).await {
+                    fail(e);
+                }
+            }
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(PURGE_SAVED) {
+        let options = match filter_options(matches) {
+            Ok(options) => RunOptions {
+                dry: matches.is_present(DRYRUN),
+                yes: matches.is_present(YES),
+                preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+                ..options
+            },
+            Err(e) => {
+                say!("{}", e);
+                return;
+            }
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(_) => match purge_saved(username.into(), options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(PURGE_VOTES) {
+        let limit = if matches.is_present(LIMIT) {
+            match value_t!(matches, LIMIT, usize) {
+                Ok(limit) => Some(limit),
+                Err(_) => {
+                    say!("Limit requires a positive integer value.");
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            limit,
+            preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let votes = matches.value_of(VOTES).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(_) => match purge_votes(username.into(), votes, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(PURGE_HIDDEN) {
+        let limit = if matches.is_present(LIMIT) {
+            match value_t!(matches, LIMIT, usize) {
+                Ok(limit) => Some(limit),
+                Err(_) => {
+                    say!("Limit requires a positive integer value.");
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            limit,
+            preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let hide_own = matches.is_present(HIDE_OWN);
+        match config::read_config_account_info(&username) {
+            Some(_) => match purge_hidden(username.into(), hide_own, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(UNSUBSCRIBE_ALL) {
+        let only: Option<Vec<String>> = matches
+            .values_of(SEARCH_SUBREDDITS)
+            .map(|subreddits| subreddits.map(String::from).collect());
+        let keep: Option<Vec<String>> = matches
+            .values_of(KEEP_SUBSCRIPTIONS)
+            .map(|subreddits| subreddits.map(String::from).collect());
+        for list in only.iter().chain(keep.iter()) {
+            if let Err(e) = patterns::validate_subreddit_patterns(list.iter().map(|p| p.as_str())) {
+                say!("{}", e);
+                return;
+            }
+        }
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(_) => match unsubscribe_all(username.into(), only, keep, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(MULTIS) {
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let delete_all = matches.is_present(DELETE_ALL);
+        match config::read_config_account_info(&username) {
+            Some(_) => match multireddits(username.into(), delete_all, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(FRIENDS) {
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let remove_all = matches.is_present(REMOVE_ALL);
+        match config::read_config_account_info(&username) {
+            Some(_) => match friends(username.into(), remove_all, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(BLOCKED) {
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let export_path = matches.value_of(EXPORT).map(Path::new);
+        let unblock_all = matches.is_present(UNBLOCK_ALL);
+        match config::read_config_account_info(&username) {
+            Some(_) => {
+                match blocked_users(username.into(), export_path, unblock_all, options).await {
+                    Ok(_) => say!("Done."),
+                    Err(e) => fail(e),
+                }
+            }
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(WIPE) {
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let backup = match matches.value_of(WIPE_BACKUP) {
+            Some(path) => std::path::PathBuf::from(path),
+            None => std::path::PathBuf::from(format!("{}-backup.json", username)),
+        };
+        match config::read_config_account_info(&username) {
+            Some(_) => match wipe(username.into(), &backup, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if matches.subcommand_matches(ACCOUNTS).is_some() {
+        let accounts: Vec<config::AccountInfo> = config::read_config_usernames()
+            .unwrap_or_else(|e| fail(e.into()))
+            .iter()
+            .filter_map(|username| config::read_config_account_info(username))
+            .collect();
+        if accounts.is_empty() {
+            say!("There are no saved usernames in your config. Try authorizing a username first.");
+            std::process::exit(EXIT_CONFIG_NOT_FOUND);
+        }
+        let now = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        say!("{}", saved_accounts_table(&accounts, now));
+    } else if let Some(matches) = matches.subcommand_matches(LOGOUT) {
+        let username = matches.value_of(USERNAME).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(ai) => match logout(ai).await {
+                Ok(_) => say!(
+                    "Revoked redelete's access to {} and removed it from the config.",
+                    username
+                ),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(WHOAMI) {
+        let username = matches.value_of(USERNAME).unwrap();
+        match config::read_config_account_info(&username) {
+            Some(ai) => {
+                if let Err(e) = whoami(ai).await {
+                    fail_or_reauthorize(e).await
+                }
+            }
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(SIMULATE) {
+        let mut options = match filter_options(matches) {
+            Ok(options) => options,
+            Err(e) => {
+                say!("{}", e);
+                std::process::exit(EXIT_ERROR);
+            }
+        };
+        options.preview_length = DEFAULT_PREVIEW_LENGTH.parse().ok();
+        let sample_size = value_t!(matches, SAMPLE, usize).unwrap_or_else(|e| e.exit());
+        let username = matches.value_of(USERNAME);
+        if let Some(username) = username {
+            if config::read_config_account_info(username).is_none() {
+                unknown_username(username);
+            }
+        }
+        let input = matches.value_of(SIMULATE_INPUT).map(Path::new);
+        if let Err(e) = simulate(username, input, sample_size, &options) {
+            fail(e)
+        }
+    } else if let Some(matches) = matches.subcommand_matches(RESTORE) {
+        let options = RunOptions {
+            dry: matches.is_present(DRYRUN),
+            yes: matches.is_present(YES),
+            preview_length: DEFAULT_PREVIEW_LENGTH.parse().ok(),
+            ..Default::default()
+        };
+        let username = matches.value_of(USERNAME).unwrap();
+        let backup = Path::new(matches.value_of(RESTORE_FROM).unwrap());
+        let fullnames: Vec<String> = matches
+            .values_of(RESTORE_IDS)
+            .unwrap()
+            .map(String::from)
+            .collect();
+        match config::read_config_account_info(&username) {
+            Some(_) => match restore_items(username.into(), backup, &fullnames, options).await {
+                Ok(_) => say!("Done."),
+                Err(e) => fail(e),
+            },
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(TRASH) {
+        let username = matches.value_of(USERNAME).unwrap();
+        if config::read_config_account_info(&username).is_none() {
+            unknown_username(&username);
+        }
+        match matches.values_of(UNMARK) {
+            Some(names) => {
+                let names: Vec<String> = names.map(String::from).collect();
+                match state::unmark_pending(&username, &names) {
+                    Ok(n) => say!("Took {} posts out of the trash.", n),
+                    Err(e) => fail(e.into()),
+                }
+            }
+            None => {
+                if let Err(e) = list_trash(&username, DEFAULT_PREVIEW_LENGTH.parse().ok()) {
+                    fail(e);
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches(EXPORT_COMMAND) {
+        let username = matches.value_of(USERNAME).unwrap();
+        let format = matches.value_of(FORMAT).unwrap();
+        let group_by = archive::GroupBy::parse(matches.value_of(GROUP_BY).unwrap()).unwrap();
+        let path = Path::new(matches.value_of(EXPORT_OUTPUT).unwrap());
+        match config::read_config_account_info(&username) {
+            Some(_) => {
+                if let Err(e) = archive_history(username.into(), format, group_by, path).await {
+                    fail(e);
+                }
+            }
+            None => unknown_username(&username),
+        }
+    } else if let Some(matches) = matches.subcommand_matches(SEARCH) {
+        let username = matches.value_of(USERNAME).unwrap();
+        let ai = match config::read_config_account_info(&username) {
+            Some(ai) => ai,
+            None => unknown_username(&username),
+        };
+        let search = match search_options(matches, &ai) {
+            Ok(search) => search,
+            Err(e) => {
+                say!("{}", e);
+                return;
+            }
+        };
+        let options = RunOptions {
+            archive: matches.value_of(OFFLINE_ARCHIVE).map(String::from),
+            ..Default::default()
+        };
+        if let Err(e) = search_history(username.into(), &search, options).await {
+            fail(e);
+        }
+    } else if let Some(matches) = matches.subcommand_matches(KEEP) {
+        let username = matches.value_of(USERNAME).unwrap();
+        if config::read_config_account_info(&username).is_none() {
+            unknown_username(&username);
+        }
+        let names: Vec<String> = matches
+            .values_of(FULLNAMES)
+            .unwrap()
+            .map(String::from)
+            .collect();
+        if let Some(bad) = names
+            .iter()
+            .find(|n| !(n.starts_with("t1_") || n.starts_with("t3_")))
+        {
+            say!(
+                "{} is not a comment or submission fullname, they look like t1_abc123 or t3_def456.",
+                bad
+            );
+            return;
+        }
+        if matches.is_present(UNKEEP) {
+            match state::forget(&username, &names) {
+                Ok(n) => say!("No longer keeping {} posts.", n),
+                Err(e) => {
+                    say!("Unable to update kept posts: {}", e);
+                    std::process::exit(EXIT_ERROR);
+                }
+            }
+        } else {
+            let items: Vec<(String, String)> =
+                names.into_iter().map(|n| (n, String::new())).collect();
+            match state::record(&username, &items, state::Decision::Kept) {
+                Ok(()) => say!("Keeping {} posts.", items.len()),
+                Err(e) => {
+                    say!("Unable to update kept posts: {}", e);
+                    std::process::exit(EXIT_ERROR);
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches(SCHEDULE) {
+        if let Err(e) = run_scheduled(matches.is_present(DRYRUN), false).await {
+            fail(e);
+        }
+    } else if let Some(matches) = matches.subcommand_matches(DAEMON) {
+        // Logs go to the journal, which shows escape codes as is.
+        output::set_color(true);
+        if let Err(e) = run_scheduled(matches.is_present(DRYRUN), true).await {
+            fail(e);
+        }
+    } else if let Some(matches) = matches.subcommand_matches(VERIFY_LOG) {
+        let username = matches.value_of(USERNAME).unwrap();
+        match audit::verify_log(&username) {
+            Ok(0) => say!("No deletions have been logged for {}.", &username),
+            Ok(n) => say!("Audit log for {} is intact ({} entries).", &username, n),
+            Err(e) => {
+                say!("{}", e);
+                std::process::exit(EXIT_ERROR);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Need to test check_should_delete with more mixed configs to make sure.
+    use super::*;
+    use config::tests::{account_info, fresh_account_info};
+    use reddit_api::RedditPost;
+    const SUBREDDIT: &'static str = "subreddit";
+    fn hours_ago_to_epoch(hours: f64) -> f64 {
+        time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64()
+            - (3600.0 * hours)
+    }
+    fn test_post(hours_ago: f64, score: i32) -> reddit_api::Post {
+        reddit_api::Post {
+            saved: true,
+            name: "name".into(),
+            created_utc: hours_ago_to_epoch(hours_ago),
+            subreddit: String::from(SUBREDDIT),
+            score,
+            selftext: "".into(),
+            url: "".into(),
+            title: "".into(),
+            distinguished: None,
+            stickied: false,
+            domain: "self.rust".into(),
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            archived: false,
+            quarantine: false,
+            is_self: true,
+            is_gallery: false,
+            post_hint: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_no_config_delete() {
+        assert_eq!(
+            check_should_delete(
+                &fresh_account_info(),
+                &None,
+                &test_post(0.0, 0).deletion_info()
+            ),
+            true
+        )
+    }
+    #[test]
+    fn test_max_hours_keep() {
+        let mut account = account_info();
+        account.minimum_score = None;
+        account.excluded_subreddits = None;
+        let keep = test_post(23.0, 0).deletion_info();
+        assert_eq!(check_should_delete(&account, &None, &keep), false);
+    }
+    #[test]
+    fn test_max_hours_delete() {
+        let mut account = account_info();
+        account.minimum_score = None;
+        account.excluded_subreddits = None;
+        let delete = test_post(25.0, 0).deletion_info();
+        assert_eq!(check_should_delete(&account, &None, &delete), true);
+    }
+    #[test]
+    fn test_minimum_score_keep() {
+        let mut account = fresh_account_info();
+        account.minimum_score = Some(1000);
+        let keep = test_post(0.0, 1001).deletion_info();
+        assert_eq!(check_should_delete(&account, &None, &keep), false);
+    }
+    #[test]
+    fn test_minimum_score_delete() {
+        let mut account = fresh_account_info();
+        account.minimum_score = Some(1000);
+        let delete = test_post(25.0, 0).deletion_info();
+        assert_eq!(check_should_delete(&account, &None, &delete), true);
+    }
+    #[test]
+    fn test_max_score() {
+        let mut account = fresh_account_info();
+        account.max_score = Some(0);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, -3).deletion_info()),
+            true
+        );
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            true
+        );
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 1).deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_without_latest() {
+        let all = vec![
+            test_post(5.0, 0).deletion_info(),
+            test_post(1.0, 1).deletion_info(),
+            test_post(10.0, 2).deletion_info(),
+            test_post(3.0, 3).deletion_info(),
+        ];
+        let candidates = without_latest(all, 2);
+        let scores: Vec<i32> = candidates.iter().map(|p| p.score).collect();
+        assert_eq!(scores, vec![0, 2]);
+    }
+    #[test]
+    fn test_keep_distinguished() {
+        let mut account = fresh_account_info();
+        let mut post = test_post(0.0, 0);
+        post.distinguished = Some("moderator".into());
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        account.keep_distinguished = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        post.distinguished = None;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_edited_filter() {
+        let mut account = fresh_account_info();
+        let mut post = test_post(0.0, 0);
+        account.edited = Some(config::EditedFilter::Keep);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.edited = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        account.edited = Some(config::EditedFilter::Only);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.edited = false;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_rule() {
+        let mut account = fresh_account_info();
+        account.rule = Some("score < 5 && subreddit != \"rust\"".into());
+        let filters = Filters::load(&account).unwrap();
+        assert_eq!(
+            check_should_delete(&account, &filters.rule, &test_post(0.0, 0).deletion_info()),
+            true
+        );
+        assert_eq!(
+            check_should_delete(&account, &filters.rule, &test_post(0.0, 5).deletion_info()),
+            false
+        );
+        account.rule = Some("score <".into());
+        assert_eq!(Filters::load(&account).is_err(), true);
+    }
+    #[test]
+    fn test_controversial_filter() {
+        let mut account = fresh_account_info();
+        let mut info = test_post(0.0, 0).deletion_info();
+        account.controversial = Some(config::ControversialFilter::Keep);
+        assert_eq!(check_should_delete(&account, &None, &info), true);
+        info.controversial = true;
+        assert_eq!(check_should_delete(&account, &None, &info), false);
+        account.controversial = Some(config::ControversialFilter::Only);
+        assert_eq!(check_should_delete(&account, &None, &info), true);
+        info.controversial = false;
+        assert_eq!(check_should_delete(&account, &None, &info), false);
+    }
+    #[test]
+    fn test_combine_any() {
+        let mut account = fresh_account_info();
+        account.max_hours = Some(24);
+        account.minimum_score = Some(5);
+        account.combine = Some(config::CombineMode::Any);
+        let old_high = test_post(48.0, 100).deletion_info();
+        let new_low = test_post(1.0, 1).deletion_info();
+        let new_high = test_post(1.0, 100).deletion_info();
+        assert_eq!(check_should_delete(&account, &None, &old_high), true);
+        assert_eq!(check_should_delete(&account, &None, &new_low), true);
+        assert_eq!(check_should_delete(&account, &None, &new_high), false);
+        account.combine = Some(config::CombineMode::All);
+        assert_eq!(check_should_delete(&account, &None, &old_high), false);
+        assert_eq!(check_should_delete(&account, &None, &new_low), false);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(48.0, 1).deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_combine_any_keeps_exclusions() {
+        let mut account = fresh_account_info();
+        account.combine = Some(config::CombineMode::Any);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            true
+        );
+        account.max_hours = Some(24);
+        account.keywords = Some(vec!["acme".into()]);
+        account.excluded_subreddits = Some(vec![SUBREDDIT.into()]);
+        let mut post = test_post(48.0, 0);
+        post.selftext = "acme".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        account.excluded_subreddits = None;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        let post = test_post(1.0, 0);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_combine_any_keeps_restrictions() {
+        let mut account = fresh_account_info();
+        account.combine = Some(config::CombineMode::Any);
+        account.max_hours = Some(24);
+        account.minimum_score = Some(5);
+        account.included_subreddits = Some(vec!["rust".into()]);
+        let old = test_post(48.0, 100).deletion_info();
+        assert_eq!(check_should_delete(&account, &None, &old), false);
+        account.included_subreddits = Some(vec![SUBREDDIT.into()]);
+        assert_eq!(check_should_delete(&account, &None, &old), true);
+        let mut link = test_post(48.0, 100);
+        link.domain = "github.com".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &link.deletion_info()),
+            true
+        );
+        account.deleted_domains = Some(vec!["imgur.com".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &link.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_post_types() {
+        let mut account = fresh_account_info();
+        account.post_types = Some(vec!["link".into(), "image".into()]);
+        let mut post = test_post(0.0, 0);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        post.is_self = false;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.post_hint = Some("image".into());
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.post_hint = Some("hosted:video".into());
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_quarantined() {
         let mut account = fresh_account_info();
-        account.minimum_score = Some(1000);
-        let delete = test_post(25.0, 0).deletion_info();
-        assert_eq!(check_should_delete(&account, &delete), true);
+        let mut post = test_post(0.0, 0);
+        post.quarantine = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        account.include_quarantined = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_archived_policy() {
+        let mut account = fresh_account_info();
+        let mut post = test_post(0.0, 0);
+        post.archived = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        account.archived = Some(config::ArchivedPolicy::Warn);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        account.archived = Some(config::ArchivedPolicy::Skip);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        post.archived = false;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_keep_stickied() {
+        let mut account = fresh_account_info();
+        let mut post = test_post(0.0, 0);
+        post.stickied = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        account.keep_stickied = true;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        post.stickied = false;
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_failure_table() {
+        let failed = vec![
+            journal::FailedDeletion::new("t1_a".into(), "rust".into(), "Reddit API Error".into()),
+            journal::FailedDeletion::new(
+                "t3_abcdef".into(),
+                "AskReddit".into(),
+                "Timed out".into(),
+            ),
+        ];
+        let table = failure_table(&failed);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "NAME       SUBREDDIT     ERROR");
+        assert_eq!(lines[1], "t1_a       /r/rust       Reddit API Error");
+        assert_eq!(lines[2], "t3_abcdef  /r/AskReddit  Timed out");
+        assert_eq!(check_failures(&Vec::new()).is_ok(), true);
+        match check_failures(&failed) {
+            Err(RedeleteError::DeletionsFailed { count }) => assert_eq!(count, 2),
+            _ => panic!("expected failed deletions"),
+        }
+    }
+    #[test]
+    fn test_still_listed() {
+        let listed: Vec<reddit_api::DeletionInfo> = (0..3)
+            .map(|i| {
+                let mut p = test_post(1.0, 1);
+                p.name = format!("t3_{}", i);
+                p.deletion_info()
+            })
+            .collect();
+        let deleted: HashSet<String> = vec!["t3_1".into(), "t3_9".into()].into_iter().collect();
+        let leftovers = still_listed(listed.into_iter(), &deleted);
+        assert_eq!(leftovers.len(), 1);
+        assert_eq!(leftovers[0].name, "t3_1");
+        assert_eq!(leftovers[0].subreddit, SUBREDDIT);
+    }
+    #[test]
+    fn test_can_stream() {
+        let mut account = account_info();
+        account.keep_latest = None;
+        account.keep_top_per_subreddit = None;
+        account.keep_top_percent = None;
+        account.backup = false;
+        let mut options = RunOptions {
+            yes: true,
+            ..Default::default()
+        };
+        assert_eq!(can_stream(&account, &options), true);
+        options.backfill = true;
+        assert_eq!(can_stream(&account, &options), false);
+        options.backfill = false;
+        options.yes = false;
+        assert_eq!(can_stream(&account, &options), false);
+        options.dry = true;
+        assert_eq!(can_stream(&account, &options), true);
+        options.export = Some(String::from("deletions.csv"));
+        assert_eq!(can_stream(&account, &options), false);
+        options.export = None;
+        options.trash = true;
+        assert_eq!(can_stream(&account, &options), false);
+        options.trash = false;
+        account.keep_latest = Some(10);
+        assert_eq!(can_stream(&account, &options), false);
+    }
+    #[test]
+    fn test_settings_json_leaves_out_token() {
+        let mut account = account_info();
+        account.minimum_score = Some(10);
+        let settings = settings_json(&account);
+        assert_eq!(settings["minimum_score"], 10);
+        assert_eq!(settings.get("token"), None);
+        assert_eq!(settings.get("token_expires"), None);
+    }
+    #[test]
+    fn test_parse_smtp_server() {
+        let server = |host: &str, port: u16| Ok((String::from(host), port));
+        assert_eq!(
+            parse_smtp_server("smtp.example.com"),
+            server("smtp.example.com", DEFAULT_SMTP_PORT)
+        );
+        assert_eq!(
+            parse_smtp_server("smtp.example.com:465"),
+            server("smtp.example.com", 465)
+        );
+        assert_eq!(parse_smtp_server("[::1]:2525"), server("::1", 2525));
+        assert_eq!(parse_smtp_server("[::1]"), server("::1", DEFAULT_SMTP_PORT));
+        assert_eq!(
+            parse_smtp_server("2001:db8::25"),
+            server("2001:db8::25", DEFAULT_SMTP_PORT)
+        );
+        assert_eq!(parse_smtp_server("[::1]2525").is_err(), true);
+        assert_eq!(parse_smtp_server("smtp.example.com:smtp").is_err(), true);
+        assert_eq!(parse_smtp_server(":25").is_err(), true);
+    }
+    #[test]
+    fn test_settings_toml() {
+        let mut account = account_info();
+        account.minimum_score = Some(10);
+        account.webhook_url = Some("https://hooks.example.com/secret".into());
+        let settings = settings_toml(&account).unwrap();
+        assert_eq!(settings.contains("minimum_score = 10"), true);
+        let parsed: toml::Value = toml::from_str(&settings).unwrap();
+        assert_eq!(parsed["webhook_url"].as_str(), Some("<hidden>"));
+        assert_eq!(settings.contains("secret"), false);
+        assert_eq!(settings.contains("ACCESS_TOKEN"), false);
+    }
+    #[test]
+    fn test_accounts_table() {
+        colored::control::set_override(false);
+        let results = vec![
+            (String::from("first"), Ok(())),
+            (String::from("second_one"), Err(RedeleteError::RunError)),
+        ];
+        assert_eq!(
+            accounts_table(&results),
+            "first       ok\nsecond_one  Unable to run"
+        );
+    }
+
+    #[test]
+    fn test_item_line() {
+        colored::control::set_override(false);
+        let post = test_post(2.0, -4).deletion_info();
+        let now = post.created_utc + 3.0 * 60.0 * 60.0;
+        assert_eq!(
+            item_line("DELETE", now, &post, Some(5)),
+            format!(
+                "DELETE submission 3 hours ago, score -4, /r/subreddit: {}",
+                output::preview(&post.summary(), Some(5))
+            )
+        );
+        assert_eq!(
+            item_line("KEEP", now, &post, None).starts_with("KEEP submission "),
+            true
+        );
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let not_found = || config::ConfigError::NotFound {
+            what: String::from("Username someone"),
+        };
+        assert_eq!(
+            RedeleteError::DeletionsFailed { count: 2 }.exit_code(),
+            EXIT_PARTIAL
+        );
+        assert_eq!(
+            RedeleteError::ConfigError {
+                source: not_found()
+            }
+            .exit_code(),
+            EXIT_CONFIG_NOT_FOUND
+        );
+        assert_eq!(
+            RedeleteError::RedditApiError {
+                source: reddit_api::RedditApiError::RefreshTokenError
+            }
+            .exit_code(),
+            EXIT_AUTH
+        );
+        assert_eq!(
+            RedeleteError::RedditApiError {
+                source: reddit_api::RedditApiError::Config {
+                    source: not_found()
+                }
+            }
+            .exit_code(),
+            EXIT_CONFIG_NOT_FOUND
+        );
+        assert_eq!(
+            RedeleteError::RedditApiError {
+                source: reddit_api::RedditApiError::Forbidden {
+                    reason: String::from("banned")
+                }
+            }
+            .exit_code(),
+            EXIT_API
+        );
+        assert_eq!(RedeleteError::RunError.exit_code(), EXIT_ERROR);
+    }
+
+    #[test]
+    fn test_next_scheduled_run() {
+        use chrono::TimeZone;
+        let mut account = account_info();
+        account.timezone = Some(String::from("UTC"));
+        let now = chrono::Utc.ymd(2020, 1, 15).and_hms(10, 0, 0);
+        assert_eq!(next_scheduled_run(&account, now), Ok(None));
+        account.schedule = Some(String::from("30 9 * * *"));
+        assert_eq!(
+            next_scheduled_run(&account, now),
+            Ok(Some(chrono::Utc.ymd(2020, 1, 16).and_hms(9, 30, 0)))
+        );
+        account.schedule = Some(String::from("every day"));
+        assert_eq!(next_scheduled_run(&account, now).is_err(), true);
+    }
+
+    #[test]
+    fn test_confirm() {
+        assert_eq!(confirm("", "y\n".as_bytes()), true);
+        assert_eq!(confirm("", " YES \n".as_bytes()), true);
+        assert_eq!(confirm("", "n\n".as_bytes()), false);
+        assert_eq!(confirm("", "\n".as_bytes()), false);
+        assert_eq!(confirm("", "".as_bytes()), false);
+    }
+    #[test]
+    fn test_apply_filter_overrides() {
+        let mut account = account_info();
+        let options = RunOptions {
+            minimum_score: Some(0),
+            max_hours: Some(720),
+            add_excluded: vec!["rust".into(), "A".into()],
+            remove_excluded: vec!["b".into()],
+            ..Default::default()
+        };
+        apply_filter_overrides(&mut account, &options);
+        assert_eq!(account.minimum_score, None);
+        assert_eq!(account.max_hours, Some(720));
+        assert_eq!(
+            account.excluded_subreddits,
+            Some(vec!["a".into(), "c".into(), "rust".into()])
+        );
+        let mut account = account_info();
+        apply_filter_overrides(&mut account, &RunOptions::default());
+        assert_eq!(account, account_info());
+    }
+    #[test]
+    fn test_without_top_percent() {
+        let all: Vec<reddit_api::DeletionInfo> = (0..20)
+            .map(|i| {
+                let mut p = test_post(1.0, i);
+                p.name = format!("t3_{}", i);
+                p.deletion_info()
+            })
+            .collect();
+        let candidates = without_top_percent(all, 10);
+        assert_eq!(candidates.len(), 18);
+        assert_eq!(candidates.iter().any(|p| p.score >= 18), false);
+        let few = vec![test_post(1.0, 3).deletion_info()];
+        assert_eq!(without_top_percent(few, 1).is_empty(), true);
+    }
+    #[test]
+    fn test_without_top_per_subreddit() {
+        let post = |name: &str, subreddit: &str, score: i32| {
+            let mut p = test_post(1.0, score);
+            p.name = name.into();
+            p.subreddit = subreddit.into();
+            p.deletion_info()
+        };
+        let all = vec![
+            post("a", "rust", 10),
+            post("b", "rust", 50),
+            post("c", "Rust", 20),
+            post("d", "webdev", 1),
+            post("e", "webdev", 5),
+        ];
+        let candidates = without_top_per_subreddit(all, 1);
+        let names: Vec<String> = candidates.into_iter().map(|p| p.name).collect();
+        assert_eq!(
+            names,
+            vec![String::from("a"), String::from("c"), String::from("d")]
+        );
+    }
+    #[test]
+    fn test_filter_summary() {
+        let mut account = fresh_account_info();
+        assert_eq!(filter_summary(&account), "none, deletes everything");
+        account.max_hours = Some(48);
+        account.excluded_subreddits = Some(vec!["rust".into(), "pics".into()]);
+        account.keep_latest = Some(10);
+        assert_eq!(
+            filter_summary(&account),
+            "older than 48h, 2 excluded subreddits, keep latest 10"
+        );
+    }
+    #[test]
+    fn test_saved_accounts_table() {
+        let mut account = fresh_account_info();
+        account.token_expires = 1000;
+        account.timezone = Some("UTC".into());
+        account.last_run = Some(1579506505);
+        let table = saved_accounts_table(&[account], 100);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].starts_with("USERNAME"), true);
+        assert_eq!(lines[1].starts_with("/u/TestUser"), true);
+        assert_eq!(lines[1].contains("valid"), true);
+        assert_eq!(lines[1].contains("2020-01-20 07:48 UTC"), true);
+        assert_eq!(lines[1].contains("none, deletes everything"), true);
+    }
+    #[test]
+    fn test_token_info() {
+        let mut account = fresh_account_info();
+        account.token_expires = 1579506505;
+        account.timezone = Some("UTC".into());
+        let info = token_info(&account, Some("TestUser"), 0);
+        assert_eq!(info.contains("Verified: reddit accepts the token\n"), true);
+        assert_eq!(
+            info.contains("Token: valid, expires 2020-01-20 07:48 UTC"),
+            true
+        );
+        assert_eq!(
+            info.contains("Granted scopes: history, edit, account"),
+            true
+        );
+        assert_eq!(info.contains("Missing scopes: identity, save"), true);
+        let refused = token_info(&account, None, 0);
+        assert_eq!(refused.contains("Verified: no"), true);
+    }
+    #[test]
+    fn test_matching() {
+        let mut account = fresh_account_info();
+        account.max_hours = Some(24);
+        let mut kept_old = test_post(72.0, 0).deletion_info();
+        kept_old.name = "t3_kept".into();
+        let items = vec![
+            test_post(48.0, 0).deletion_info(),
+            test_post(1.0, 0).deletion_info(),
+            kept_old,
+        ];
+        let kept: HashSet<String> = vec![String::from("t3_kept")].into_iter().collect();
+        let filters = Filters {
+            script: None,
+            rule: None,
+        };
+        let matched = matching(&account, &filters, &items, &kept);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].created_utc, items[0].created_utc);
+    }
+    #[test]
+    fn test_load_history_offline() {
+        let history: Vec<serde_json::Value> = test_data::comments()
+            .iter()
+            .map(|item| serde_json::from_str::<serde_json::Value>(item).unwrap()["data"].clone())
+            .collect();
+        let mut path = std::env::temp_dir();
+        path.push("redelete-test-offline.json");
+        std::fs::write(&path, serde_json::to_string(&history).unwrap()).unwrap();
+        let options = RunOptions {
+            archive: Some(path.display().to_string()),
+            ..Default::default()
+        };
+        // Nothing is mocked, so any API call would fail.
+        let client = reddit_api::RedditClient::new("TestUser".into());
+        let all = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async { load_history(&client, &account_info(), &options).await })
+            .unwrap();
+        assert_eq!(all.len(), history.len());
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn test_check_scopes() {
+        let mut account = account_info();
+        account.token.scope = String::from("history edit identity read vote");
+        assert_eq!(check_scopes(&account, &["vote"]).is_ok(), true);
+        assert_eq!(
+            check_scopes(&account, &["mysubreddits", "subscribe"])
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "{} hasn't given redelete these scopes: mysubreddits, subscribe. Run `redelete authorize --reauthorize {}` to grant them",
+                account.username, account.username
+            )
+        );
+        account.token.scope = String::from("*");
+        assert_eq!(check_scopes(&account, &["submit"]).is_ok(), true);
+    }
+    #[test]
+    fn test_trash() {
+        colored::control::set_override(false);
+        let pending = |name: &str, marked_at: u64| {
+            let mut item = test_post(2.0, 1).deletion_info();
+            item.name = name.into();
+            state::PendingItem { item, marked_at }
+        };
+        let week = time::Duration::from_secs(7 * 24 * 60 * 60);
+        let now = 1579506505;
+        let due = due_for_purge(
+            vec![
+                pending("t3_old", now - 8 * 24 * 60 * 60),
+                pending("t3_new", now - 60),
+            ],
+            week,
+            now,
+        );
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].name, "t3_old");
+        let zone = timezone::Zone::from_config(&Some("UTC".into())).unwrap();
+        let listing = trash_listing(&[pending("t3_new", now)], &zone, now_utc(), None);
+        assert_eq!(listing.starts_with("t3_new TRASH submission "), true);
+        assert_eq!(listing.ends_with("marked 2020-01-20 07:48 UTC"), true);
+    }
+    #[test]
+    fn test_trash_rescues_what_settings_now_keep() {
+        let mut account = fresh_account_info();
+        account.username = String::from("TrashRescueUser");
+        let mut item = test_post(2.0, 1).deletion_info();
+        item.name = "t3_rescued".into();
+        state::mark_pending(&account.username, &[item]).unwrap();
+        account.excluded_subreddits = Some(vec![SUBREDDIT.into()]);
+        let options = RunOptions {
+            trash: true,
+            purge_after: Some(time::Duration::from_secs(0)),
+            ..Default::default()
+        };
+        let filters = Filters::load(&account).unwrap();
+        let zone = timezone::Zone::from_config(&Some("UTC".into())).unwrap();
+        let due = take_out_trash(
+            &account,
+            &options,
+            &filters,
+            Vec::new(),
+            &HashSet::new(),
+            &zone,
+        )
+        .unwrap();
+        assert_eq!(due.is_empty(), true);
+        assert_eq!(state::pending(&account.username).unwrap().is_empty(), true);
+    }
+    #[test]
+    fn test_trash_rescues_what_keep_latest_keeps() {
+        let mut account = fresh_account_info();
+        account.username = String::from("TrashKeepLatestUser");
+        let post = |name: &str, hours_ago: f64| {
+            let mut item = test_post(hours_ago, 1).deletion_info();
+            item.name = name.into();
+            item
+        };
+        state::mark_pending(&account.username, &[post("t3_newest", 1.0)]).unwrap();
+        account.keep_latest = Some(1);
+        let (_, kept_by_settings) = split_kept_by_settings(
+            &account,
+            vec![post("t3_older", 5.0), post("t3_newest", 1.0)],
+        );
+        let options = RunOptions {
+            trash: true,
+            purge_after: Some(time::Duration::from_secs(0)),
+            ..Default::default()
+        };
+        let filters = Filters::load(&account).unwrap();
+        let zone = timezone::Zone::from_config(&Some("UTC".into())).unwrap();
+        let due = take_out_trash(
+            &account,
+            &options,
+            &filters,
+            Vec::new(),
+            &kept_by_settings,
+            &zone,
+        )
+        .unwrap();
+        assert_eq!(due.is_empty(), true);
+        assert_eq!(state::pending(&account.username).unwrap().is_empty(), true);
+    }
+    #[test]
+    fn test_wipe_report() {
+        let results = vec![
+            (WipeStage::Backup, String::from("saved to a.json")),
+            (WipeStage::Votes, String::from("skipped")),
+        ];
+        let report = wipe_report(&results, false);
+        assert_eq!(report.starts_with("Wipe finished:"), true);
+        assert_eq!(
+            report.contains("Back up your history               saved to a.json"),
+            true
+        );
+        assert_eq!(report.lines().count(), 3);
+        assert_eq!(wipe_report(&results, true).contains("dry run"), true);
+    }
+    #[test]
+    fn test_unsubscribe_targets() {
+        let subreddit = |name: &str| reddit_api::Subreddit {
+            name: format!("t5_{}", name),
+            display_name: name.into(),
+            subscribers: None,
+        };
+        let subscriptions = vec![
+            subreddit("AskReddit"),
+            subreddit("AskScience"),
+            subreddit("rust"),
+        ];
+        let names = |targets: Vec<reddit_api::Subreddit>| -> Vec<String> {
+            targets.into_iter().map(|s| s.display_name).collect()
+        };
+        assert_eq!(
+            names(unsubscribe_targets(subscriptions.clone(), &None, &None)).len(),
+            3
+        );
+        assert_eq!(
+            names(unsubscribe_targets(
+                subscriptions.clone(),
+                &Some(vec!["ask*".into()]),
+                &Some(vec!["askscience".into()])
+            )),
+            vec![String::from("AskReddit")]
+        );
+        assert_eq!(
+            names(unsubscribe_targets(
+                subscriptions,
+                &None,
+                &Some(vec!["rust".into()])
+            )),
+            vec![String::from("AskReddit"), String::from("AskScience")]
+        );
     }
     #[test]
     fn test_excluded_subreddits_keep() {
         let mut account = fresh_account_info();
         account.excluded_subreddits = Some(vec![SUBREDDIT.into()]);
         assert_eq!(
-            check_should_delete(&account, &test_post(0.0, 0).deletion_info()),
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_match_patterns() {
+        let mut account = fresh_account_info();
+        account.match_patterns = Some(vec!["(?i)acme corp".into(), "^my name".into()]);
+        let mut post = test_post(0.0, 0);
+        post.title = "Working at ACME Corp".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.title = "nothing to see".into();
+        post.selftext = "my name is".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.selftext = "".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_keywords() {
+        let mut account = fresh_account_info();
+        account.keywords = Some(vec!["Acme".into()]);
+        let mut post = test_post(0.0, 0);
+        post.selftext = "I work at ACME".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.selftext = "I work elsewhere".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_domains() {
+        let mut account = fresh_account_info();
+        account.kept_domains = Some(vec!["github.com".into()]);
+        account.deleted_domains = Some(vec!["imgur.com".into()]);
+        let mut post = test_post(0.0, 0);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.domain = "github.com".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        post.domain = "i.imgur.com".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.domain = "notimgur.com".into();
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_flairs() {
+        let mut account = fresh_account_info();
+        account.excluded_flairs = Some(vec!["keep".into()]);
+        let mut post = test_post(0.0, 0);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+        post.link_flair_text = Some("Keep".into());
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        account.excluded_flairs = None;
+        account.included_flairs = Some(vec!["Meta*".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            false
+        );
+        post.author_flair_text = Some("Meta mod".into());
+        assert_eq!(
+            check_should_delete(&account, &None, &post.deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_included_subreddits() {
+        let mut account = fresh_account_info();
+        account.included_subreddits = Some(vec![SUBREDDIT.to_uppercase()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            true
+        );
+        account.included_subreddits = Some(vec!["a".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_excluded_subreddits_win_over_included() {
+        let mut account = fresh_account_info();
+        account.included_subreddits = Some(vec![SUBREDDIT.into()]);
+        account.excluded_subreddits = Some(vec![SUBREDDIT.into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            false
+        );
+    }
+    #[test]
+    fn test_excluded_subreddit_patterns() {
+        let mut account = fresh_account_info();
+        account.excluded_subreddits = Some(vec!["sub*".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            false
+        );
+        account.excluded_subreddits = Some(vec!["/^a/".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            true
+        );
+    }
+    #[test]
+    fn test_broken_excluded_subreddits_protect_everything() {
+        let mut account = fresh_account_info();
+        account.excluded_subreddits = Some(vec!["a".into(), "/(broken/".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
+            false
+        );
+        assert_eq!(config::validate_account_info(&account).is_err(), true);
+        account.excluded_subreddits = None;
+        account.match_patterns = Some(vec!["(broken".into()]);
+        assert_eq!(
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
             false
         );
     }
@@ -393,7 +7265,7 @@ mod tests {
         let mut account = fresh_account_info();
         account.excluded_subreddits = Some(vec!["a".into()]);
         assert_eq!(
-            check_should_delete(&account, &test_post(0.0, 0).deletion_info()),
+            check_should_delete(&account, &None, &test_post(0.0, 0).deletion_info()),
             true
         );
     }
@@ -16,25 +16,25 @@ pub struct OAuthRedirect {
     pub state: String,
 }
 
-fn port_is_available(port: u16) -> bool {
-    match TcpListener::bind(("127.0.0.1", port)) {
+fn port_is_available(host: &str, port: u16) -> bool {
+    match TcpListener::bind((host, port)) {
         Ok(_) => true,
         Err(_) => false,
     }
 }
-fn get_available_port(start: u16, end: u16) -> Option<u16> {
-    (start..end).find(|port| port_is_available(*port))
+fn get_available_port(host: &str, start: u16, end: u16) -> Option<u16> {
+    (start..end).find(|port| port_is_available(host, *port))
 }
 
 fn parse_oauth_redirect(query_params: HashMap<String, String>) -> Option<OAuthRedirect> {
     // sample hashmap: {"error": "errors", "code": "nYMT0zn-3hh2-efn2j18BN-an8Z", "/?state": "UnK1pO8pbyEGRw0iYwUrt"}
     if query_params.contains_key("error") {
         match &*query_params["error"] {
-            "access_denied" => println!("User declined authorization of redelete."),
-            "unsupported_response_type" => println!("response_type parameter was incorrect"),
-            "invalid_scope" => println!("Scope was invalid"),
-            "invalid_request" => println!("Authorization request was invalid"),
-            s => println!("Unknown error: {}", s),
+            "access_denied" => say!("User declined authorization of redelete."),
+            "unsupported_response_type" => say!("response_type parameter was incorrect"),
+            "invalid_scope" => say!("Scope was invalid"),
+            "invalid_request" => say!("Authorization request was invalid"),
+            s => say!("Unknown error: {}", s),
         }
         None
     } else {
@@ -45,15 +45,17 @@ fn parse_oauth_redirect(query_params: HashMap<String, String>) -> Option<OAuthRe
     }
 }
 
+/// Waits on `host` for reddit to redirect the browser back with the authorization code.
 pub fn wait_for_oauth_redirect(
+    host: &str,
     start: u16,
     end: u16,
 ) -> Result<OAuthRedirect, Box<dyn std::error::Error>> {
-    let port = get_available_port(start, end).expect(&format!(
-        "Please open up port 8000 and rerun the authorization." // "Unable to find an open port in range {} to {}, please open up a port.",
-                                                                // start, end
+    let port = get_available_port(host, start, end).expect(&format!(
+        "Please open up port {} on {} and rerun the authorization.",
+        start, host
     ));
-    let server = Server::http(format!("0.0.0.0:{}", port))
+    let server = Server::http(format!("{}:{}", host, port))
         .expect("Could not start tiny_http server for oauth2 authentication.");
     let request = server
         .recv()
@@ -78,7 +80,7 @@ mod tests {
     fn test_get_available_port() {
         // make sure you've got at least one open port for this!
         let (start, end) = (0, 65535);
-        let port = get_available_port(start, end).unwrap();
+        let port = get_available_port("127.0.0.1", start, end).unwrap();
         assert_eq!(port >= start && port <= end, true);
     }
 
@@ -86,9 +88,10 @@ mod tests {
     fn test_run() {
         let (start, end) = (8000, 9000);
         let test_header_value = "TEST HEADER";
-        let port = get_available_port(start, end).unwrap();
+        let port = get_available_port("127.0.0.1", start, end).unwrap();
         // Spawning the server in a separate thread
-        let server = std::thread::spawn(move || wait_for_oauth_redirect(start, end).unwrap());
+        let server =
+            std::thread::spawn(move || wait_for_oauth_redirect("127.0.0.1", start, end).unwrap());
         let test_oauth_redirect = OAuthRedirect {
             code: "test_code".into(),
             state: "test_state".into(),
@@ -116,8 +119,8 @@ mod tests {
     #[test]
     #[should_panic]
     fn test_closed_ports() {
-        let test_port = get_available_port(8500, 9000).unwrap();
+        let test_port = get_available_port("127.0.0.1", 8500, 9000).unwrap();
         let _tcp = TcpListener::bind(("127.0.0.1", test_port)).unwrap();
-        wait_for_oauth_redirect(test_port, test_port + 1).unwrap();
+        wait_for_oauth_redirect("127.0.0.1", test_port, test_port + 1).unwrap();
     }
 }
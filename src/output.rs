@@ -0,0 +1,267 @@
+use serde_json::{Map, Value};
+use std::io::prelude::*;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use unicode_segmentation::UnicodeSegmentation;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+static LEVEL: AtomicUsize = AtomicUsize::new(Level::Normal as usize);
+/// Lines held back for the pager while `paging` is on.
+static PAGED: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// How much text output there is, picked with `-q`, `-v` and `-vv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Counts and problems, without the contents of every item.
+    Quiet,
+    Normal,
+    /// Adds request URLs and pagination cursors.
+    Verbose,
+    /// Adds the rate limiter's state.
+    Debug,
+}
+
+impl Level {
+    /// `-q` wins over any number of `-v`s.
+    pub fn from_flags(quiet: bool, verbose: u64) -> Level {
+        match (quiet, verbose) {
+            (true, _) => Level::Quiet,
+            (false, 0) => Level::Normal,
+            (false, 1) => Level::Verbose,
+            _ => Level::Debug,
+        }
+    }
+}
+
+/// How `run` and `view` report what they're doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Messages for people reading the terminal.
+    Text,
+    /// One JSON object per line for scripts, see `event`.
+    Json,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+pub fn set_format(format: Format) {
+    JSON.store(format == Format::Json, Ordering::SeqCst);
+}
+
+pub fn json() -> bool {
+    JSON.load(Ordering::SeqCst)
+}
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as usize, Ordering::SeqCst);
+}
+
+/// Whether text output at `level` is shown.
+pub fn enabled(level: Level) -> bool {
+    !json() && LEVEL.load(Ordering::SeqCst) >= level as usize
+}
+
+/// `println!` for messages meant for people, left out of JSON output. Shown even with `-q`.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::output::enabled($crate::output::Level::Quiet) {
+            $crate::output::line(format!($($arg)*));
+        }
+    };
+}
+
+/// Like `say!`, but left out with `-q`. For item contents and per-item progress.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::output::enabled($crate::output::Level::Normal) {
+            $crate::output::line(format!($($arg)*));
+        }
+    };
+}
+
+/// Only shown with `-v` or `-vv`.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::output::enabled($crate::output::Level::Verbose) {
+            $crate::output::line(format!($($arg)*));
+        }
+    };
+}
+
+/// Only shown with `-vv`.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::output::enabled($crate::output::Level::Debug) {
+            $crate::output::line(format!($($arg)*));
+        }
+    };
+}
+
+/// Colors are used when stdout is a terminal, unless turned off with `--no-color` or the
+/// `NO_COLOR` environment variable.
+pub fn set_color(no_color_flag: bool) {
+    let enabled =
+        !no_color_flag && std::env::var_os("NO_COLOR").is_none() && atty::is(atty::Stream::Stdout);
+    colored::control::set_override(enabled);
+}
+
+/// Prints a line of text output, or holds it for the pager.
+pub fn line(text: String) {
+    let mut paged = PAGED.lock().expect("Output lock poisoned.");
+    match paged.as_mut() {
+        Some(lines) => lines.push(text),
+        None => println!("{}", text),
+    }
+}
+
+/// Holds text output back until `end_paging`, when it's shown through the pager. Does
+/// nothing unless stdout is a terminal and the output is text.
+pub fn start_paging() {
+    if json() || !atty::is(atty::Stream::Stdout) {
+        return;
+    }
+    *PAGED.lock().expect("Output lock poisoned.") = Some(Vec::new());
+}
+
+/// Shows the held back output through `$PAGER`, or `less` when it isn't set. The output is
+/// printed as is if the pager can't be started.
+pub fn end_paging() {
+    let lines = match PAGED.lock().expect("Output lock poisoned.").take() {
+        Some(lines) => lines,
+        None => return,
+    };
+    let mut text = lines.join("\n");
+    text.push('\n');
+    let pager = std::env::var("PAGER").ok();
+    let shown = match pager_command(pager.as_ref().map(|p| p.as_str())) {
+        Some((program, args)) => page(&program, &args, &text).is_ok(),
+        None => false,
+    };
+    if !shown {
+        print!("{}", text);
+    }
+}
+
+/// An empty `$PAGER` or `cat` turns paging off, like git.
+fn pager_command(pager: Option<&str>) -> Option<(String, Vec<String>)> {
+    let pager = pager.unwrap_or("less").trim();
+    let mut words = pager.split_whitespace().map(String::from);
+    let program = words.next()?;
+    if program == "cat" {
+        return None;
+    }
+    Some((program, words.collect()))
+}
+
+fn page(program: &str, args: &Vec<String>, text: &str) -> std::io::Result<()> {
+    let mut command = Command::new(program);
+    command.args(args).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        // Quit if it fits on one screen, keep colors and don't clear the screen on exit.
+        command.env("LESS", "FRX");
+    }
+    let mut child = command.spawn()?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        // The pager closing early, e.g. after `q`, isn't an error.
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Cuts `text` down to `limit` user-perceived characters, ending with an ellipsis when
+/// anything was cut. Emoji and accented letters made of several code points stay whole.
+pub fn preview(text: &str, limit: Option<usize>) -> String {
+    let limit = match limit {
+        Some(limit) => limit,
+        None => return String::from(text),
+    };
+    let mut graphemes = text.graphemes(true);
+    let kept: String = graphemes.by_ref().take(limit).collect();
+    if graphemes.next().is_some() {
+        format!("{}…", kept.trim_end())
+    } else {
+        kept
+    }
+}
+
+/// Writes `{"event": <name>, ...fields}` on its own line when the output is JSON.
+pub fn event(name: &str, fields: Value) {
+    if !json() {
+        return;
+    }
+    println!("{}", event_line(name, fields));
+}
+
+fn event_line(name: &str, fields: Value) -> String {
+    let mut object = Map::new();
+    object.insert(String::from("event"), Value::from(name));
+    if let Value::Object(fields) = fields {
+        object.extend(fields);
+    }
+    Value::Object(object).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_line() {
+        let line = event_line("item", json!({"name": "t1_a", "decision": "delete"}));
+        let parsed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["event"], "item");
+        assert_eq!(parsed["name"], "t1_a");
+        assert_eq!(parsed["decision"], "delete");
+    }
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(Level::from_flags(false, 0), Level::Normal);
+        assert_eq!(Level::from_flags(false, 1), Level::Verbose);
+        assert_eq!(Level::from_flags(false, 3), Level::Debug);
+        assert_eq!(Level::from_flags(true, 2), Level::Quiet);
+        assert_eq!(Level::Quiet < Level::Normal, true);
+    }
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), Some((String::from("less"), vec![])));
+        assert_eq!(
+            pager_command(Some("less -S")),
+            Some((String::from("less"), vec![String::from("-S")]))
+        );
+        assert_eq!(pager_command(Some("")), None);
+        assert_eq!(pager_command(Some("cat")), None);
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("short", Some(10)), "short");
+        assert_eq!(preview("exactly", Some(7)), "exactly");
+        assert_eq!(preview("a longer comment", Some(9)), "a longer…");
+        assert_eq!(
+            preview("e\u{301}e\u{301}e\u{301}", Some(2)),
+            "e\u{301}e\u{301}…"
+        );
+        assert_eq!(preview("👍🏽👍🏽", Some(1)), "👍🏽…");
+        assert_eq!(preview("untouched", None), "untouched");
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(Format::parse("json"), Some(Format::Json));
+        assert_eq!(Format::parse("text"), Some(Format::Text));
+        assert_eq!(Format::parse("yaml"), None);
+    }
+}
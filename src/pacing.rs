@@ -0,0 +1,102 @@
+use rand::Rng;
+use std::future::Future;
+use std::result;
+use std::time::Duration;
+
+/// A pause between delete calls, on top of the rate limiter, so deletions are spread
+/// out instead of arriving in one burst.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pace {
+    pub delay: Duration,
+    /// Up to this much extra time, picked at random for every pause.
+    pub jitter: Duration,
+}
+
+impl Pace {
+    fn next_delay(&self) -> Duration {
+        let jitter = self.jitter.as_millis() as u64;
+        if jitter == 0 {
+            return self.delay;
+        }
+        self.delay + Duration::from_millis(rand::thread_rng().gen_range(0, jitter + 1))
+    }
+
+    /// Sleeps for one pause. Returns false if `interrupt` finished first.
+    pub async fn wait<F: Future + Unpin>(&self, interrupt: &mut F) -> bool {
+        tokio::select! {
+            _ = tokio::time::delay_for(self.next_delay()) => true,
+            _ = interrupt => false,
+        }
+    }
+}
+
+/// Parses durations like `2s`, `500ms`, `1m`, `2h` or `7d`. A bare number is taken as seconds.
+pub fn parse_duration(text: &str) -> result::Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || {
+        format!(
+            "Invalid duration {}. Use e.g. 2s, 500ms, 1m, 6h or 7d.",
+            text
+        )
+    };
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 60 * 60)),
+        "d" => Ok(Duration::from_secs(number * 24 * 60 * 60)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("3"), Ok(Duration::from_secs(3)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1m"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("7d"), Ok(Duration::from_secs(604800)));
+        assert_eq!(parse_duration("s").is_err(), true);
+        assert_eq!(parse_duration("2w").is_err(), true);
+        assert_eq!(parse_duration("-2s").is_err(), true);
+    }
+
+    #[test]
+    fn test_jitter_stays_in_range() {
+        let pace = Pace {
+            delay: Duration::from_millis(100),
+            jitter: Duration::from_millis(50),
+        };
+        for _ in 0..100 {
+            let delay = pace.next_delay();
+            assert_eq!(
+                delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150),
+                true
+            );
+        }
+    }
+
+    #[test]
+    fn test_wait_is_interruptible() {
+        let pace = Pace {
+            delay: Duration::from_secs(60),
+            jitter: Duration::from_secs(0),
+        };
+        let finished = Runtime::new().unwrap().block_on(async {
+            let mut interrupt = futures::future::ready(());
+            pace.wait(&mut interrupt).await
+        });
+        assert_eq!(finished, false);
+    }
+}
@@ -0,0 +1,172 @@
+use regex::{Regex, RegexSet};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Content,
+    Subreddit,
+}
+
+thread_local! {
+    /// Filters run once per item with the same few pattern lists, so compile each list once.
+    static CACHE: RefCell<HashMap<(Kind, Vec<String>), result::Result<RegexSet, String>>> =
+        RefCell::new(HashMap::new());
+}
+
+fn compile(kind: Kind, patterns: &Vec<String>) -> result::Result<RegexSet, String> {
+    let regexes: Vec<String> = match kind {
+        Kind::Content => patterns.clone(),
+        Kind::Subreddit => patterns.iter().map(|p| subreddit_regex(p)).collect(),
+    };
+    for (pattern, regex) in patterns.iter().zip(&regexes) {
+        if let Err(e) = Regex::new(regex) {
+            return Err(format!("Invalid pattern {}: {}", pattern, e));
+        }
+    }
+    RegexSet::new(regexes).map_err(|e| e.to_string())
+}
+
+fn cached_is_match(kind: Kind, patterns: &Vec<String>, text: &str) -> result::Result<bool, String> {
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let set = cache
+            .entry((kind, patterns.clone()))
+            .or_insert_with(|| compile(kind, patterns));
+        match set {
+            Ok(set) => Ok(set.is_match(text)),
+            Err(e) => Err(e.clone()),
+        }
+    })
+}
+
+/// True if any of the regular expressions in `patterns` matches `text`. Fails if an entry
+/// doesn't compile, so callers decide which way a broken list errs: a list of things to
+/// keep should keep everything, a list of things to delete should delete nothing.
+pub fn matches_any(patterns: &Vec<String>, text: &str) -> result::Result<bool, String> {
+    cached_is_match(Kind::Content, patterns, text)
+}
+
+/// True if `subreddit` is matched by any entry of a subreddit list. Entries are either a
+/// plain name, a glob like `Ask*`, or a regular expression between slashes like
+/// `/^r?programming$/`. Matching ignores case, like reddit does. Fails like `matches_any`.
+pub fn subreddit_matches(patterns: &Vec<String>, subreddit: &str) -> result::Result<bool, String> {
+    cached_is_match(Kind::Subreddit, patterns, subreddit)
+}
+
+/// Flair lists use the same syntax as subreddit lists, so "keep" also catches "Keep".
+pub fn flair_matches(patterns: &Vec<String>, flair: &str) -> result::Result<bool, String> {
+    cached_is_match(Kind::Subreddit, patterns, flair)
+}
+
+fn subreddit_regex(pattern: &str) -> String {
+    if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+        return format!("(?i){}", &pattern[1..pattern.len() - 1]);
+    }
+    let glob: Vec<String> = pattern
+        .split('*')
+        .map(|part| {
+            part.split('?')
+                .map(|p| regex::escape(p))
+                .collect::<Vec<String>>()
+                .join(".")
+        })
+        .collect();
+    format!("(?i)^{}$", glob.join(".*"))
+}
+
+/// Checks patterns before they're saved or used so a typo doesn't silently match nothing.
+pub fn validate_patterns<'a, I: Iterator<Item = &'a str>>(
+    patterns: I,
+) -> result::Result<(), String> {
+    for p in patterns {
+        if let Err(e) = Regex::new(p) {
+            return Err(format!("Invalid pattern {}: {}", p, e));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_subreddit_patterns<'a, I: Iterator<Item = &'a str>>(
+    patterns: I,
+) -> result::Result<(), String> {
+    for p in patterns {
+        if let Err(e) = Regex::new(&subreddit_regex(p)) {
+            return Err(format!("Invalid subreddit pattern {}: {}", p, e));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_flair_patterns<'a, I: Iterator<Item = &'a str>>(
+    patterns: I,
+) -> result::Result<(), String> {
+    for p in patterns {
+        if let Err(e) = Regex::new(&subreddit_regex(p)) {
+            return Err(format!("Invalid flair pattern {}: {}", p, e));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_patterns() {
+        assert_eq!(
+            validate_patterns(vec!["a+", "(?i)b"].into_iter()).is_ok(),
+            true
+        );
+        assert_eq!(
+            validate_patterns(vec!["a+", "(b"].into_iter()).is_err(),
+            true
+        );
+        assert_eq!(
+            validate_subreddit_patterns(vec!["Ask*", "/^r?programming$/"].into_iter()).is_ok(),
+            true
+        );
+        assert_eq!(
+            validate_subreddit_patterns(vec!["/(rust/"].into_iter()).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_subreddit_matches() {
+        let patterns: Vec<String> = vec!["Ask*".into(), "/^r?programming$/".into(), "rust".into()];
+        assert_eq!(subreddit_matches(&patterns, "AskReddit"), Ok(true));
+        assert_eq!(subreddit_matches(&patterns, "askscience"), Ok(true));
+        assert_eq!(subreddit_matches(&patterns, "rprogramming"), Ok(true));
+        assert_eq!(subreddit_matches(&patterns, "programming"), Ok(true));
+        assert_eq!(subreddit_matches(&patterns, "Rust"), Ok(true));
+        assert_eq!(subreddit_matches(&patterns, "rust_gamedev"), Ok(false));
+        assert_eq!(subreddit_matches(&patterns, "TaskRabbit"), Ok(false));
+    }
+
+    #[test]
+    fn test_invalid_entry_fails_whole_list() {
+        let patterns: Vec<String> = vec!["rust".into(), "/(broken/".into()];
+        assert_eq!(subreddit_matches(&patterns, "rust").is_err(), true);
+        assert_eq!(subreddit_matches(&patterns, "AskReddit").is_err(), true);
+        let patterns: Vec<String> = vec!["(broken".into()];
+        assert_eq!(matches_any(&patterns, "anything").is_err(), true);
+    }
+
+    #[test]
+    fn test_flair_matches() {
+        let patterns: Vec<String> = vec!["keep".into(), "/^OC/".into()];
+        assert_eq!(flair_matches(&patterns, "Keep"), Ok(true));
+        assert_eq!(flair_matches(&patterns, "OC: photo"), Ok(true));
+        assert_eq!(flair_matches(&patterns, "keeper"), Ok(false));
+    }
+
+    #[test]
+    fn test_glob_escapes_regex_characters() {
+        let patterns: Vec<String> = vec!["a.b?".into()];
+        assert_eq!(subreddit_matches(&patterns, "a.bc"), Ok(true));
+        assert_eq!(subreddit_matches(&patterns, "axbc"), Ok(false));
+    }
+}
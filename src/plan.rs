@@ -0,0 +1,93 @@
+use super::reddit_api::DeletionInfo;
+use custom_error::custom_error;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+custom_error! {pub PlanError
+    Serde{source: serde_json::Error} = "Unable to read the plan, it isn't valid plan json",
+    IO{source: std::io::Error} = "Unable to read or write the plan file",
+}
+pub type Result<T> = result::Result<T, PlanError>;
+
+/// The exact items `redelete plan` picked, written out so they can be reviewed before
+/// `redelete apply` deletes them without fetching the history again.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Plan {
+    pub username: String,
+    pub created_utc: u64,
+    pub items: Vec<DeletionInfo>,
+}
+
+impl Plan {
+    pub fn new(username: String, items: Vec<DeletionInfo>) -> Plan {
+        Plan {
+            username,
+            created_utc: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Couldn't get systemtime")
+                .as_secs(),
+            items,
+        }
+    }
+}
+
+pub fn write_plan(path: &Path, plan: &Plan) -> Result<()> {
+    let json = serde_json::to_string_pretty(plan)?;
+    File::create(path)?.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+pub fn read_plan(path: &Path) -> Result<Plan> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(name: &str, subreddit: &str) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: name.into(),
+            created_utc: 0.0,
+            subreddit: subreddit.into(),
+            score: 1,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some("body".into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan_round_trip() {
+        let mut path = std::env::temp_dir();
+        path.push("redelete-test-plan.json");
+        let items = vec![comment("t1_a", "rust")];
+        write_plan(&path, &Plan::new("PlanTestUser".into(), items)).unwrap();
+        let plan = read_plan(&path).unwrap();
+        assert_eq!(plan.username, "PlanTestUser");
+        assert_eq!(plan.items.len(), 1);
+        assert_eq!(plan.items[0].name, "t1_a");
+        assert_eq!(plan.items[0].subreddit, "rust");
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_plan(&path).is_err(), true);
+    }
+}
@@ -0,0 +1,157 @@
+use custom_error::custom_error;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::result;
+use std::time::{Duration, Instant};
+
+custom_error! {pub ArchiveApiError
+    Reqwest{source: reqwest::Error} = "Archive API request failed",
+    Status{status: u16} = "Archive API answered with status {status}",
+}
+pub type Result<T> = result::Result<T, ArchiveApiError>;
+
+/// Gap between archive requests. Pushshift style APIs allow about one a second, and they
+/// don't share reddit's quota so they get their own pacing.
+#[cfg(not(test))]
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+#[cfg(test)]
+const REQUEST_INTERVAL: Duration = Duration::from_millis(0);
+/// Wait after a 429 before asking again, and how many times to do that.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+const RETRIES: usize = 3;
+const PAGE_SIZE: usize = 100;
+
+/// Spaces requests at least `interval` apart.
+struct Throttle {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Throttle {
+    async fn wait(&mut self) {
+        if let Some(last) = self.last {
+            let elapsed = last.elapsed();
+            if elapsed < self.interval {
+                tokio::time::delay_for(self.interval - elapsed).await;
+            }
+        }
+        self.last = Some(Instant::now());
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Page {
+    data: Vec<Hit>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Hit {
+    id: String,
+    created_utc: f64,
+}
+
+/// Fullnames of every comment and then every submission the archive at `base` has for
+/// `username`, newest first. These include items reddit's listings stopped showing, and
+/// items that have since been deleted, so they need checking on reddit before use.
+pub async fn discover(base: &str, username: &str) -> Result<Vec<String>> {
+    let client = Client::new();
+    let mut throttle = Throttle {
+        interval: REQUEST_INTERVAL,
+        last: None,
+    };
+    let mut fullnames = Vec::new();
+    for (kind, prefix) in &[("comment", "t1_"), ("submission", "t3_")] {
+        let url = format!("{}/reddit/search/{}/", base.trim_end_matches('/'), kind);
+        let mut before: Option<i64> = None;
+        loop {
+            let page = search_page(&client, &mut throttle, &url, username, before).await?;
+            let full = page.data.len() >= PAGE_SIZE;
+            let oldest = page.data.iter().map(|hit| hit.created_utc as i64).min();
+            fullnames.extend(
+                page.data
+                    .into_iter()
+                    .map(|hit| format!("{}{}", prefix, hit.id)),
+            );
+            match oldest {
+                Some(oldest) if full && Some(oldest) != before => before = Some(oldest),
+                _ => break,
+            }
+        }
+    }
+    Ok(fullnames)
+}
+
+/// One page of the author's items created before `before`, retrying when rate limited.
+async fn search_page(
+    client: &Client,
+    throttle: &mut Throttle,
+    url: &str,
+    username: &str,
+    before: Option<i64>,
+) -> Result<Page> {
+    let mut params = vec![
+        ("author", String::from(username)),
+        ("size", PAGE_SIZE.to_string()),
+        ("sort", String::from("desc")),
+        ("sort_type", String::from("created_utc")),
+        ("fields", String::from("id,created_utc")),
+    ];
+    if let Some(before) = before {
+        params.push(("before", before.to_string()));
+    }
+    let mut retries = 0;
+    loop {
+        throttle.wait().await;
+        verbose!("GET {} {:?}", url, params);
+        let resp = client.get(url).query(&params).send().await?;
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS && retries < RETRIES {
+            retries += 1;
+            verbose!("Archive API is rate limiting, waiting {:?}.", RETRY_DELAY);
+            tokio::time::delay_for(RETRY_DELAY).await;
+            continue;
+        }
+        if !resp.status().is_success() {
+            return Err(ArchiveApiError::Status {
+                status: resp.status().as_u16(),
+            });
+        }
+        return Ok(resp.json::<Page>().await?);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, server_url, Matcher};
+    use serial_test::serial;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    #[serial]
+    fn test_discover() {
+        let _comments = mock("GET", "/reddit/search/comment/")
+            .match_query(Matcher::UrlEncoded("author".into(), "TestUser".into()))
+            .with_body(
+                r#"{"data": [{"id": "b", "created_utc": 200}, {"id": "a", "created_utc": 100}]}"#,
+            )
+            .create();
+        let _submissions = mock("GET", "/reddit/search/submission/")
+            .match_query(Matcher::UrlEncoded("author".into(), "TestUser".into()))
+            .with_body(r#"{"data": [{"id": "c", "created_utc": 50}]}"#)
+            .create();
+        let fullnames = Runtime::new()
+            .unwrap()
+            .block_on(async { discover(&server_url(), "TestUser").await.unwrap() });
+        assert_eq!(fullnames, vec!["t1_b", "t1_a", "t3_c"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_discover_error() {
+        let _m = mock("GET", Matcher::Any).with_status(500).create();
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { discover(&server_url(), "TestUser").await });
+        assert_eq!(result.is_err(), true);
+    }
+}
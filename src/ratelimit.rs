@@ -0,0 +1,307 @@
+use super::output;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Quota assumed until reddit reports the real one, a little under its documented 60
+/// requests a minute. It may be used in a burst since it isn't spread over the window.
+const FALLBACK_QUOTA: f64 = 55.0;
+const FALLBACK_WINDOW: Duration = Duration::from_secs(60);
+/// Gap between requests that queued up waiting for the same window reset.
+const QUEUED_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to back off after a 429 that didn't say when the window resets.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// One limiter per OAuth client for the whole process. Reddit counts the quota per client
+/// across all endpoints, so clients for different accounts running at the same time share
+/// it instead of each assuming all of it.
+static LIMITERS: Mutex<Option<HashMap<String, Arc<RateLimiter>>>> = Mutex::new(None);
+
+/// The process-wide limiter for the OAuth client `client_id`, created the first time it's
+/// asked for.
+pub fn for_client(client_id: &str) -> Arc<RateLimiter> {
+    let mut limiters = LIMITERS.lock().expect("Rate limiter lock poisoned.");
+    limiters
+        .get_or_insert_with(HashMap::new)
+        .entry(String::from(client_id))
+        .or_insert_with(|| Arc::new(RateLimiter::new()))
+        .clone()
+}
+
+/// What reddit's `X-Ratelimit-*` headers say is left of the current window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub remaining: f64,
+    /// Time until the window resets.
+    pub reset: Duration,
+}
+
+impl Quota {
+    /// `None` when the response doesn't carry rate limit headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Quota> {
+        let remaining = header(headers, "x-ratelimit-remaining")?
+            .parse::<f64>()
+            .ok()?;
+        let reset = header(headers, "x-ratelimit-reset")?.parse::<u64>().ok()?;
+        Some(Quota {
+            remaining,
+            reset: Duration::from_secs(reset),
+        })
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok().map(|v| v.trim())
+}
+
+#[derive(Debug)]
+struct State {
+    /// Requests left in the window and when it resets.
+    remaining: Option<(f64, Instant)>,
+    /// Whether `remaining` came from reddit rather than the fallback quota.
+    reported: bool,
+    /// Earliest time the next request may go out.
+    next_slot: Instant,
+    /// Reset time of the last window a countdown was shown for, so concurrent requests
+    /// waiting for the same reset only show it once.
+    announced: Option<Instant>,
+}
+
+impl State {
+    /// Picks the time the next request may be sent, spreading the remaining quota evenly
+    /// over what's left of the window. The flag is set for the first request that has to
+    /// wait for the window to reset.
+    fn reserve(&mut self, now: Instant) -> (Instant, bool) {
+        let mut announce = false;
+        let mut slot = if self.next_slot > now {
+            self.next_slot
+        } else {
+            now
+        };
+        // Until reddit reports a quota, or once its window is over and we don't know how
+        // big the new one is, fall back to the assumed quota.
+        let expired = match self.remaining {
+            Some((_, reset_at)) => reset_at <= now,
+            None => true,
+        };
+        if expired {
+            self.remaining = Some((FALLBACK_QUOTA, now + FALLBACK_WINDOW));
+            self.reported = false;
+        }
+        let (remaining, reset_at) = self
+            .remaining
+            .as_mut()
+            .expect("Rate limiter quota was just set.");
+        let interval = if *remaining < 1.0 {
+            if slot < *reset_at {
+                slot = *reset_at;
+            }
+            if self.announced != Some(*reset_at) {
+                self.announced = Some(*reset_at);
+                announce = true;
+            }
+            QUEUED_INTERVAL
+        } else if self.reported {
+            let interval = (*reset_at - now).div_f64(*remaining);
+            *remaining -= 1.0;
+            interval
+        } else {
+            *remaining -= 1.0;
+            Duration::from_secs(0)
+        };
+        self.next_slot = slot + interval;
+        (slot, announce)
+    }
+
+    fn describe(&self, now: Instant) -> String {
+        match self.remaining {
+            Some((remaining, reset_at)) => format!(
+                "{} requests left{}, window resets in {}s",
+                remaining,
+                if self.reported { "" } else { " (assumed)" },
+                reset_at.saturating_duration_since(now).as_secs()
+            ),
+            None => String::from("no quota known yet"),
+        }
+    }
+}
+
+/// Paces requests from the quota reddit reports on every response, so a change in
+/// reddit's limits or a client with a different quota is picked up automatically.
+#[derive(Debug)]
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub fn new() -> RateLimiter {
+        RateLimiter {
+            state: Mutex::new(State {
+                remaining: None,
+                reported: false,
+                next_slot: Instant::now(),
+                announced: None,
+            }),
+        }
+    }
+
+    /// Waits until the next request may be sent. When the quota is used up this sleeps
+    /// until the window resets, showing a countdown.
+    pub async fn take(&self) {
+        let now = Instant::now();
+        let (slot, announce) = {
+            let mut state = self.state.lock().expect("Rate limiter lock poisoned.");
+            let reserved = state.reserve(now);
+            debug!(
+                "Rate limiter: {}, sending in {:?}.",
+                state.describe(now),
+                reserved.0.saturating_duration_since(now)
+            );
+            reserved
+        };
+        if announce {
+            countdown(slot).await;
+        } else if slot > now {
+            tokio::time::delay_for(slot - now).await;
+        }
+    }
+
+    /// Reddit answered 429 Too Many Requests, so nothing more goes out until the window
+    /// resets, even if the response didn't say when that is.
+    pub fn exhausted(&self, headers: &HeaderMap) {
+        let reset = Quota::from_headers(headers)
+            .map(|q| q.reset)
+            .unwrap_or(DEFAULT_COOLDOWN);
+        let mut state = self.state.lock().expect("Rate limiter lock poisoned.");
+        state.remaining = Some((0.0, Instant::now() + reset));
+        state.reported = true;
+    }
+
+    pub fn update(&self, headers: &HeaderMap) {
+        if let Some(quota) = Quota::from_headers(headers) {
+            let mut state = self.state.lock().expect("Rate limiter lock poisoned.");
+            state.remaining = Some((quota.remaining, Instant::now() + quota.reset));
+            state.reported = true;
+        }
+    }
+}
+
+async fn countdown(until: Instant) {
+    if output::json() {
+        tokio::time::delay_for(until.saturating_duration_since(Instant::now())).await;
+        return;
+    }
+    loop {
+        let now = Instant::now();
+        if now >= until {
+            break;
+        }
+        let left = until - now;
+        print!(
+            "\rRate limit reached, resuming in {}s. ",
+            left.as_secs() + 1
+        );
+        std::io::stdout().flush().ok();
+        tokio::time::delay_for(left.min(Duration::from_secs(1))).await;
+    }
+    say!("\rRate limit reset, resuming.          ");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_quota_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Quota::from_headers(&headers), None);
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("598.0"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("120"));
+        headers.insert("x-ratelimit-used", HeaderValue::from_static("2"));
+        assert_eq!(
+            Quota::from_headers(&headers),
+            Some(Quota {
+                remaining: 598.0,
+                reset: Duration::from_secs(120),
+            })
+        );
+    }
+
+    #[test]
+    fn test_reserve_spreads_remaining_quota() {
+        let now = Instant::now();
+        let mut state = State {
+            remaining: Some((10.0, now + Duration::from_secs(10))),
+            reported: true,
+            next_slot: now,
+            announced: None,
+        };
+        assert_eq!(state.reserve(now), (now, false));
+        assert_eq!(state.reserve(now), (now + Duration::from_secs(1), false));
+        assert_eq!(state.remaining.unwrap().0, 8.0);
+    }
+
+    #[test]
+    fn test_reserve_waits_for_reset_when_exhausted() {
+        let now = Instant::now();
+        let reset_at = now + Duration::from_secs(30);
+        let mut state = State {
+            remaining: Some((0.0, reset_at)),
+            reported: true,
+            next_slot: now,
+            announced: None,
+        };
+        assert_eq!(state.reserve(now), (reset_at, true));
+        assert_eq!(state.reserve(now), (reset_at + QUEUED_INTERVAL, false));
+        let mut state = State {
+            remaining: Some((0.0, now)),
+            reported: true,
+            next_slot: now,
+            announced: None,
+        };
+        assert_eq!(state.reserve(now), (now, false));
+        assert_eq!(state.reported, false);
+    }
+
+    #[test]
+    fn test_for_client() {
+        assert_eq!(
+            Arc::ptr_eq(&for_client("first_client"), &for_client("first_client")),
+            true
+        );
+        assert_eq!(
+            Arc::ptr_eq(&for_client("first_client"), &for_client("second_client")),
+            false
+        );
+    }
+
+    #[test]
+    fn test_exhausted_without_headers() {
+        let limiter = RateLimiter::new();
+        limiter.exhausted(&HeaderMap::new());
+        let state = limiter.state.lock().unwrap();
+        let (remaining, reset_at) = state.remaining.unwrap();
+        assert_eq!(remaining, 0.0);
+        assert_eq!(reset_at > Instant::now() + Duration::from_secs(50), true);
+    }
+
+    #[test]
+    fn test_reserve_without_quota() {
+        let now = Instant::now();
+        let mut state = State {
+            remaining: None,
+            reported: false,
+            next_slot: now,
+            announced: None,
+        };
+        assert_eq!(state.reserve(now), (now, false));
+        assert_eq!(state.reserve(now), (now, false));
+        assert_eq!(
+            state.remaining,
+            Some((FALLBACK_QUOTA - 2.0, now + FALLBACK_WINDOW))
+        );
+    }
+}
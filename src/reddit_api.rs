@@ -1,13 +1,19 @@
 use super::config::{read_config_account_info, save_token, AccountInfo, ConfigError};
+use super::config::{reddit_app, redirect_listener, RedditApp};
 use super::oauth_server::{wait_for_oauth_redirect, OAuthRedirect};
+use super::ratelimit;
+use super::shred::Shred;
 use async_std::sync::Mutex;
 use custom_error::custom_error;
-use rate_limit::SyncLimiter;
-use reqwest::{header, Client};
-use serde::{Deserialize, Serialize};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use reqwest::{header, Client, Method};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::collections::HashSet;
+use std::pin::Pin;
 use std::result;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use webbrowser;
 
 #[cfg(test)]
@@ -41,15 +47,52 @@ fn domain() -> String {
 }
 
 const DELETE_ENDPOINT: &'static str = "/api/del";
+const EDIT_ENDPOINT: &'static str = "/api/editusertext";
+const QUARANTINE_OPTIN_ENDPOINT: &'static str = "/api/quarantine_optin";
+const INFO_ENDPOINT: &'static str = "/api/info";
+const UNSAVE_ENDPOINT: &'static str = "/api/unsave";
+const VOTE_ENDPOINT: &'static str = "/api/vote";
+const HIDE_ENDPOINT: &'static str = "/api/hide";
+const UNHIDE_ENDPOINT: &'static str = "/api/unhide";
+const SUBSCRIPTIONS_ENDPOINT: &'static str = "/subreddits/mine/subscriber";
+const SUBSCRIBE_ENDPOINT: &'static str = "/api/subscribe";
+const MULTI_ENDPOINT: &'static str = "/api/multi";
+const FRIENDS_ENDPOINT: &'static str = "/api/v1/me/friends";
+const BLOCKED_ENDPOINT: &'static str = "/prefs/blocked";
+const UNFRIEND_ENDPOINT: &'static str = "/api/unfriend";
+const DELETE_MESSAGE_ENDPOINT: &'static str = "/api/del_msg";
+const SUBMIT_ENDPOINT: &'static str = "/api/submit";
+const COMMENT_ENDPOINT: &'static str = "/api/comment";
+/// Most fullnames `/api/info` takes at once.
+const INFO_BATCH: usize = 100;
+/// Delete calls kept in flight at once unless the account sets its own limit.
+pub const DEFAULT_CONCURRENCY: usize = 8;
 const ACCESS_TOKEN_ENDPOINT: &'static str = "/api/v1/access_token";
 const ACCOUNT_INFO_ENDPOINT: &'static str = "/api/v1/me";
+const REVOKE_TOKEN_ENDPOINT: &'static str = "/api/v1/revoke_token";
 const USER_AGENT_STRING: &'static str = "redelete: v0.0.1 (by /u/ardeaf)";
 
+/// redelete's own reddit app, used unless the config has the user's.
 const CLIENT_ID: &str = "8h7fZ5mmBb8uxA";
 const RESPONSE_TYPE: &str = "code";
 const REDIRECT_URI: &str = "http://localhost:8000";
+const REDIRECT_PORT: u16 = 8000;
+/// Every interface, so redirects forwarded into a container or over ssh still arrive.
+const LISTEN_HOST: &str = "0.0.0.0";
 const DURATION: &str = "permanent";
-const SCOPE: &str = "history,edit,identity";
+const SCOPE: &str =
+    "history,edit,identity,save,vote,report,mysubreddits,subscribe,read,privatemessages,submit";
+/// What every account needs to find and delete its own comments and submissions.
+const BASE_SCOPES: [&str; 4] = ["history", "edit", "identity", "read"];
+/// Extra scopes by feature, for `redelete authorize --with-<feature>`.
+pub const FEATURE_SCOPES: [(&str, &[&str]); 6] = [
+    ("saved", &["save"]),
+    ("votes", &["vote"]),
+    ("hidden", &["report"]),
+    ("subscriptions", &["mysubreddits", "subscribe"]),
+    ("messages", &["privatemessages"]),
+    ("posting", &["submit"]),
+];
 
 custom_error! {pub RedditApiError
     OAuthValidationError{text: String} = "Unable to authorize using oauth: {text}",
@@ -60,7 +103,51 @@ custom_error! {pub RedditApiError
     InvalidHeaderValue{source: reqwest::header::InvalidHeaderValue} = "Error creating headermap.",
     SystemTimeError{source: std::time::SystemTimeError} = "Error reading system time.",
     RefreshTokenError = "Unable to refresh oauth2 token",
-    ParseCommentError = "Unable to parse comments from json response."
+    ReauthorizeNeeded{username: String} = "reddit no longer accepts the saved token for {username}. Run `redelete authorize --reauthorize {username}` to authorize it again",
+    WrongAccount{expected: String, authorized: String} = "Authorized {authorized} instead of {expected}. Log into reddit as {expected} and try again",
+    ParseCommentError = "Unable to parse comments from json response.",
+    Forbidden{reason: String} = "Reddit refused access ({reason})",
+    EditRejected{reason: String} = "Reddit refused the edit ({reason})",
+    PostRejected{reason: String} = "Reddit refused the post ({reason})",
+    BudgetExhausted = "Request budget for this run is used up",
+    TimedOut = "Time limit for this run is up"
+}
+
+impl RedditApiError {
+    /// The run's request budget or time limit ran out. What's left is saved for
+    /// `redelete retry` instead of counting as failed.
+    pub fn stops_run(&self) -> bool {
+        match self {
+            RedditApiError::BudgetExhausted | RedditApiError::TimedOut => true,
+            _ => false,
+        }
+    }
+}
+
+/// Feeds the response's quota to the client's rate limiter. True when reddit refused the
+/// request with 429 Too Many Requests, so it should be sent again once the window resets.
+fn rate_limited(limiter: &ratelimit::RateLimiter, response: &reqwest::Response) -> bool {
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        limiter.exhausted(response.headers());
+        true
+    } else {
+        limiter.update(response.headers());
+        false
+    }
+}
+
+/// reddit answers requests for quarantined, banned or private subreddits with a 403 whose
+/// json body has a `reason` like "quarantined" or "banned".
+async fn check_forbidden(response: reqwest::Response) -> Result<reqwest::Response> {
+    if response.status() != reqwest::StatusCode::FORBIDDEN {
+        return Ok(response);
+    }
+    let body: Value = serde_json::from_str(&response.text().await?).unwrap_or(Value::Null);
+    let reason = match body["reason"].as_str() {
+        Some(reason) => String::from(reason).to_lowercase(),
+        None => String::from("forbidden"),
+    };
+    Err(RedditApiError::Forbidden { reason })
 }
 
 pub type Result<T> = result::Result<T, RedditApiError>;
@@ -71,18 +158,73 @@ pub struct OAuthToken {
     pub expires_in: u64,
     pub scope: String,
     pub refresh_token: Option<String>,
+    /// The reddit app that issued the token, which is the only one that can refresh or
+    /// revoke it. Tokens saved before this was recorded don't have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+}
+
+impl OAuthToken {
+    /// The scopes reddit granted. Reddit separates them with spaces, older configs have commas.
+    pub fn scopes(&self) -> Vec<String> {
+        self.scope
+            .split(|c| c == ' ' || c == ',')
+            .filter(|scope| !scope.is_empty())
+            .map(String::from)
+            .collect()
+    }
+}
+
+/// Every scope `redelete authorize` asks for.
+pub fn requested_scopes() -> Vec<&'static str> {
+    SCOPE.split(',').collect()
+}
+
+/// The scopes to ask for when only `features` from `FEATURE_SCOPES` are wanted. No
+/// features asks for everything.
+pub fn scopes_for(features: &[&str]) -> Vec<&'static str> {
+    if features.is_empty() {
+        return requested_scopes();
+    }
+    let mut scopes = BASE_SCOPES.to_vec();
+    for (feature, extra) in FEATURE_SCOPES.iter() {
+        if features.contains(feature) {
+            scopes.extend(extra.iter());
+        }
+    }
+    scopes
 }
 
 #[derive(serde::Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
 pub struct Expiration {
     pub expires_at: u32,
 }
+/// Sort/time window combinations fetched when backfilling. Each listing is capped at
+/// roughly 1000 items by reddit, but the caps apply per listing so their union reaches further.
+const BACKFILL_LISTINGS: &'static [(&'static str, &'static str)] = &[
+    ("new", "all"),
+    ("hot", "all"),
+    ("top", "hour"),
+    ("top", "day"),
+    ("top", "week"),
+    ("top", "month"),
+    ("top", "year"),
+    ("top", "all"),
+    ("controversial", "hour"),
+    ("controversial", "day"),
+    ("controversial", "week"),
+    ("controversial", "month"),
+    ("controversial", "year"),
+    ("controversial", "all"),
+];
+
 #[derive(Serialize, Deserialize, Debug)]
 struct RedditParams {
     after: Option<String>,
     before: Option<String>,
     limit: u64,
     show: String, // can be "all"
+    sort: String,
     t: String,
 }
 impl RedditParams {
@@ -91,6 +233,7 @@ impl RedditParams {
         // ("before", &self.before.ok_or(""),
         let mut vec = vec![
             ("limit", self.limit.to_string()),
+            ("sort", String::from(&self.sort)),
             ("t", String::from(&self.t)),
         ];
         match &self.after {
@@ -104,7 +247,7 @@ impl RedditParams {
         vec
     }
 }
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DeletionInfo {
     pub saved: bool,
     pub name: String,
@@ -115,6 +258,66 @@ pub struct DeletionInfo {
     pub url: Option<String>,
     pub title: Option<String>,
     pub body: Option<String>,
+    pub distinguished: Option<String>,
+    pub stickied: bool,
+    /// Domain a link submission points at. `None` for comments and self posts.
+    pub domain: Option<String>,
+    pub link_flair_text: Option<String>,
+    pub author_flair_text: Option<String>,
+    pub edited: bool,
+    /// Comments reddit marked as controversial. Always false for submissions.
+    pub controversial: bool,
+    /// reddit archives items after six months. They can still be deleted but not edited.
+    pub archived: bool,
+    pub quarantined: bool,
+    /// "self", "link", "image", "video" or "gallery". `None` for comments.
+    pub post_type: Option<String>,
+    /// Path on reddit.com, like "/r/rust/comments/abc123/title/def456/".
+    #[serde(default)]
+    pub permalink: String,
+}
+
+impl DeletionInfo {
+    pub fn kind(&self) -> &'static str {
+        if self.body.is_some() {
+            "comment"
+        } else {
+            "submission"
+        }
+    }
+
+    /// A comment's body or a submission's title, with line breaks and runs of spaces
+    /// squeezed so it fits on one line.
+    pub fn summary(&self) -> String {
+        self.body
+            .as_ref()
+            .or(self.title.as_ref())
+            .map_or("", |text| text.as_str())
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// How long before `now` this was posted, e.g. "3 years ago".
+    pub fn age(&self, now: f64) -> String {
+        humanize_age((now - self.created_utc).max(0.0) as u64)
+    }
+}
+
+fn humanize_age(seconds: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    let (count, unit) = match seconds {
+        s if s < MINUTE => return String::from("just now"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < 30 * DAY => (s / DAY, "day"),
+        s if s < 365 * DAY => (s / (30 * DAY), "month"),
+        s => (s / (365 * DAY), "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
 }
 
 pub trait RedditPost {
@@ -132,6 +335,36 @@ impl RedditPost for Post {
             url: Some(String::from(&self.url)),
             title: Some(String::from(&self.title)),
             body: None,
+            distinguished: self.distinguished.clone(),
+            stickied: self.stickied,
+            domain: if self.domain.is_empty() || self.domain.starts_with("self.") {
+                None
+            } else {
+                Some(self.domain.to_lowercase())
+            },
+            link_flair_text: self.link_flair_text.clone(),
+            author_flair_text: self.author_flair_text.clone(),
+            edited: self.edited,
+            controversial: false,
+            archived: self.archived,
+            quarantined: self.quarantine,
+            post_type: Some(String::from(self.post_type())),
+            permalink: String::from(&self.permalink),
+        }
+    }
+}
+impl Post {
+    fn post_type(&self) -> &'static str {
+        if self.is_self {
+            return "self";
+        }
+        if self.is_gallery {
+            return "gallery";
+        }
+        match self.post_hint.as_ref().map(|h| h.as_str()) {
+            Some("image") => "image",
+            Some("hosted:video") | Some("rich:video") => "video",
+            _ => "link",
         }
     }
 }
@@ -147,9 +380,64 @@ impl RedditPost for Comment {
             url: None,
             title: None,
             body: Some(String::from(&self.body)),
+            distinguished: self.distinguished.clone(),
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: self.author_flair_text.clone(),
+            edited: self.edited,
+            controversial: self.controversiality > 0,
+            archived: self.archived,
+            quarantined: self.quarantine,
+            post_type: None,
+            permalink: String::from(&self.permalink),
+        }
+    }
+}
+/// A comment or a submission from a listing that mixes them, like the overview. Told apart
+/// by the kind prefix of the fullname.
+#[derive(Debug)]
+pub enum Thing {
+    Comment(Comment),
+    Post(Post),
+}
+impl<'de> Deserialize<'de> for Thing {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Thing, D::Error> {
+        let data = Value::deserialize(deserializer)?;
+        let kind = data["name"].as_str().and_then(|name| name.get(..3));
+        match kind {
+            Some("t1_") => Ok(Thing::Comment(
+                serde_json::from_value(data).map_err(serde::de::Error::custom)?,
+            )),
+            Some("t3_") => Ok(Thing::Post(
+                serde_json::from_value(data).map_err(serde::de::Error::custom)?,
+            )),
+            _ => Err(serde::de::Error::custom(format!(
+                "expected a comment or submission, got {}",
+                data["name"]
+            ))),
+        }
+    }
+}
+impl RedditPost for Thing {
+    fn deletion_info(&self) -> DeletionInfo {
+        match self {
+            Thing::Comment(comment) => comment.deletion_info(),
+            Thing::Post(post) => post.deletion_info(),
         }
     }
 }
+/// reddit sends `false` for items that were never edited and the time of the last edit otherwise.
+fn deserialize_edited<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> result::Result<bool, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Bool(edited) => edited,
+        Value::Number(_) => true,
+        _ => false,
+    })
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Post {
     pub saved: bool,
@@ -160,6 +448,31 @@ pub struct Post {
     pub selftext: String,
     pub url: String,
     pub title: String,
+    /// "moderator" or "admin" when the post was distinguished, null otherwise.
+    pub distinguished: Option<String>,
+    /// Pinned to the top of the subreddit by a moderator.
+    #[serde(default)]
+    pub stickied: bool,
+    /// e.g. "github.com", or "self.rust" for self posts.
+    #[serde(default)]
+    pub domain: String,
+    pub link_flair_text: Option<String>,
+    pub author_flair_text: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_edited")]
+    pub edited: bool,
+    #[serde(default)]
+    pub archived: bool,
+    /// The item is in a quarantined subreddit.
+    #[serde(default)]
+    pub quarantine: bool,
+    #[serde(default)]
+    pub is_self: bool,
+    #[serde(default)]
+    pub is_gallery: bool,
+    /// reddit's guess at the content, e.g. "image", "hosted:video" or "link".
+    pub post_hint: Option<String>,
+    #[serde(default)]
+    pub permalink: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -170,134 +483,728 @@ pub struct Comment {
     pub subreddit: String,
     pub score: i32,
     pub body: String,
+    /// "moderator" or "admin" when the comment was distinguished, null otherwise.
+    pub distinguished: Option<String>,
+    pub author_flair_text: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_edited")]
+    pub edited: bool,
+    /// 1 when the comment has a lot of both up and down votes, 0 otherwise.
+    #[serde(default)]
+    pub controversiality: u8,
+    #[serde(default)]
+    pub archived: bool,
+    /// The item is in a quarantined subreddit.
+    #[serde(default)]
+    pub quarantine: bool,
+    #[serde(default)]
+    pub permalink: String,
+}
+
+/// A subreddit the account is subscribed to.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Subreddit {
+    /// Fullname, like t5_2qh1i.
+    pub name: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub subscribers: Option<u64>,
+}
+
+/// One of the account's multireddits.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Multireddit {
+    pub name: String,
+    pub display_name: String,
+    /// Like /user/name/m/multi, which identifies it to the API.
+    pub path: String,
+    #[serde(default)]
+    pub subreddits: Vec<MultiSubreddit>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MultiSubreddit {
+    pub name: String,
+}
+
+/// Someone on the account's friends or blocked list.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserListEntry {
+    pub name: String,
+    /// When they were added, in epoch seconds.
+    #[serde(default)]
+    pub date: Option<f64>,
+}
+
+/// A private message in the account's inbox or sent folder.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub dest: Option<String>,
+    #[serde(default)]
+    pub subject: String,
+    pub created_utc: f64,
 }
 
 pub struct RedditClient {
     client: Client,
     pub username: String,
+    /// Fetch every sort in `BACKFILL_LISTINGS` instead of just `new`.
+    pub backfill: bool,
+    /// Fetch comments and submissions together from the overview listing, in half the
+    /// requests. The overview's 1000 item cap covers both, so it reaches less far back.
+    pub overview: bool,
+    /// Opt in to quarantined subreddits when reddit refuses a deletion because of quarantine.
+    pub include_quarantined: bool,
+    /// How many delete calls `delete_many` keeps in flight at once.
+    pub concurrency: usize,
+    /// Most API calls this client may make. Further calls fail with `BudgetExhausted`.
+    pub max_requests: Option<usize>,
+    /// Calls after this fail with `TimedOut`.
+    pub deadline: Option<Instant>,
+    requests: AtomicUsize,
     account_info_mutex: Mutex<()>,
-    ratelimiter: SyncLimiter,
 }
 impl RedditClient {
     pub fn new(username: String) -> RedditClient {
         RedditClient {
             client: make_client().expect("Unable to create reqwest client."),
             username,
+            backfill: false,
+            overview: false,
+            include_quarantined: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            max_requests: None,
+            deadline: None,
+            requests: AtomicUsize::new(0),
             account_info_mutex: Mutex::new(()),
-            ratelimiter: SyncLimiter::full(55, Duration::from_secs(60)),
         }
     }
     async fn post(&self, endpoint: &str, params: &Vec<(&str, &str)>) -> Result<String> {
+        self.send(Method::POST, endpoint, params).await
+    }
+    /// Sends a form to `endpoint` with `method`, for calls that change something.
+    async fn send(
+        &self,
+        method: Method,
+        endpoint: &str,
+        params: &Vec<(&str, &str)>,
+    ) -> Result<String> {
         let ai = self.check_account_info().await?;
-        self.ratelimiter.take();
-        let response = self
-            .client
-            .post(&format!("{}{}", domain(), endpoint))
-            .bearer_auth(ai.token.access_token)
-            .form(params)
-            .send()
-            .await?;
+        let limiter = ratelimit::for_client(&app_for(&ai.token).client_id);
+        let response = loop {
+            self.spend_request()?;
+            limiter.take().await;
+            self.check_deadline()?;
+            verbose!("{} {}", method, endpoint);
+            let response = self
+                .client
+                .request(method.clone(), &format!("{}{}", domain(), endpoint))
+                .bearer_auth(&ai.token.access_token)
+                .form(params)
+                .send()
+                .await?;
+            if !rate_limited(&limiter, &response) {
+                break response;
+            }
+        };
+        let response = check_forbidden(response).await?.error_for_status()?;
         let response_text = response.text().await?;
         Ok(response_text)
     }
+    /// API calls made so far.
+    pub fn requests_made(self: &Self) -> usize {
+        let made = self.requests.load(Ordering::SeqCst);
+        match self.max_requests {
+            Some(max) => made.min(max),
+            None => made,
+        }
+    }
+    /// Counts a request against `max_requests`, failing once the budget is used up.
+    fn spend_request(self: &Self) -> Result<()> {
+        self.check_deadline()?;
+        let made = self.requests.fetch_add(1, Ordering::SeqCst);
+        match self.max_requests {
+            Some(max) if made >= max => Err(RedditApiError::BudgetExhausted),
+            _ => Ok(()),
+        }
+    }
+    /// Also checked after waiting for the rate limiter, which can take a minute.
+    fn check_deadline(self: &Self) -> Result<()> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(RedditApiError::TimedOut),
+            _ => Ok(()),
+        }
+    }
     async fn fetch(self: &Self, endpoint: &str, params: &Vec<(&str, String)>) -> Result<String> {
         let ai = self.check_account_info().await?;
-        self.ratelimiter.take();
-        let a = self
-            .client
-            .get(&format!("{}{}", domain(), endpoint))
-            .bearer_auth(ai.token.access_token)
-            .query(params);
-        let resp = a.send().await?;
+        let limiter = ratelimit::for_client(&app_for(&ai.token).client_id);
+        let resp = loop {
+            self.spend_request()?;
+            limiter.take().await;
+            self.check_deadline()?;
+            verbose!("GET {} {:?}", endpoint, params);
+            let a = self
+                .client
+                .get(&format!("{}{}", domain(), endpoint))
+                .bearer_auth(&ai.token.access_token)
+                .query(params);
+            let resp = a.send().await?;
+            if !rate_limited(&limiter, &resp) {
+                break resp;
+            }
+        };
+        let resp = check_forbidden(resp).await?;
         let response_text = resp.text().await?;
         Ok(response_text)
     }
-    async fn gather_all<T: serde::de::DeserializeOwned>(
+    /// One page of a listing and the `after` cursor for the next one, if there is one.
+    async fn listing_page<T: serde::de::DeserializeOwned>(
         self: &Self,
         endpoint: &str,
-    ) -> Result<Vec<T>> {
-        let limit: u64 = 100;
-        let show = "all";
-        let mut after: Option<String> = None;
-        let mut total: Vec<T> = Vec::new();
-        loop {
-            let params = RedditParams {
-                limit,
-                show: String::from(show),
-                after: after.clone(),
-                before: None,
-                t: String::from("all"),
-            };
-            let text = self.fetch(&endpoint, &params.as_vec()).await?;
-            let mut json: Value = serde_json::from_str(&*text)?;
-            let raw_posts: Vec<Value> = json["data"]["children"]
-                .take()
-                .as_array()
-                .ok_or(RedditApiError::ParseCommentError)?
-                .to_owned();
-            after = match json["data"]["after"].take() {
-                Value::String(s) => Some(s),
-                Value::Null => None,
-                _ => None,
-            };
-            for mut p in raw_posts.into_iter() {
-                let data: Value = p["data"].take();
-                let post: T = serde_json::from_value(data)?;
-                total.push(post);
+        sort: &str,
+        t: &str,
+        after: Option<String>,
+    ) -> Result<(Vec<T>, Option<String>)> {
+        let params = RedditParams {
+            limit: 100,
+            show: String::from("all"),
+            after,
+            before: None,
+            sort: String::from(sort),
+            t: String::from(t),
+        };
+        let text = self.fetch(&endpoint, &params.as_vec()).await?;
+        let mut json: Value = serde_json::from_str(&*text)?;
+        let raw_posts: Vec<Value> = json["data"]["children"]
+            .take()
+            .as_array()
+            .ok_or(RedditApiError::ParseCommentError)?
+            .to_owned();
+        let after = match json["data"]["after"].take() {
+            Value::String(s) => Some(s),
+            Value::Null => None,
+            _ => None,
+        };
+        verbose!(
+            "{} {}: {} items, next page after {:?}",
+            endpoint,
+            sort,
+            raw_posts.len(),
+            after
+        );
+        let mut page: Vec<T> = Vec::new();
+        for mut p in raw_posts.into_iter() {
+            let data: Value = p["data"].take();
+            let post: T = serde_json::from_value(data)?;
+            page.push(post);
+        }
+        Ok((page, after))
+    }
+    /// A listing as a stream of pages, each fetched only when the previous one has been used.
+    /// The stream ends after the last page or the first error.
+    fn listing_pages<'a, T: serde::de::DeserializeOwned + 'a>(
+        self: &'a Self,
+        endpoint: String,
+        sort: &'a str,
+        t: &'a str,
+    ) -> impl Stream<Item = Result<Vec<T>>> + 'a {
+        // `None` once there are no more pages, otherwise the cursor for the next one.
+        let start: Option<Option<String>> = Some(None);
+        stream::unfold(start, move |cursor| {
+            let endpoint = endpoint.clone();
+            async move {
+                let after = match cursor {
+                    Some(after) => after,
+                    None => return None,
+                };
+                match self.listing_page::<T>(&endpoint, sort, t, after).await {
+                    Ok((page, next)) => Some((Ok(page), next.map(Some))),
+                    Err(e) => Some((Err(e), None)),
+                }
             }
-            match after {
-                Some(_) => (),
-                None => break,
+        })
+    }
+    async fn gather_listing<T: serde::de::DeserializeOwned>(
+        self: &Self,
+        endpoint: &str,
+        sort: &str,
+        t: &str,
+    ) -> Result<Vec<T>> {
+        self.listing_pages::<T>(String::from(endpoint), sort, t)
+            .try_concat()
+            .await
+    }
+    /// Sort/time window combinations to fetch: only `new`, or when backfilling, every one in
+    /// `BACKFILL_LISTINGS`.
+    fn listings(self: &Self) -> &'static [(&'static str, &'static str)] {
+        if self.backfill {
+            BACKFILL_LISTINGS
+        } else {
+            &BACKFILL_LISTINGS[..1]
+        }
+    }
+    /// Every item in one listing of `endpoint`, sorted by `sort` over the time window `t`.
+    async fn gather_all<T: serde::de::DeserializeOwned + RedditPost>(
+        self: &Self,
+        endpoint: &str,
+        sort: &str,
+        t: &str,
+    ) -> Result<Vec<DeletionInfo>> {
+        Ok(self
+            .gather_listing::<T>(endpoint, sort, t)
+            .await?
+            .into_iter()
+            .map(|p| p.deletion_info())
+            .collect())
+    }
+    /// Fetches each of `listings()` for `endpoint` and merges them into one history.
+    async fn gather_listings<T: serde::de::DeserializeOwned + RedditPost>(
+        self: &Self,
+        endpoint: &str,
+    ) -> Result<Vec<DeletionInfo>> {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut total: Vec<DeletionInfo> = Vec::new();
+        for (sort, t) in self.listings() {
+            let listing = self.gather_all::<T>(endpoint, sort, t).await?;
+            let added = merge_listing(&mut total, &mut seen, listing);
+            if self.backfill && added > 0 {
+                say!("Found {} more items sorting by {} ({}).", added, sort, t);
             }
         }
         Ok(total)
     }
     pub async fn comments<'de>(self: &Self) -> Result<Vec<DeletionInfo>> {
         let endpoint = format!("/user/{}/comments", self.username);
-        let comments = self.gather_all::<Comment>(&endpoint).await?;
-        let di = comments.into_iter().map(|c| c.deletion_info()).collect();
-        Ok(di)
+        self.gather_listings::<Comment>(&endpoint).await
     }
     pub async fn posts<'de>(self: &Self) -> Result<Vec<DeletionInfo>> {
         let endpoint = format!("/user/{}/submitted", self.username);
-        let posts = self.gather_all::<Post>(&endpoint).await?;
-        let di = posts.into_iter().map(|c| c.deletion_info()).collect();
-        Ok(di)
+        self.gather_listings::<Post>(&endpoint).await
+    }
+    /// Comments and submissions interleaved, newest first, from one listing.
+    pub async fn overview(self: &Self) -> Result<Vec<DeletionInfo>> {
+        let endpoint = format!("/user/{}/overview", self.username);
+        self.gather_listings::<Thing>(&endpoint).await
+    }
+    /// Comments and submissions the account has saved, by anyone, most recently saved first.
+    pub async fn saved(self: &Self) -> Result<Vec<DeletionInfo>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        let endpoint = format!("/user/{}/saved", self.username);
+        self.gather_all::<Thing>(&endpoint, sort, t).await
+    }
+    /// Comments and submissions the account has upvoted, most recent vote first.
+    pub async fn upvoted(self: &Self) -> Result<Vec<DeletionInfo>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        let endpoint = format!("/user/{}/upvoted", self.username);
+        self.gather_all::<Thing>(&endpoint, sort, t).await
+    }
+    /// Comments and submissions the account has downvoted, most recent vote first.
+    pub async fn downvoted(self: &Self) -> Result<Vec<DeletionInfo>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        let endpoint = format!("/user/{}/downvoted", self.username);
+        self.gather_all::<Thing>(&endpoint, sort, t).await
+    }
+    /// Submissions the account has hidden, most recently hidden first.
+    pub async fn hidden(self: &Self) -> Result<Vec<DeletionInfo>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        let endpoint = format!("/user/{}/hidden", self.username);
+        self.gather_all::<Post>(&endpoint, sort, t).await
+    }
+    /// Every subreddit the account is subscribed to.
+    pub async fn subscriptions(self: &Self) -> Result<Vec<Subreddit>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        self.gather_listing::<Subreddit>(SUBSCRIPTIONS_ENDPOINT, sort, t)
+            .await
+    }
+    /// The multireddits the account has made.
+    pub async fn multis(self: &Self) -> Result<Vec<Multireddit>> {
+        let text = self
+            .fetch(&format!("{}/mine", MULTI_ENDPOINT), &Vec::new())
+            .await?;
+        let json: Vec<Value> = serde_json::from_str(&*text)?;
+        let mut multis = Vec::new();
+        for mut multi in json {
+            multis.push(serde_json::from_value(multi["data"].take())?);
+        }
+        Ok(multis)
+    }
+    /// Everyone on the account's friends list.
+    pub async fn friends(self: &Self) -> Result<Vec<UserListEntry>> {
+        self.user_list(FRIENDS_ENDPOINT).await
+    }
+    /// Everyone the account has blocked.
+    pub async fn blocked(self: &Self) -> Result<Vec<UserListEntry>> {
+        self.user_list(BLOCKED_ENDPOINT).await
+    }
+    async fn user_list(self: &Self, endpoint: &str) -> Result<Vec<UserListEntry>> {
+        let text = self.fetch(endpoint, &Vec::new()).await?;
+        let mut json: Value = serde_json::from_str(&*text)?;
+        Ok(serde_json::from_value(json["data"]["children"].take())?)
+    }
+    /// The name reddit has for the account the token belongs to. Checks the token works.
+    pub async fn verified_username(self: &Self) -> Result<String> {
+        let text = self.fetch(ACCOUNT_INFO_ENDPOINT, &Vec::new()).await?;
+        let user: User = serde_json::from_str(&text)?;
+        Ok(user.name)
+    }
+    /// The account's own fullname, like t2_abc.
+    pub async fn own_fullname(self: &Self) -> Result<String> {
+        let text = self.fetch(ACCOUNT_INFO_ENDPOINT, &Vec::new()).await?;
+        let json: Value = serde_json::from_str(&*text)?;
+        json["id"]
+            .as_str()
+            .map(|id| format!("t2_{}", id))
+            .ok_or(RedditApiError::ParseCommentError)
+    }
+    /// Private messages in the inbox and then the sent folder. Comment replies and mentions
+    /// are left out, they aren't the account's to delete.
+    pub async fn messages(self: &Self) -> Result<Vec<Message>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        let (mut messages, sent) = futures::try_join!(
+            self.gather_listing::<Message>("/message/messages", sort, t),
+            self.gather_listing::<Message>("/message/sent", sort, t)
+        )?;
+        messages.extend(sent);
+        Ok(messages)
+    }
+    /// The whole history: the overview when `overview` is set, otherwise every comment and
+    /// then every submission.
+    pub async fn history(self: &Self) -> Result<Vec<DeletionInfo>> {
+        if self.overview {
+            return self.overview().await;
+        }
+        let (mut history, posts) = futures::try_join!(self.comments(), self.posts())?;
+        history.extend(posts);
+        Ok(history)
+    }
+    /// Every comment and then every submission in the `new` listings, with all the fields
+    /// reddit returns for them, for backups.
+    pub async fn raw_history(self: &Self) -> Result<Vec<serde_json::Map<String, Value>>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        let comments_endpoint = format!("/user/{}/comments", self.username);
+        let posts_endpoint = format!("/user/{}/submitted", self.username);
+        let (mut history, posts) = futures::try_join!(
+            self.gather_listing::<serde_json::Map<String, Value>>(&comments_endpoint, sort, t),
+            self.gather_listing::<serde_json::Map<String, Value>>(&posts_endpoint, sort, t)
+        )?;
+        history.extend(posts);
+        Ok(history)
+    }
+    /// The `new` listings of comments and then submissions, or the overview when `overview`
+    /// is set, a page at a time, so items can be handled while the rest of the history is
+    /// still downloading.
+    pub fn history_pages<'a>(
+        self: &'a Self,
+    ) -> Pin<Box<dyn Stream<Item = Result<Vec<DeletionInfo>>> + 'a>> {
+        let (sort, t) = BACKFILL_LISTINGS[0];
+        if self.overview {
+            return Box::pin(
+                self.listing_pages::<Thing>(format!("/user/{}/overview", self.username), sort, t)
+                    .map_ok(|page| {
+                        page.into_iter()
+                            .map(|thing| thing.deletion_info())
+                            .collect::<Vec<DeletionInfo>>()
+                    }),
+            );
+        }
+        let comments = self
+            .listing_pages::<Comment>(format!("/user/{}/comments", self.username), sort, t)
+            .map_ok(|page| {
+                page.into_iter()
+                    .map(|c| c.deletion_info())
+                    .collect::<Vec<DeletionInfo>>()
+            });
+        let posts = self
+            .listing_pages::<Post>(format!("/user/{}/submitted", self.username), sort, t)
+            .map_ok(|page| {
+                page.into_iter()
+                    .map(|p| p.deletion_info())
+                    .collect::<Vec<DeletionInfo>>()
+            });
+        Box::pin(comments.chain(posts))
+    }
+    /// Only this user's comments in a single thread, read from the thread's comment tree.
+    /// Comments hidden behind "load more comments" links are not fetched.
+    pub async fn thread_comments(self: &Self, article: &str) -> Result<Vec<DeletionInfo>> {
+        let endpoint = format!("/comments/{}", article);
+        let params = vec![
+            ("limit", String::from("500")),
+            ("sort", String::from("new")),
+        ];
+        let text = self.fetch(&endpoint, &params).await?;
+        let json: Value = serde_json::from_str(&*text)?;
+        let mut comments: Vec<Comment> = Vec::new();
+        collect_own_comments(&json[1]["data"]["children"], &self.username, &mut comments)?;
+        Ok(comments.into_iter().map(|c| c.deletion_info()).collect())
+    }
+
+    /// Looks comments and submissions up by fullname, for items the listings don't reach.
+    /// Ones that were already deleted, that reddit doesn't know, or that someone else wrote
+    /// are left out, since the fullnames can come from archives and exports.
+    pub async fn info(self: &Self, fullnames: &[String]) -> Result<Vec<DeletionInfo>> {
+        let mut found: Vec<DeletionInfo> = Vec::new();
+        for batch in fullnames.chunks(INFO_BATCH) {
+            let params = vec![("id", batch.join(","))];
+            let text = self.fetch(INFO_ENDPOINT, &params).await?;
+            let json: Value = serde_json::from_str(&*text)?;
+            let children = json["data"]["children"]
+                .as_array()
+                .ok_or(RedditApiError::ParseCommentError)?;
+            for child in children {
+                let data = &child["data"];
+                let author = data["author"].as_str().unwrap_or_default();
+                if !author.eq_ignore_ascii_case(&self.username) {
+                    continue;
+                }
+                let info = match child["kind"].as_str() {
+                    Some("t1") => serde_json::from_value::<Comment>(data.clone())?.deletion_info(),
+                    Some("t3") => serde_json::from_value::<Post>(data.clone())?.deletion_info(),
+                    _ => continue,
+                };
+                found.push(info);
+            }
+        }
+        Ok(found)
     }
 
     pub async fn delete(self: &Self, fullname: String) -> Result<()> {
         let params = vec![("id", &*fullname)];
         let _resp = self.post(DELETE_ENDPOINT, &params).await?;
-        println!("Deleted!");
+        info!("Deleted!");
+        Ok(())
+    }
+
+    /// Deletes `fullname`, opting in to its subreddit and trying again if reddit refused
+    /// because the subreddit is quarantined and `include_quarantined` is set.
+    pub async fn delete_from(self: &Self, fullname: String, subreddit: &str) -> Result<()> {
+        match self.delete(fullname.clone()).await {
+            Err(RedditApiError::Forbidden { reason })
+                if reason == "quarantined" && self.include_quarantined =>
+            {
+                say!("Opting in to quarantined subreddit /r/{}.", subreddit);
+                self.quarantine_optin(subreddit).await?;
+                self.delete(fullname).await
+            }
+            result => result,
+        }
+    }
+
+    /// Replaces the text of a comment or self post.
+    pub async fn edit(self: &Self, fullname: &str, text: &str) -> Result<()> {
+        let params = vec![("api_type", "json"), ("thing_id", fullname), ("text", text)];
+        let text = self.post(EDIT_ENDPOINT, &params).await?;
+        let json: Value = serde_json::from_str(&*text)?;
+        match rejection(&json) {
+            Some(reason) => Err(RedditApiError::EditRejected { reason }),
+            None => Ok(()),
+        }
+    }
+
+    /// Submits a self post to `subreddit` and returns its fullname.
+    pub async fn submit_self(
+        self: &Self,
+        subreddit: &str,
+        title: &str,
+        text: &str,
+    ) -> Result<String> {
+        let params = vec![
+            ("api_type", "json"),
+            ("kind", "self"),
+            ("sr", subreddit),
+            ("title", title),
+            ("text", text),
+        ];
+        let json: Value = serde_json::from_str(&self.post(SUBMIT_ENDPOINT, &params).await?)?;
+        if let Some(reason) = rejection(&json) {
+            return Err(RedditApiError::PostRejected { reason });
+        }
+        json["json"]["data"]["name"]
+            .as_str()
+            .map(String::from)
+            .ok_or(RedditApiError::ParseCommentError)
+    }
+
+    /// Replies to `parent`, a comment or submission fullname, and returns the new comment's
+    /// fullname.
+    pub async fn comment(self: &Self, parent: &str, text: &str) -> Result<String> {
+        let params = vec![("api_type", "json"), ("thing_id", parent), ("text", text)];
+        let json: Value = serde_json::from_str(&self.post(COMMENT_ENDPOINT, &params).await?)?;
+        if let Some(reason) = rejection(&json) {
+            return Err(RedditApiError::PostRejected { reason });
+        }
+        json["json"]["data"]["things"][0]["data"]["name"]
+            .as_str()
+            .map(String::from)
+            .ok_or(RedditApiError::ParseCommentError)
+    }
+
+    /// Deletes every (fullname, subreddit, overwrite) item with up to `concurrency` calls in
+    /// flight, all sharing the rate limiter. With `shred`, the items marked for overwriting
+    /// are overwritten `shred.passes` times first, one pass over all of them at a time with
+    /// `shred.delay` between passes, so the wait doesn't grow with the number of items.
+    /// Nothing more is done to an item once an overwrite fails. Results are in the same
+    /// order as `items`.
+    pub async fn delete_many(
+        self: &Self,
+        items: &[(String, String, bool)],
+        shred: Option<&Shred>,
+    ) -> Vec<Result<()>> {
+        let concurrency = self.concurrency.max(1);
+        let mut results: Vec<Result<()>> = items.iter().map(|_| Ok(())).collect();
+        if let Some(shred) = shred {
+            let passes = shred.passes.max(1);
+            for pass in 1..=passes {
+                let overwriting: Vec<usize> = (0..items.len())
+                    .filter(|&i| items[i].2 && results[i].is_ok())
+                    .collect();
+                if overwriting.is_empty() {
+                    break;
+                }
+                if pass > 1 {
+                    tokio::time::delay_for(shred.delay).await;
+                }
+                let edits: Vec<Result<()>> = stream::iter(&overwriting)
+                    .map(|&i| async move { self.edit(&items[i].0, &shred.replacement()).await })
+                    .buffered(concurrency)
+                    .collect()
+                    .await;
+                for (i, result) in overwriting.into_iter().zip(edits) {
+                    if result.is_err() {
+                        results[i] = result;
+                    }
+                }
+                if passes > 1 {
+                    info!("Overwrote everything ({}/{})", pass, passes);
+                }
+            }
+        }
+        let deleting: Vec<usize> = (0..items.len()).filter(|&i| results[i].is_ok()).collect();
+        let deletions: Vec<Result<()>> = stream::iter(&deleting)
+            .map(|&i| self.delete_from(items[i].0.clone(), &items[i].1))
+            .buffered(concurrency)
+            .collect()
+            .await;
+        for (i, result) in deleting.into_iter().zip(deletions) {
+            results[i] = result;
+        }
+        results
+    }
+
+    /// Removes `fullname` from the account's saved items. Needs the `save` scope, so accounts
+    /// authorized before it was requested have to authorize again.
+    pub async fn unsave(self: &Self, fullname: &str) -> Result<()> {
+        let params = vec![("id", fullname)];
+        self.post(UNSAVE_ENDPOINT, &params).await?;
+        info!("Unsaved!");
+        Ok(())
+    }
+
+    /// Takes back the account's vote on `fullname`. Needs the `vote` scope, so accounts
+    /// authorized before it was requested have to authorize again.
+    pub async fn clear_vote(self: &Self, fullname: &str) -> Result<()> {
+        let params = vec![("id", fullname), ("dir", "0")];
+        self.post(VOTE_ENDPOINT, &params).await?;
+        info!("Vote cleared!");
+        Ok(())
+    }
+
+    /// Hides the submission `fullname` from the account's listings. Hiding and unhiding need
+    /// the `report` scope.
+    pub async fn hide(self: &Self, fullname: &str) -> Result<()> {
+        let params = vec![("id", fullname)];
+        self.post(HIDE_ENDPOINT, &params).await?;
+        info!("Hidden!");
+        Ok(())
+    }
+
+    pub async fn unhide(self: &Self, fullname: &str) -> Result<()> {
+        let params = vec![("id", fullname)];
+        self.post(UNHIDE_ENDPOINT, &params).await?;
+        info!("Unhidden!");
+        Ok(())
+    }
+
+    /// Needs the `subscribe` scope, and `mysubreddits` to list subscriptions.
+    pub async fn unsubscribe(self: &Self, subreddit: &Subreddit) -> Result<()> {
+        let params = vec![("action", "unsub"), ("sr", &*subreddit.name)];
+        self.post(SUBSCRIBE_ENDPOINT, &params).await?;
+        info!("Unsubscribed!");
+        Ok(())
+    }
+
+    /// Deletes one of the account's multireddits. Needs the `subscribe` scope.
+    pub async fn delete_multi(self: &Self, multi: &Multireddit) -> Result<()> {
+        let endpoint = format!("{}{}", MULTI_ENDPOINT, multi.path.trim_end_matches('/'));
+        self.send(Method::DELETE, &endpoint, &Vec::new()).await?;
+        info!("Deleted!");
+        Ok(())
+    }
+
+    /// Takes `friend` off the account's friends list. Needs the `subscribe` scope.
+    pub async fn remove_friend(self: &Self, friend: &UserListEntry) -> Result<()> {
+        let endpoint = format!("{}/{}", FRIENDS_ENDPOINT, friend.name);
+        self.send(Method::DELETE, &endpoint, &Vec::new()).await?;
+        info!("Removed!");
+        Ok(())
+    }
+
+    /// Unblocks `user`. `own_fullname` is the account's, from `own_fullname()`.
+    pub async fn unblock(self: &Self, user: &UserListEntry, own_fullname: &str) -> Result<()> {
+        let params = vec![
+            ("name", &*user.name),
+            ("type", "enemy"),
+            ("container", own_fullname),
+        ];
+        self.post(UNFRIEND_ENDPOINT, &params).await?;
+        info!("Unblocked!");
+        Ok(())
+    }
+
+    /// Deletes a private message from the account's side of the conversation. Needs the
+    /// `privatemessages` scope.
+    pub async fn delete_message(self: &Self, fullname: &str) -> Result<()> {
+        let params = vec![("id", fullname)];
+        self.post(DELETE_MESSAGE_ENDPOINT, &params).await?;
+        info!("Deleted!");
+        Ok(())
+    }
+
+    pub async fn quarantine_optin(self: &Self, subreddit: &str) -> Result<()> {
+        let params = vec![("sr_name", subreddit)];
+        self.post(QUARANTINE_OPTIN_ENDPOINT, &params).await?;
         Ok(())
     }
 
-    async fn refresh(self: &Self, refresh_token: &str) -> Result<AccountInfo> {
-        println!("Refreshing OAuth2 token.");
-        let new_oauth_token = self.update_token(refresh_token).await?;
+    async fn refresh(self: &Self, refresh_token: &str, app: &RedditApp) -> Result<AccountInfo> {
+        verbose!("Refreshing OAuth2 token.");
+        let new_oauth_token = self.update_token(refresh_token, app).await?;
         Ok(save_token(String::from(&self.username), new_oauth_token)?)
     }
     async fn check_account_info(self: &Self) -> Result<AccountInfo> {
         let _x = self.account_info_mutex.lock().await;
         let ai =
             read_config_account_info(&self.username).expect("Unable to open account config file.");
-        if ai.token_expires > SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() {
+        if !ai.needs_refresh(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()) {
             Ok(ai)
         } else {
-            let refresh = self
-                .refresh(&ai.token.refresh_token.expect(&format!(
-                    "Unable to read refresh token from account {}, please reauthorize it.",
-                    ai.username
-                )))
-                .await?;
-            Ok(refresh)
+            let app = app_for(&ai.token);
+            let refresh_token =
+                ai.token
+                    .refresh_token
+                    .ok_or(RedditApiError::ReauthorizeNeeded {
+                        username: ai.username.clone(),
+                    })?;
+            Ok(self.refresh(&refresh_token, &app).await?)
         }
     }
-    async fn update_token(self: &Self, refresh_token: &str) -> Result<OAuthToken> {
+    async fn update_token(self: &Self, refresh_token: &str, app: &RedditApp) -> Result<OAuthToken> {
         let text = self
             .client
             .post(&format!("{}{}", auth_domain(), ACCESS_TOKEN_ENDPOINT))
-            .basic_auth(CLIENT_ID, Some(""))
+            .basic_auth(&app.client_id, Some(client_secret(&app)))
             .body(format!(
                 "grant_type=refresh_token&refresh_token={}",
                 &refresh_token
@@ -306,12 +1213,103 @@ impl RedditClient {
             .await?
             .text()
             .await?;
-        let mut new_oauth_token: OAuthToken = serde_json::from_str(&text)?;
+        let response: Value = serde_json::from_str(&text)?;
+        match response["error"].as_str() {
+            // The refresh token was revoked, or the account's password changed.
+            Some("invalid_grant") => {
+                return Err(RedditApiError::ReauthorizeNeeded {
+                    username: String::from(&self.username),
+                })
+            }
+            Some(error) => {
+                verbose!("Refreshing the token failed: {}", error);
+                return Err(RedditApiError::RefreshTokenError);
+            }
+            None => (),
+        }
+        let mut new_oauth_token: OAuthToken = serde_json::from_value(response)?;
         new_oauth_token.refresh_token = Some(String::from(refresh_token));
+        new_oauth_token.client_id = Some(app.client_id.clone());
         Ok(new_oauth_token)
     }
 }
 
+/// Reddit answers `api_type=json` calls it refuses with a 200 and [code, message, field]
+/// lists in `json.errors`. Returns the first code, lowercased.
+fn rejection(json: &Value) -> Option<String> {
+    match json["json"]["errors"].as_array() {
+        Some(errors) if !errors.is_empty() => {
+            Some(errors[0][0].as_str().unwrap_or("unknown").to_lowercase())
+        }
+        _ => None,
+    }
+}
+
+/// Appends the items in `listing` that no earlier listing had, going by fullname, and
+/// returns how many that was.
+fn merge_listing(
+    total: &mut Vec<DeletionInfo>,
+    seen: &mut HashSet<String>,
+    listing: Vec<DeletionInfo>,
+) -> usize {
+    let before = total.len();
+    for item in listing {
+        if seen.insert(item.name.clone()) {
+            total.push(item);
+        }
+    }
+    total.len() - before
+}
+
+fn collect_own_comments(children: &Value, username: &str, out: &mut Vec<Comment>) -> Result<()> {
+    let children = match children.as_array() {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+    for child in children {
+        if child["kind"] != "t1" {
+            continue;
+        }
+        let data = &child["data"];
+        if data["author"]
+            .as_str()
+            .map_or(false, |a| a.eq_ignore_ascii_case(username))
+        {
+            out.push(serde_json::from_value(data.clone())?);
+        }
+        collect_own_comments(&data["replies"]["data"]["children"], username, out)?;
+    }
+    Ok(())
+}
+
+/// Accepts a bare article id, a `t3_` fullname, or any reddit permalink to the thread.
+pub fn parse_thread_id(link: &str) -> Option<String> {
+    let link = link.trim();
+    if let Some(i) = link.find("/comments/") {
+        return link[i + "/comments/".len()..]
+            .split('/')
+            .next()
+            .filter(|id| !id.is_empty())
+            .map(String::from);
+    }
+    if let Ok(url) = url::Url::parse(link) {
+        if url.host_str() == Some("redd.it") {
+            return url
+                .path_segments()
+                .and_then(|mut s| s.next())
+                .filter(|id| !id.is_empty())
+                .map(String::from);
+        }
+        return None;
+    }
+    let id = link.trim_start_matches("t3_");
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Some(String::from(id))
+    } else {
+        None
+    }
+}
+
 fn validate_oauth_redirect(state: String, oauth_redirect: &OAuthRedirect) -> Result<()> {
     if state != oauth_redirect.state {
         Err(RedditApiError::OAuthValidationError {
@@ -335,17 +1333,72 @@ fn make_client() -> Result<Client> {
     Ok(builder.default_headers(headers).build()?)
 }
 
+/// The reddit app in the config, or redelete's own.
+fn app() -> RedditApp {
+    reddit_app().unwrap_or_else(|| app_with_id(CLIENT_ID))
+}
+
+fn app_with_id(client_id: &str) -> RedditApp {
+    RedditApp {
+        client_id: String::from(client_id),
+        client_secret: None,
+        redirect_uri: None,
+    }
+}
+
+/// The app that issued `token`, so switching apps doesn't break refreshing or revoking
+/// tokens from the previous one. Tokens that don't say belong to the configured app.
+fn app_for(token: &OAuthToken) -> RedditApp {
+    let configured = app();
+    match &token.client_id {
+        Some(client_id) if *client_id != configured.client_id => app_with_id(client_id),
+        _ => configured,
+    }
+}
+
+fn client_secret(app: &RedditApp) -> &str {
+    app.client_secret.as_deref().unwrap_or("")
+}
+
+fn redirect_uri(app: &RedditApp) -> &str {
+    app.redirect_uri.as_deref().unwrap_or(REDIRECT_URI)
+}
+
+/// The port the redirect uri points at, which is where the authorization is waited for.
+fn redirect_port(redirect_uri: &str) -> u16 {
+    url::Url::parse(redirect_uri)
+        .ok()
+        .and_then(|url| url.port_or_known_default())
+        .unwrap_or(REDIRECT_PORT)
+}
+
+/// The host and port to wait for the redirect on. `listen` is HOST or HOST:PORT, missing
+/// parts come from the redirect uri.
+fn listen_address(listen: Option<&str>, redirect_uri: &str) -> (String, u16) {
+    let port = redirect_port(redirect_uri);
+    match listen {
+        None => (String::from(LISTEN_HOST), port),
+        Some(listen) => match listen.rfind(':') {
+            Some(i) => match listen[i + 1..].parse() {
+                Ok(port) => (String::from(&listen[..i]), port),
+                Err(_) => (String::from(listen), port),
+            },
+            None => (String::from(listen), port),
+        },
+    }
+}
+
 async fn get_token<'de>(oauth_redirect: &OAuthRedirect) -> Result<OAuthToken> {
     let client = make_client()?;
-
+    let app = app();
     let data = format!(
         "grant_type=authorization_code&code={}&redirect_uri={}",
         String::from(&oauth_redirect.code),
-        REDIRECT_URI
+        redirect_uri(&app)
     );
     let t = client
         .post(&format!("{}{}", &auth_domain(), ACCESS_TOKEN_ENDPOINT))
-        .basic_auth(CLIENT_ID, Some(""))
+        .basic_auth(&app.client_id, Some(client_secret(&app)))
         .body(data)
         .send()
         .await
@@ -353,8 +1406,9 @@ async fn get_token<'de>(oauth_redirect: &OAuthRedirect) -> Result<OAuthToken> {
         .text()
         .await
         .expect("Unable to parse auth response");
-    let token: OAuthToken =
+    let mut token: OAuthToken =
         serde_json::from_str(&t).expect("Unable to serialize access token response text");
+    token.client_id = Some(app.client_id.clone());
     Ok(token)
 }
 
@@ -376,30 +1430,96 @@ pub async fn username(token: &OAuthToken) -> Result<String> {
     Ok(user.name)
 }
 
-pub async fn authorize() -> Result<String> {
+/// Revokes the refresh token and then the access token, so neither works after logging out.
+/// Reddit answers 204 for tokens it already forgot, so only real failures are errors.
+pub async fn revoke_tokens(token: &OAuthToken) -> Result<()> {
+    let client = make_client()?;
+    let app = app_for(token);
+    let mut tokens = Vec::new();
+    if let Some(refresh_token) = &token.refresh_token {
+        tokens.push((refresh_token.as_str(), "refresh_token"));
+    }
+    // Accounts imported without their tokens have nothing to revoke.
+    if !token.access_token.is_empty() {
+        tokens.push((token.access_token.as_str(), "access_token"));
+    }
+    for (value, hint) in tokens {
+        verbose!("POST {} ({})", REVOKE_TOKEN_ENDPOINT, hint);
+        client
+            .post(&format!("{}{}", auth_domain(), REVOKE_TOKEN_ENDPOINT))
+            .basic_auth(&app.client_id, Some(client_secret(&app)))
+            .form(&[("token", value), ("token_type_hint", hint)])
+            .send()
+            .await?
+            .error_for_status()?;
+    }
+    Ok(())
+}
+
+pub async fn authorize(scopes: &[&str]) -> Result<String> {
+    let (username, access_token) = authorization(scopes).await?;
+    save_token(username.clone(), access_token)?;
+    Ok(username)
+}
+
+/// Authorizes a saved account again, replacing only its token so it picks up scopes added
+/// since it was first authorized. Returns the scopes the new token has.
+pub async fn reauthorize(expected: &str, scopes: &[&str]) -> Result<Vec<String>> {
+    let (username, access_token) = authorization(scopes).await?;
+    check_account(expected, &username)?;
+    let scopes = access_token.scopes();
+    save_token(String::from(expected), access_token)?;
+    Ok(scopes)
+}
+
+/// reddit authorizes whoever is logged in on the browser, which may not be the account the
+/// user meant. Usernames aren't case sensitive.
+fn check_account(expected: &str, authorized: &str) -> Result<()> {
+    if expected.eq_ignore_ascii_case(authorized) {
+        Ok(())
+    } else {
+        Err(RedditApiError::WrongAccount {
+            expected: String::from(expected),
+            authorized: String::from(authorized),
+        })
+    }
+}
+
+/// Sends the user through reddit's authorization page and returns who they authorized as
+/// and the token.
+async fn authorization(scopes: &[&str]) -> Result<(String, OAuthToken)> {
     // I don't see how to test this without installing a webdriver and using a dummy account. I don't want to do that.
-    let state = open_authorization_page()?;
-    let oauth_redirect = wait_for_oauth_redirect(8000, 8001).unwrap();
+    let state = open_authorization_page(scopes)?;
+    let listen = redirect_listener();
+    let (host, port) = listen_address(listen.as_deref(), redirect_uri(&app()));
+    verbose!("Waiting for reddit's redirect on {}:{}", host, port);
+    let oauth_redirect = wait_for_oauth_redirect(&host, port, port + 1).unwrap();
     validate_oauth_redirect(state, &oauth_redirect)?;
     let access_token = get_token(&oauth_redirect).await?;
     let username = username(&access_token).await?;
-    save_token(username.clone(), access_token)?;
-    Ok(username)
+    Ok((username, access_token))
 }
-fn open_authorization_page() -> Result<String> {
-    println!("Opening browser, please authorize redelete to access your account.");
+fn open_authorization_page(scopes: &[&str]) -> Result<String> {
+    say!("Opening browser, please authorize redelete to access your account.");
     let state = nanoid::simple();
+    let app = app();
     let url = format!(
         "{}/api/v1/authorize?client_id={}&response_type={}&state={}&redirect_uri={}&duration={}&scope={}",
         auth_domain(),
-        CLIENT_ID,
+        app.client_id,
         RESPONSE_TYPE,
         state,
-        REDIRECT_URI,
+        redirect_uri(&app),
         DURATION,
-        SCOPE
+        scopes.join(",")
     );
-    webbrowser::open(&url)?;
+    // There's no browser to open when authorizing on a remote machine.
+    if webbrowser::open(&url).is_err() {
+        say!(
+            "Unable to open a browser, open this link to authorize redelete:\n{}",
+            url
+        );
+    }
     Ok(state)
 }
 
@@ -449,6 +1569,14 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn test_humanize_age() {
+        assert_eq!(humanize_age(5), "just now");
+        assert_eq!(humanize_age(60), "1 minute ago");
+        assert_eq!(humanize_age(3 * 60 * 60), "3 hours ago");
+        assert_eq!(humanize_age(45 * 24 * 60 * 60), "1 month ago");
+        assert_eq!(humanize_age(3 * 366 * 24 * 60 * 60), "3 years ago");
+    }
+    #[test]
     fn test_validate_oauth_redirect() {
         assert_eq!(
             (),
@@ -464,7 +1592,8 @@ mod tests {
         let _m = mock("POST", ACCESS_TOKEN_ENDPOINT)
             .with_body(TOKEN_BODY)
             .create();
-        let token: OAuthToken = serde_json::from_str(TOKEN_BODY).unwrap();
+        let mut token: OAuthToken = serde_json::from_str(TOKEN_BODY).unwrap();
+        token.client_id = Some(app().client_id);
         let test_token: OAuthToken = Runtime::new()
             .unwrap()
             .block_on(async { get_token(&oauth_redirect()).await.unwrap() });
@@ -496,15 +1625,85 @@ mod tests {
         save_token(reddit_client.username.clone(), token()).unwrap();
         let new_token = Runtime::new().unwrap().block_on(async {
             reddit_client
-                .update_token(&token().refresh_token.unwrap())
+                .update_token(&token().refresh_token.unwrap(), &app())
                 .await
                 .unwrap()
         });
         assert_eq!(new_token.refresh_token.unwrap(), "REFRESH_TOKEN");
         assert_eq!(new_token.access_token, REFRESHED_ACCESS_TOKEN);
+        assert_eq!(new_token.client_id, Some(app().client_id));
+        delete_user(TEST_USER).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_revoked_refresh_token() {
+        let _m = mock("POST", ACCESS_TOKEN_ENDPOINT)
+            .with_status(400)
+            .with_body(r#"{"error": "invalid_grant"}"#)
+            .create();
+        let mut expired = token();
+        expired.expires_in = 0;
+        save_token(String::from(TEST_USER), expired).unwrap();
+        let client = reddit_client(String::from(TEST_USER));
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { client.fetch(ACCOUNT_INFO_ENDPOINT, &vec![]).await });
+        match result {
+            Err(RedditApiError::ReauthorizeNeeded { username }) => assert_eq!(username, TEST_USER),
+            other => panic!("Expected ReauthorizeNeeded, got {:?}", other.map(|_| ())),
+        }
+        // Without a refresh token there's nothing to try.
+        let mut expired = token();
+        expired.expires_in = 0;
+        expired.refresh_token = None;
+        save_token(String::from(TEST_USER), expired).unwrap();
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { client.fetch(ACCOUNT_INFO_ENDPOINT, &vec![]).await });
+        match result {
+            Err(RedditApiError::ReauthorizeNeeded { .. }) => (),
+            other => panic!("Expected ReauthorizeNeeded, got {:?}", other.map(|_| ())),
+        }
         delete_user(TEST_USER).unwrap();
     }
 
+    #[test]
+    #[serial]
+    fn test_revoke_tokens() {
+        let refresh = mock("POST", REVOKE_TOKEN_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("token".into(), "REFRESH_TOKEN".into()),
+                Matcher::UrlEncoded("token_type_hint".into(), "refresh_token".into()),
+            ]))
+            .with_status(204)
+            .create();
+        let access = mock("POST", REVOKE_TOKEN_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("token".into(), "ACCESS_TOKEN".into()),
+                Matcher::UrlEncoded("token_type_hint".into(), "access_token".into()),
+            ]))
+            .with_status(204)
+            .create();
+        Runtime::new()
+            .unwrap()
+            .block_on(async { revoke_tokens(&token()).await.unwrap() });
+        refresh.assert();
+        access.assert();
+    }
+
+    #[test]
+    #[serial]
+    fn test_revoke_missing_tokens() {
+        let revoke = mock("POST", REVOKE_TOKEN_ENDPOINT).expect(0).create();
+        Runtime::new().unwrap().block_on(async {
+            revoke_tokens(&super::super::config::unauthorized_token())
+                .await
+                .unwrap()
+        });
+        revoke.assert();
+    }
+
     #[test]
     #[serial]
     fn test_expired_token() {
@@ -564,6 +1763,29 @@ mod tests {
     }
     #[test]
     #[serial]
+    fn test_raw_history() {
+        let comments = test_data::comments();
+        let body = format!(
+            r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+            comments.join(", ")
+        );
+        let _m = mock("GET", Matcher::Any)
+            .with_body(body)
+            .with_status(200)
+            .create();
+        let reddit_client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let history = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.raw_history().await.unwrap() });
+        delete_user(TEST_USER).unwrap();
+        // The same listing answers for comments and submissions.
+        assert_eq!(history.len(), 2 * comments.len());
+        // Fields redelete doesn't use otherwise are kept.
+        assert_eq!(history[0].contains_key("link_id"), true);
+    }
+    #[test]
+    #[serial]
     fn test_comments() {
         let comments = test_data::comments();
         let end = 40;
@@ -586,7 +1808,10 @@ mod tests {
                             "before": null
                         }}
                     }}"#,
-                    &String::from(comments.join(", ")),
+                    // Listings are merged by fullname, so each page needs its own.
+                    &comments
+                        .join(", ")
+                        .replace(r#""name":"t1_"#, &format!(r#""name":"t1_{}_"#, i)),
                     &after
                 );
                 // let endpoint = &format!("/user/{}/comments", TEST_USER);
@@ -669,6 +1894,304 @@ mod tests {
         delete_user(TEST_USER).unwrap();
     }
 
+    #[test]
+    #[serial]
+    fn test_backfill_dedupes_listings() {
+        let comments = test_data::comments();
+        let body = format!(
+            r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+            comments.join(", ")
+        );
+        let _m = mock("GET", Matcher::Any)
+            .with_body(body)
+            .with_status(200)
+            .expect(BACKFILL_LISTINGS.len())
+            .create();
+        let mut reddit_client = reddit_client(String::from(TEST_USER));
+        reddit_client.backfill = true;
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let fetched_comments = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.comments().await.unwrap() });
+        _m.assert();
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(fetched_comments.len(), comments.len())
+    }
+
+    #[test]
+    #[serial]
+    fn test_backfill_unions_sorts() {
+        let comments = test_data::comments();
+        let listing = |children: &[&str]| {
+            format!(
+                r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+                children.join(", ")
+            )
+        };
+        let sort = |sort: &str| Matcher::UrlEncoded("sort".into(), sort.into());
+        // `new` and `top` each reach part of the history, with one item in both.
+        let _new = mock("GET", Matcher::Any)
+            .match_query(sort("new"))
+            .with_body(listing(&comments[..2]))
+            .create();
+        let _top = mock("GET", Matcher::Any)
+            .match_query(sort("top"))
+            .with_body(listing(&comments[1..]))
+            .create();
+        let _hot = mock("GET", Matcher::Any)
+            .match_query(sort("hot"))
+            .with_body(listing(&[]))
+            .create();
+        let _controversial = mock("GET", Matcher::Any)
+            .match_query(sort("controversial"))
+            .with_body(listing(&[]))
+            .create();
+        let mut reddit_client = reddit_client(String::from(TEST_USER));
+        reddit_client.backfill = true;
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let fetched_comments = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.comments().await.unwrap() });
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(fetched_comments.len(), comments.len());
+        let names: HashSet<String> = fetched_comments.into_iter().map(|c| c.name).collect();
+        assert_eq!(names.len(), comments.len());
+    }
+
+    #[test]
+    #[serial]
+    fn test_overview() {
+        let mut children = test_data::comments();
+        children.extend(test_data::posts());
+        let body = format!(
+            r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+            children.join(", ")
+        );
+        let _m = mock("GET", &*format!("/user/{}/overview", TEST_USER))
+            .match_query(Matcher::Any)
+            .with_body(body)
+            .with_status(200)
+            .expect(1)
+            .create();
+        let mut reddit_client = reddit_client(String::from(TEST_USER));
+        reddit_client.overview = true;
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let history = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.history().await.unwrap() });
+        _m.assert();
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(history.len(), children.len());
+        assert_eq!(
+            history.iter().filter(|item| item.body.is_some()).count(),
+            test_data::comments().len()
+        );
+        assert_eq!(
+            history.iter().filter(|item| item.title.is_some()).count(),
+            test_data::posts().len()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_saved() {
+        let mut children = test_data::posts();
+        children.extend(test_data::comments());
+        let body = format!(
+            r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+            children.join(", ")
+        );
+        let _m = mock("GET", &*format!("/user/{}/saved", TEST_USER))
+            .match_query(Matcher::Any)
+            .with_body(body)
+            .with_status(200)
+            .create();
+        let reddit_client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let saved = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.saved().await.unwrap() });
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(saved.len(), children.len());
+        assert_eq!(saved[0].name.starts_with("t3_"), true);
+    }
+
+    #[test]
+    #[serial]
+    fn test_voted() {
+        let listing = |children: Vec<&str>| {
+            format!(
+                r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+                children.join(", ")
+            )
+        };
+        let _up = mock("GET", &*format!("/user/{}/upvoted", TEST_USER))
+            .match_query(Matcher::Any)
+            .with_body(listing(test_data::posts()))
+            .with_status(200)
+            .create();
+        let _down = mock("GET", &*format!("/user/{}/downvoted", TEST_USER))
+            .match_query(Matcher::Any)
+            .with_body(listing(test_data::comments()))
+            .with_status(200)
+            .create();
+        let reddit_client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let (upvoted, downvoted) = Runtime::new().unwrap().block_on(async {
+            futures::try_join!(reddit_client.upvoted(), reddit_client.downvoted()).unwrap()
+        });
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(upvoted.len(), test_data::posts().len());
+        assert_eq!(downvoted.len(), test_data::comments().len());
+    }
+
+    #[test]
+    #[serial]
+    fn test_history_pages() {
+        let listing = |children: Vec<&str>| {
+            format!(
+                r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+                children.join(", ")
+            )
+        };
+        let _c = mock("GET", &*format!("/user/{}/comments", TEST_USER))
+            .match_query(Matcher::Any)
+            .with_body(listing(test_data::comments()))
+            .with_status(200)
+            .create();
+        let _p = mock("GET", &*format!("/user/{}/submitted", TEST_USER))
+            .match_query(Matcher::Any)
+            .with_body(listing(test_data::posts()))
+            .with_status(200)
+            .create();
+        let reddit_client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let pages: Vec<Vec<DeletionInfo>> = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.history_pages().try_collect().await.unwrap() });
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].len(), test_data::comments().len());
+        assert_eq!(pages[0].iter().all(|c| c.body.is_some()), true);
+        assert_eq!(pages[1].len(), test_data::posts().len());
+    }
+
+    #[test]
+    fn test_deserialize_edited() {
+        let comment = |edited: &str| {
+            format!(
+                r#"{{"saved":false,"name":"t1_a","created_utc":1579506505.0,"subreddit":"rust","score":1,"body":"hi","distinguished":null,"author_flair_text":null,"edited":{},"controversiality":1}}"#,
+                edited
+            )
+        };
+        let edited: Comment = serde_json::from_str(&comment("1579510000.0")).unwrap();
+        assert_eq!(edited.edited, true);
+        assert_eq!(edited.deletion_info().controversial, true);
+        let unedited: Comment = serde_json::from_str(&comment("false")).unwrap();
+        assert_eq!(unedited.edited, false);
+    }
+
+    #[test]
+    fn test_parse_thread_id() {
+        assert_eq!(
+            parse_thread_id(
+                "https://www.reddit.com/r/webdev/comments/er8pzu/tools_for_webdevelopment/ff2hmdi/"
+            ),
+            Some("er8pzu".into())
+        );
+        assert_eq!(
+            parse_thread_id("https://redd.it/er8pzu"),
+            Some("er8pzu".into())
+        );
+        assert_eq!(parse_thread_id("t3_er8pzu"), Some("er8pzu".into()));
+        assert_eq!(parse_thread_id("er8pzu"), Some("er8pzu".into()));
+        assert_eq!(parse_thread_id("https://example.com/"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_thread_comments() {
+        let comment = |name: &str, author: &str, replies: &str| {
+            format!(
+                r#"{{"kind": "t1", "data": {{"saved": false, "name": "{}", "author": "{}", "created_utc": 1579506505.0, "subreddit": "rust", "score": 1, "body": "body", "replies": {}}}}}"#,
+                name, author, replies
+            )
+        };
+        let nested = comment("t1_c", TEST_USER, r#""""#);
+        let reply = comment(
+            "t1_b",
+            "someone_else",
+            &format!(
+                r#"{{"kind": "Listing", "data": {{"children": [{}]}}}}"#,
+                nested
+            ),
+        );
+        let top = comment(
+            "t1_a",
+            TEST_USER,
+            &format!(
+                r#"{{"kind": "Listing", "data": {{"children": [{}]}}}}"#,
+                reply
+            ),
+        );
+        let body = format!(
+            r#"[{{"kind": "Listing", "data": {{"children": []}}}}, {{"kind": "Listing", "data": {{"children": [{}, {{"kind": "more", "data": {{}}}}]}}}}]"#,
+            top
+        );
+        let _m = mock("GET", Matcher::Regex(String::from("^/comments/er8pzu")))
+            .with_body(body)
+            .with_status(200)
+            .create();
+        let reddit_client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let comments = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.thread_comments("er8pzu").await.unwrap() });
+        delete_user(TEST_USER).unwrap();
+        let names: Vec<String> = comments.into_iter().map(|c| c.name).collect();
+        assert_eq!(names, vec![String::from("t1_a"), String::from("t1_c")]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_info() {
+        let comment = |name: &str, author: &str| {
+            format!(
+                r#"{{"kind": "t1", "data": {{"saved": false, "name": "{}", "author": "{}", "created_utc": 1579506505.0, "subreddit": "rust", "score": 1, "body": "body"}}}}"#,
+                name, author
+            )
+        };
+        let post = test_data::posts()[0].replace(
+            r#""author":"GallowBoob""#,
+            &format!(r#""author":"{}""#, TEST_USER.to_lowercase()),
+        );
+        let body = format!(
+            r#"{{"kind": "Listing", "data": {{"children": [{}, {}, {}, {}]}}}}"#,
+            comment("t1_a", TEST_USER),
+            comment("t1_b", "[deleted]"),
+            comment("t1_d", "SomeoneElse"),
+            post
+        );
+        let _m = mock("GET", Matcher::Regex(String::from("^/api/info")))
+            .match_query(Matcher::UrlEncoded(
+                "id".into(),
+                "t1_a,t1_b,t1_d,t3_c".into(),
+            ))
+            .with_body(body)
+            .with_status(200)
+            .create();
+        let reddit_client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&reddit_client.username), token()).unwrap();
+        let fullnames = vec!["t1_a".into(), "t1_b".into(), "t1_d".into(), "t3_c".into()];
+        let found = Runtime::new()
+            .unwrap()
+            .block_on(async { reddit_client.info(&fullnames).await.unwrap() });
+        delete_user(TEST_USER).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].name, "t1_a");
+        assert_eq!(found[1].title.is_some(), true);
+    }
+
     #[test]
     #[serial]
     fn test_delete() {
@@ -680,4 +2203,535 @@ mod tests {
             .block_on(async { client.delete(String::from("t1_a")).await.unwrap() });
         assert_eq!((), res)
     }
+
+    #[test]
+    #[serial]
+    fn test_unsave() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _m = mock("POST", UNSAVE_ENDPOINT)
+            .match_body(Matcher::UrlEncoded("id".into(), "t3_a".into()))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        Runtime::new()
+            .unwrap()
+            .block_on(async { client.unsave("t3_a").await.unwrap() });
+        _m.assert();
+    }
+
+    #[test]
+    #[serial]
+    fn test_clear_vote() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _m = mock("POST", VOTE_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("id".into(), "t1_a".into()),
+                Matcher::UrlEncoded("dir".into(), "0".into()),
+            ]))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        Runtime::new()
+            .unwrap()
+            .block_on(async { client.clear_vote("t1_a").await.unwrap() });
+        _m.assert();
+    }
+
+    #[test]
+    #[serial]
+    fn test_hide_and_unhide() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _hide = mock("POST", HIDE_ENDPOINT)
+            .match_body(Matcher::UrlEncoded("id".into(), "t3_a".into()))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let _unhide = mock("POST", UNHIDE_ENDPOINT)
+            .match_body(Matcher::UrlEncoded("id".into(), "t3_b".into()))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        Runtime::new().unwrap().block_on(async {
+            client.hide("t3_a").await.unwrap();
+            client.unhide("t3_b").await.unwrap();
+        });
+        _hide.assert();
+        _unhide.assert();
+    }
+
+    #[test]
+    #[serial]
+    fn test_subscriptions() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _list = mock("GET", SUBSCRIPTIONS_ENDPOINT)
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"kind": "Listing", "data": {"children": [
+                    {"kind": "t5", "data": {"name": "t5_2qh1i", "display_name": "AskReddit", "subscribers": 100}},
+                    {"kind": "t5", "data": {"name": "t5_2s7lj", "display_name": "rust"}}
+                ], "after": null, "before": null}}"#,
+            )
+            .with_status(200)
+            .create();
+        let _unsub = mock("POST", SUBSCRIBE_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("action".into(), "unsub".into()),
+                Matcher::UrlEncoded("sr".into(), "t5_2s7lj".into()),
+            ]))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let subscriptions = Runtime::new().unwrap().block_on(async {
+            let subscriptions = client.subscriptions().await.unwrap();
+            client.unsubscribe(&subscriptions[1]).await.unwrap();
+            subscriptions
+        });
+        _unsub.assert();
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(subscriptions[0].display_name, "AskReddit");
+        assert_eq!(subscriptions[1].subscribers, None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_multis() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _list = mock("GET", "/api/multi/mine")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[{"kind": "LabeledMulti", "data": {"name": "langs", "display_name": "Languages",
+                    "path": "/user/TestUser/m/langs/", "subreddits": [{"name": "rust"}, {"name": "golang"}]}}]"#,
+            )
+            .with_status(200)
+            .create();
+        let _delete = mock("DELETE", "/api/multi/user/TestUser/m/langs")
+            .with_status(200)
+            .expect(1)
+            .create();
+        let multis = Runtime::new().unwrap().block_on(async {
+            let multis = client.multis().await.unwrap();
+            client.delete_multi(&multis[0]).await.unwrap();
+            multis
+        });
+        _delete.assert();
+        assert_eq!(multis.len(), 1);
+        assert_eq!(multis[0].display_name, "Languages");
+        assert_eq!(multis[0].subreddits.len(), 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_friends() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _list = mock("GET", FRIENDS_ENDPOINT)
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"kind": "UserList", "data": {"children": [
+                    {"name": "spez", "id": "t2_1w72", "date": 1579506505.0},
+                    {"name": "kn0thing", "id": "t2_1wh0"}
+                ]}}"#,
+            )
+            .with_status(200)
+            .create();
+        let _remove = mock("DELETE", "/api/v1/me/friends/spez")
+            .with_status(204)
+            .expect(1)
+            .create();
+        let friends = Runtime::new().unwrap().block_on(async {
+            let friends = client.friends().await.unwrap();
+            client.remove_friend(&friends[0]).await.unwrap();
+            friends
+        });
+        _remove.assert();
+        assert_eq!(friends.len(), 2);
+        assert_eq!(friends[1].date, None);
+    }
+
+    #[test]
+    fn test_scopes_for() {
+        assert_eq!(scopes_for(&[]), requested_scopes());
+        assert_eq!(
+            scopes_for(&["votes"]),
+            vec!["history", "edit", "identity", "read", "vote"]
+        );
+        assert_eq!(
+            scopes_for(&["saved", "subscriptions"]),
+            vec![
+                "history",
+                "edit",
+                "identity",
+                "read",
+                "save",
+                "mysubreddits",
+                "subscribe"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_account() {
+        assert_eq!(check_account("TestUser", "testuser").is_ok(), true);
+        assert_eq!(
+            check_account("TestUser", "SomeoneElse")
+                .unwrap_err()
+                .to_string(),
+            "Authorized SomeoneElse instead of TestUser. Log into reddit as TestUser and try again"
+        );
+    }
+
+    #[test]
+    fn test_listen_address() {
+        assert_eq!(
+            listen_address(None, REDIRECT_URI),
+            (String::from("0.0.0.0"), 8000)
+        );
+        assert_eq!(
+            listen_address(Some("127.0.0.1"), REDIRECT_URI),
+            (String::from("127.0.0.1"), 8000)
+        );
+        assert_eq!(
+            listen_address(Some("0.0.0.0:8080"), REDIRECT_URI),
+            (String::from("0.0.0.0"), 8080)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_app_for() {
+        let mut issued = token();
+        assert_eq!(app_for(&issued).client_id, app().client_id);
+        issued.client_id = Some(String::from("previous_client_id"));
+        assert_eq!(app_for(&issued).client_id, "previous_client_id");
+        issued.client_id = Some(app().client_id);
+        assert_eq!(app_for(&issued), app());
+    }
+
+    #[test]
+    fn test_redirect_port() {
+        assert_eq!(redirect_port(REDIRECT_URI), 8000);
+        assert_eq!(redirect_port("http://localhost:65010/callback"), 65010);
+        assert_eq!(redirect_port("http://127.0.0.1"), 80);
+        assert_eq!(redirect_port("not a url"), REDIRECT_PORT);
+    }
+
+    #[test]
+    fn test_scopes() {
+        let mut token = token();
+        assert_eq!(token.scopes(), vec!["history", "edit", "account"]);
+        token.scope = "identity history".into();
+        assert_eq!(token.scopes(), vec!["identity", "history"]);
+        assert_eq!(requested_scopes().contains(&"history"), true);
+    }
+
+    #[test]
+    #[serial]
+    fn test_verified_username() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _me = mock("GET", ACCOUNT_INFO_ENDPOINT)
+            .match_query(Matcher::Any)
+            .with_body(r#"{"id": "dp1yw", "name": "TestUser"}"#)
+            .with_status(200)
+            .create();
+        let name = Runtime::new()
+            .unwrap()
+            .block_on(async { client.verified_username().await.unwrap() });
+        assert_eq!(name, "TestUser");
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_blocked() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _list = mock("GET", BLOCKED_ENDPOINT)
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"{"kind": "UserList", "data": {"children": [
+                    {"name": "troll", "id": "t2_abc", "date": 1579506505.0}
+                ]}}"#,
+            )
+            .with_status(200)
+            .create();
+        let _me = mock("GET", ACCOUNT_INFO_ENDPOINT)
+            .match_query(Matcher::Any)
+            .with_body(r#"{"id": "dp1yw", "name": "TestUser"}"#)
+            .with_status(200)
+            .create();
+        let _unblock = mock("POST", UNFRIEND_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("name".into(), "troll".into()),
+                Matcher::UrlEncoded("type".into(), "enemy".into()),
+                Matcher::UrlEncoded("container".into(), "t2_dp1yw".into()),
+            ]))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let blocked = Runtime::new().unwrap().block_on(async {
+            let blocked = client.blocked().await.unwrap();
+            let me = client.own_fullname().await.unwrap();
+            client.unblock(&blocked[0], &me).await.unwrap();
+            blocked
+        });
+        _unblock.assert();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].name, "troll");
+    }
+
+    #[test]
+    #[serial]
+    fn test_messages() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let listing = |children: &str| {
+            format!(
+                r#"{{"kind": "Listing", "data": {{"children": [{}], "after": null, "before": null}}}}"#,
+                children
+            )
+        };
+        let _inbox = mock("GET", "/message/messages")
+            .match_query(Matcher::Any)
+            .with_body(listing(
+                r#"{"kind": "t4", "data": {"name": "t4_a", "author": "spez", "dest": "TestUser", "subject": "hi", "created_utc": 1579506505.0}}"#,
+            ))
+            .create();
+        let _sent = mock("GET", "/message/sent")
+            .match_query(Matcher::Any)
+            .with_body(listing(
+                r#"{"kind": "t4", "data": {"name": "t4_b", "author": "TestUser", "dest": "spez", "subject": "re: hi", "created_utc": 1579506600.0}}"#,
+            ))
+            .create();
+        let _delete = mock("POST", DELETE_MESSAGE_ENDPOINT)
+            .match_body(Matcher::UrlEncoded("id".into(), "t4_a".into()))
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let messages = Runtime::new().unwrap().block_on(async {
+            let messages = client.messages().await.unwrap();
+            client.delete_message(&messages[0].name).await.unwrap();
+            messages
+        });
+        _delete.assert();
+        let names: Vec<&str> = messages.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["t4_a", "t4_b"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_delete_forbidden() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _m = mock("POST", DELETE_ENDPOINT)
+            .with_status(403)
+            .with_body(r#"{"reason": "quarantined", "message": "Forbidden", "error": 403}"#)
+            .create();
+        let res = Runtime::new()
+            .unwrap()
+            .block_on(async { client.delete_from(String::from("t1_a"), "q").await });
+        match res {
+            Err(RedditApiError::Forbidden { reason }) => assert_eq!(reason, "quarantined"),
+            other => panic!("expected a forbidden error, got {:?}", other),
+        }
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_max_requests() {
+        let mut client = reddit_client(String::from(TEST_USER));
+        client.max_requests = Some(1);
+        save_token(String::from(&client.username), token()).unwrap();
+        let _m = mock("POST", DELETE_ENDPOINT)
+            .with_status(200)
+            .with_body("{}")
+            .expect(1)
+            .create();
+        let (first, second) = Runtime::new().unwrap().block_on(async {
+            (
+                client.delete(String::from("t1_a")).await,
+                client.delete(String::from("t1_b")).await,
+            )
+        });
+        assert_eq!(first.is_ok(), true);
+        match second {
+            Err(RedditApiError::BudgetExhausted) => (),
+            other => panic!("expected the budget to be used up, got {:?}", other),
+        }
+        _m.assert();
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_deadline() {
+        let mut client = reddit_client(String::from(TEST_USER));
+        client.deadline = Some(Instant::now());
+        save_token(String::from(&client.username), token()).unwrap();
+        let _m = mock("POST", DELETE_ENDPOINT)
+            .with_status(200)
+            .with_body("{}")
+            .expect(0)
+            .create();
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { client.delete(String::from("t1_a")).await });
+        match result {
+            Err(e @ RedditApiError::TimedOut) => assert_eq!(e.stops_run(), true),
+            other => panic!("expected the time limit to be up, got {:?}", other),
+        }
+        assert_eq!(client.requests_made(), 0);
+        _m.assert();
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_delete_many() {
+        let mut client = reddit_client(String::from(TEST_USER));
+        client.concurrency = 2;
+        save_token(String::from(&client.username), token()).unwrap();
+        let _ok = mock("POST", DELETE_ENDPOINT)
+            .with_status(200)
+            .with_body("{}")
+            .expect(4)
+            .create();
+        let items: Vec<(String, String, bool)> = (0..4)
+            .map(|i| (format!("t1_{}", i), String::from("rust"), false))
+            .collect();
+        let results = Runtime::new()
+            .unwrap()
+            .block_on(async { client.delete_many(&items, None).await });
+        assert_eq!(results.len(), 4);
+        assert_eq!(results.iter().all(|r| r.is_ok()), true);
+        _ok.assert();
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_shred_and_delete() {
+        let mut client = reddit_client(String::from(TEST_USER));
+        client.concurrency = 2;
+        save_token(String::from(&client.username), token()).unwrap();
+        let shred = Shred {
+            text: Some(String::from("gone")),
+            passes: 3,
+            delay: std::time::Duration::from_millis(200),
+        };
+        let _edit = mock("POST", EDIT_ENDPOINT)
+            .match_body(Matcher::UrlEncoded("text".into(), "gone".into()))
+            .with_status(200)
+            .with_body(r#"{"json": {"errors": []}}"#)
+            .expect(3 * 3)
+            .create();
+        let _delete = mock("POST", DELETE_ENDPOINT)
+            .with_status(200)
+            .with_body("{}")
+            .expect(4)
+            .create();
+        let items: Vec<(String, String, bool)> = (0..4)
+            .map(|i| (format!("t1_{}", i), String::from("rust"), i != 3))
+            .collect();
+        let started = std::time::Instant::now();
+        let results = Runtime::new()
+            .unwrap()
+            .block_on(async { client.delete_many(&items, Some(&shred)).await });
+        // One delay between passes, however many items there are.
+        assert_eq!(
+            started.elapsed() < std::time::Duration::from_millis(3 * 200),
+            true
+        );
+        assert_eq!(results.iter().all(|r| r.is_ok()), true);
+        _edit.assert();
+        _delete.assert();
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_submit_and_comment() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let submit = mock("POST", SUBMIT_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("kind".into(), "self".into()),
+                Matcher::UrlEncoded("sr".into(), "rust".into()),
+                Matcher::UrlEncoded("title".into(), "Tools".into()),
+            ]))
+            .with_body(r#"{"json": {"errors": [], "data": {"name": "t3_new"}}}"#)
+            .create();
+        let comment = mock("POST", COMMENT_ENDPOINT)
+            .match_body(Matcher::UrlEncoded("thing_id".into(), "t3_er8pzu".into()))
+            .with_body(
+                r#"{"json": {"errors": [], "data": {"things": [{"kind": "t1", "data": {"name": "t1_new"}}]}}}"#,
+            )
+            .create();
+        let (post, reply) = Runtime::new().unwrap().block_on(async {
+            (
+                client
+                    .submit_self("rust", "Tools", "Webstorm")
+                    .await
+                    .unwrap(),
+                client.comment("t3_er8pzu", "I love rust").await.unwrap(),
+            )
+        });
+        assert_eq!(post, "t3_new");
+        assert_eq!(reply, "t1_new");
+        submit.assert();
+        comment.assert();
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_submit_rejected() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _submit = mock("POST", SUBMIT_ENDPOINT)
+            .with_body(r#"{"json": {"errors": [["SUBREDDIT_NOEXIST", "that subreddit doesn't exist", "sr"]]}}"#)
+            .create();
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { client.submit_self("nope", "Tools", "text").await });
+        match result {
+            Err(RedditApiError::PostRejected { reason }) => {
+                assert_eq!(reason, "subreddit_noexist")
+            }
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+        delete_user(&client.username).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_edit_rejected() {
+        let client = reddit_client(String::from(TEST_USER));
+        save_token(String::from(&client.username), token()).unwrap();
+        let _edit = mock("POST", EDIT_ENDPOINT)
+            .with_status(200)
+            .with_body(r#"{"json": {"errors": [["TOO_OLD", "that's too old", "parent"]]}}"#)
+            .create();
+        let res = Runtime::new()
+            .unwrap()
+            .block_on(async { client.edit("t1_a", "gone").await });
+        match res {
+            Err(RedditApiError::EditRejected { reason }) => assert_eq!(reason, "too_old"),
+            other => panic!("expected a rejected edit, got {:?}", other),
+        }
+        delete_user(&client.username).unwrap();
+    }
 }
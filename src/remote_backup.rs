@@ -0,0 +1,212 @@
+use super::config::S3Settings;
+use chrono::Utc;
+use custom_error::custom_error;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::path::PathBuf;
+use std::result;
+use url::Url;
+
+const ACCESS_KEY_ENV_VAR: &'static str = "REDELETE_S3_ACCESS_KEY_ID";
+const SECRET_KEY_ENV_VAR: &'static str = "REDELETE_S3_SECRET_ACCESS_KEY";
+const SIGNED_HEADERS: &'static str = "host;x-amz-content-sha256;x-amz-date";
+
+custom_error! {pub RemoteBackupError
+    IO{source: std::io::Error} = "IO Error",
+    Reqwest{source: reqwest::Error} = "Reqwest error",
+    Url{source: url::ParseError} = "Invalid S3 endpoint",
+    MissingCredentials{what: String} = "Missing S3 credentials: {what}",
+    Rejected{status: String, body: String} = "S3 upload rejected ({status}): {body}"
+}
+pub type Result<T> = result::Result<T, RemoteBackupError>;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_varkey(key).expect("HMAC accepts keys of any length.");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// AWS signature v4 key derivation.
+fn signing_key(secret: &str, date_stamp: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac(format!("AWS4{}", secret).as_bytes(), date_stamp);
+    let k_region = hmac(&k_date, region);
+    let k_service = hmac(&k_region, service);
+    hmac(&k_service, "aws4_request")
+}
+
+fn credentials(settings: &S3Settings) -> Result<(String, String)> {
+    let access_key = match &settings.access_key_id {
+        Some(key) => String::from(key),
+        None => {
+            env::var(ACCESS_KEY_ENV_VAR).map_err(|_| RemoteBackupError::MissingCredentials {
+                what: format!("set --s3-access-key-id or {}", ACCESS_KEY_ENV_VAR),
+            })?
+        }
+    };
+    let secret_key =
+        env::var(SECRET_KEY_ENV_VAR).map_err(|_| RemoteBackupError::MissingCredentials {
+            what: format!("set {}", SECRET_KEY_ENV_VAR),
+        })?;
+    Ok((access_key, secret_key))
+}
+
+/// Escapes everything but unreserved characters, as SigV4 canonical URIs require. Used
+/// for the request URL too, so what's signed is exactly what's sent.
+fn uri_encode(segment: &[u8]) -> String {
+    segment
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn percent_decode(segment: &str) -> Vec<u8> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// The object's path under the endpoint, keeping any base path the endpoint has, like a
+/// gateway that serves S3 under `/s3`.
+fn object_uri(endpoint: &Url, bucket: &str, object_key: &str) -> String {
+    let mut segments: Vec<String> = endpoint
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| uri_encode(&percent_decode(segment)))
+        .collect();
+    segments.push(uri_encode(bucket.as_bytes()));
+    segments.push(uri_encode(object_key.as_bytes()));
+    format!("/{}", segments.join("/"))
+}
+
+/// Path-style PUT so it works the same against AWS, MinIO, Backblaze, etc.
+pub async fn upload(settings: &S3Settings, file: &PathBuf) -> Result<String> {
+    let (access_key, secret_key) = credentials(settings)?;
+    let body = std::fs::read(file)?;
+    let object_key = file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("Backup file name is not valid unicode.");
+    let endpoint = Url::parse(&settings.endpoint)?;
+    let host = match (endpoint.host_str(), endpoint.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => String::from(host),
+        (None, _) => {
+            return Err(RemoteBackupError::Url {
+                source: url::ParseError::EmptyHost,
+            })
+        }
+    };
+    let uri = object_uri(&endpoint, &settings.bucket, object_key);
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let payload_hash = sha256_hex(&body);
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        uri, host, payload_hash, amz_date, SIGNED_HEADERS, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date_stamp, settings.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let signature = hex::encode(hmac(
+        &signing_key(&secret_key, &date_stamp, &settings.region, "s3"),
+        &string_to_sign,
+    ));
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key, scope, SIGNED_HEADERS, signature
+    );
+
+    let url = format!("{}://{}{}", endpoint.scheme(), host, uri);
+    let response = reqwest::Client::new()
+        .put(&url)
+        .header("x-amz-date", amz_date)
+        .header("x-amz-content-sha256", payload_hash)
+        .header("authorization", authorization)
+        .body(body)
+        .send()
+        .await?;
+    if response.status().is_success() {
+        Ok(url)
+    } else {
+        Err(RemoteBackupError::Rejected {
+            status: response.status().to_string(),
+            body: response.text().await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS signature v4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_object_uri() {
+        let endpoint = Url::parse("https://s3.us-east-1.amazonaws.com").unwrap();
+        assert_eq!(
+            object_uri(&endpoint, "backups", "redelete 2020-01-20.json"),
+            "/backups/redelete%202020-01-20.json"
+        );
+        assert_eq!(
+            object_uri(&endpoint, "backups", "a+b=c&d.json"),
+            "/backups/a%2Bb%3Dc%26d.json"
+        );
+        let endpoint = Url::parse("https://storage.example.com/s3/my%20store/").unwrap();
+        assert_eq!(
+            object_uri(&endpoint, "backups", "redelete.json"),
+            "/s3/my%20store/backups/redelete.json"
+        );
+    }
+}
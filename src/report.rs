@@ -0,0 +1,220 @@
+use super::audit::Item;
+use super::timezone::Zone;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// What happened during a single `run`, used for anything that reports on it after the fact.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunSummary {
+    pub username: String,
+    pub dry_run: bool,
+    /// Items fetched from reddit, before any filtering.
+    pub scanned: usize,
+    pub matched: usize,
+    pub deleted: usize,
+    pub failed: usize,
+    /// Items that matched but were left alone because of `--limit`.
+    pub remaining: usize,
+    /// Deleted items per subreddit, or matched ones for a dry run.
+    pub by_subreddit: BTreeMap<String, usize>,
+    /// Total score of the items in `by_subreddit`.
+    pub karma: i64,
+    /// `created_utc` of the oldest and newest items in `by_subreddit`.
+    pub oldest: Option<f64>,
+    pub newest: Option<f64>,
+    pub elapsed: Duration,
+    /// API calls made, fetching and deleting together.
+    pub requests: usize,
+    /// Zone the oldest and newest dates are shown in.
+    pub zone: Zone,
+}
+
+impl RunSummary {
+    /// Counts an item that was deleted, or would be for a dry run.
+    pub fn tally(&mut self, item: &Item) {
+        *self.by_subreddit.entry(item.subreddit.clone()).or_insert(0) += 1;
+        self.karma += item.score.unwrap_or(0) as i64;
+        if let Some(created) = item.created_utc {
+            if self.oldest.map_or(true, |oldest| created < oldest) {
+                self.oldest = Some(created);
+            }
+            if self.newest.map_or(true, |newest| created > newest) {
+                self.newest = Some(created);
+            }
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        if self.failed > 0 {
+            format!(
+                "redelete: {} deletion{} failed for /u/{}",
+                self.failed,
+                if self.failed == 1 { "" } else { "s" },
+                self.username
+            )
+        } else {
+            format!("redelete: run finished for /u/{}", self.username)
+        }
+    }
+
+    /// One line about what was deleted, e.g. "Deleted 8 of 10 matches, 2 failed."
+    pub fn headline(&self) -> String {
+        if self.dry_run {
+            return format!(
+                "Dry run, {} of {} scanned would be deleted.",
+                self.matched, self.scanned
+            );
+        }
+        let mut line = format!("Deleted {} of {} matches", self.deleted, self.matched);
+        if self.failed > 0 {
+            line.push_str(&format!(", {} failed", self.failed));
+        }
+        line.push('.');
+        line
+    }
+
+    /// The same details as `body`, for `--format json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "username": self.username,
+            "dry_run": self.dry_run,
+            "scanned": self.scanned,
+            "matched": self.matched,
+            "deleted": self.deleted,
+            "failed": self.failed,
+            "remaining": self.remaining,
+            "by_subreddit": self.by_subreddit,
+            "karma": self.karma,
+            "oldest_utc": self.oldest,
+            "newest_utc": self.newest,
+            "elapsed_secs": self.elapsed.as_secs_f64(),
+            "requests": self.requests,
+        })
+    }
+
+    pub fn body(&self) -> String {
+        let mut lines = vec![format!("Account: /u/{}", self.username)];
+        if self.dry_run {
+            lines.push(String::from("Dry run, nothing was deleted."));
+        }
+        lines.push(format!("Comments/submissions scanned: {}", self.scanned));
+        lines.push(format!("Comments/submissions matched: {}", self.matched));
+        lines.push(format!("Deleted: {}", self.deleted));
+        lines.push(format!("Failed: {}", self.failed));
+        if self.remaining > 0 {
+            lines.push(format!(
+                "Still matching after the limit was reached: {}",
+                self.remaining
+            ));
+        }
+        if !self.by_subreddit.is_empty() {
+            let verb = if self.dry_run {
+                "Would delete"
+            } else {
+                "Deleted"
+            };
+            lines.push(format!("{} by subreddit:", verb));
+            for (subreddit, count) in &self.by_subreddit {
+                lines.push(format!("  /r/{}: {}", subreddit, count));
+            }
+            lines.push(format!("Karma removed: {}", self.karma));
+        }
+        if let (Some(oldest), Some(newest)) = (self.oldest, self.newest) {
+            lines.push(format!("Oldest: {}", self.zone.format_timestamp(oldest)));
+            lines.push(format!("Newest: {}", self.zone.format_timestamp(newest)));
+        }
+        lines.push(format!("Elapsed: {:.1}s", self.elapsed.as_secs_f64()));
+        lines.push(format!("API calls: {}", self.requests));
+        if self.failed > 0 {
+            lines.push(format!(
+                "Run `redelete retry {}` to try the failed deletions again.",
+                self.username
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            username: "TestUser".into(),
+            matched: 10,
+            deleted: 8,
+            failed: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_subject() {
+        assert_eq!(
+            summary().subject(),
+            "redelete: 2 deletions failed for /u/TestUser"
+        );
+        let mut ok = summary();
+        ok.failed = 0;
+        assert_eq!(ok.subject(), "redelete: run finished for /u/TestUser");
+    }
+
+    #[test]
+    fn test_headline() {
+        assert_eq!(summary().headline(), "Deleted 8 of 10 matches, 2 failed.");
+        let mut dry = summary();
+        dry.dry_run = true;
+        dry.scanned = 40;
+        assert_eq!(
+            dry.headline(),
+            "Dry run, 10 of 40 scanned would be deleted."
+        );
+    }
+
+    #[test]
+    fn test_body_mentions_retry_on_failure() {
+        assert_eq!(summary().body().contains("redelete retry TestUser"), true);
+        let mut ok = summary();
+        ok.failed = 0;
+        assert_eq!(ok.body().contains("retry"), false);
+    }
+
+    #[test]
+    fn test_body_mentions_remaining() {
+        assert_eq!(summary().body().contains("Still matching"), false);
+        let mut limited = summary();
+        limited.remaining = 5;
+        assert_eq!(
+            limited
+                .body()
+                .contains("Still matching after the limit was reached: 5"),
+            true
+        );
+    }
+
+    #[test]
+    fn test_tally() {
+        let mut summary = summary();
+        for (subreddit, score, created) in &[
+            ("rust", 5, 200.0),
+            ("rust", -2, 100.0),
+            ("webdev", 1, 300.0),
+        ] {
+            let mut item = Item::new(String::from("t1_a"), String::from(*subreddit));
+            item.score = Some(*score);
+            item.created_utc = Some(*created);
+            summary.tally(&item);
+        }
+        summary.tally(&Item::new(String::from("t1_b"), String::from("rust")));
+        assert_eq!(summary.by_subreddit.get("rust"), Some(&3));
+        assert_eq!(summary.by_subreddit.get("webdev"), Some(&1));
+        assert_eq!(summary.karma, 4);
+        assert_eq!(summary.oldest, Some(100.0));
+        assert_eq!(summary.newest, Some(300.0));
+        let body = summary.body();
+        assert_eq!(body.contains("  /r/rust: 3"), true);
+        assert_eq!(body.contains("Karma removed: 4"), true);
+    }
+}
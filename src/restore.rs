@@ -0,0 +1,131 @@
+use custom_error::custom_error;
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::result;
+
+custom_error! {pub RestoreError
+    IO{source: std::io::Error} = "Unable to open the backup",
+    Serde{source: serde_json::Error} = "Unable to read the backup, it should be json from `redelete export` or a run's backup",
+}
+pub type Result<T> = result::Result<T, RestoreError>;
+
+/// How one backed up item gets posted again. Restored items are new posts, they don't keep
+/// their score, replies or age.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repost {
+    /// Submitted to the same subreddit again.
+    SelfPost {
+        subreddit: String,
+        title: String,
+        text: String,
+    },
+    /// Posted as a reply to `parent`, a comment or submission fullname.
+    Comment { parent: String, text: String },
+}
+
+/// Every item in a json file from `redelete export` or from a run's backup, with whatever
+/// fields it was saved with.
+pub fn read_items(path: &Path) -> Result<Vec<Map<String, Value>>> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// What posting `item` again takes, or why it can't be. Exports keep a comment's parent, run
+/// backups only have its permalink, so those comments go back as replies to the submission.
+pub fn repost(item: &Map<String, Value>) -> result::Result<Repost, &'static str> {
+    let text = |key: &str| {
+        item.get(key)
+            .and_then(Value::as_str)
+            .filter(|text| !text.is_empty())
+            .map(String::from)
+    };
+    let name = text("name").unwrap_or_default();
+    if name.starts_with("t1_") {
+        let body = text("body").ok_or("the backup has no text for it")?;
+        let parent = text("parent_id")
+            .or_else(|| submission_from_permalink(&text("permalink")?))
+            .ok_or("the backup doesn't say what it replied to")?;
+        Ok(Repost::Comment { parent, text: body })
+    } else if name.starts_with("t3_") {
+        let link = item.get("is_self").and_then(Value::as_bool) == Some(false)
+            || text("post_type").map_or(false, |post_type| post_type != "self");
+        if link {
+            return Err("only self posts can be restored");
+        }
+        Ok(Repost::SelfPost {
+            subreddit: text("subreddit").ok_or("the backup has no subreddit for it")?,
+            title: text("title").ok_or("the backup has no title for it")?,
+            text: text("selftext").unwrap_or_default(),
+        })
+    } else {
+        Err("it isn't a comment or submission")
+    }
+}
+
+/// The submission fullname in a permalink like /r/rust/comments/abc123/title/def456/.
+fn submission_from_permalink(permalink: &str) -> Option<String> {
+    let mut parts = permalink.split('/').skip_while(|part| *part != "comments");
+    parts
+        .nth(1)
+        .filter(|id| !id.is_empty())
+        .map(|id| format!("t3_{}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(json: &str) -> Map<String, Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_repost_comment() {
+        assert_eq!(
+            repost(&item(
+                r#"{"name": "t1_a", "body": "I love rust", "parent_id": "t1_parent"}"#
+            )),
+            Ok(Repost::Comment {
+                parent: "t1_parent".into(),
+                text: "I love rust".into()
+            })
+        );
+        // Run backups have no parent_id.
+        assert_eq!(
+            repost(&item(
+                r#"{"name": "t1_a", "body": "hi", "permalink": "/r/rust/comments/er8pzu/tools/a/"}"#
+            )),
+            Ok(Repost::Comment {
+                parent: "t3_er8pzu".into(),
+                text: "hi".into()
+            })
+        );
+        assert_eq!(
+            repost(&item(r#"{"name": "t1_a", "body": ""}"#)).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_repost_submission() {
+        assert_eq!(
+            repost(&item(
+                r#"{"name": "t3_b", "subreddit": "rust", "title": "Tools", "selftext": "Webstorm", "is_self": true}"#
+            )),
+            Ok(Repost::SelfPost {
+                subreddit: "rust".into(),
+                title: "Tools".into(),
+                text: "Webstorm".into()
+            })
+        );
+        assert_eq!(
+            repost(&item(
+                r#"{"name": "t3_b", "subreddit": "pics", "title": "Cat", "post_type": "image"}"#
+            )),
+            Err("only self posts can be restored")
+        );
+    }
+}
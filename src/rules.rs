@@ -0,0 +1,441 @@
+use super::patterns;
+use super::reddit_api::DeletionInfo;
+use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A parsed deletion rule like `score < 5 && age > 30d && subreddit != "rust"`.
+///
+/// Comparisons are `field op value`, combined with `&&`, `||`, `!` and parentheses.
+/// Number fields are `score` and `age` (a duration like `90m`, `12h`, `30d`, `2w` or `1y`,
+/// plain numbers are hours). Text fields are `subreddit`, `body`, `title`, `text` (any of
+/// the content), `domain`, `flair` and `kind` (`comment` or `submission`); they compare
+/// with `==`/`!=` ignoring case or with `~` against a regular expression. `edited`,
+/// `stickied`, `distinguished`, `controversial`, `archived` and `saved` can be used on their own.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, Value),
+    Flag(Field),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Score,
+    Age,
+    Subreddit,
+    Body,
+    Title,
+    Text,
+    Domain,
+    Flair,
+    Kind,
+    Edited,
+    Stickied,
+    Distinguished,
+    Controversial,
+    Archived,
+    Saved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Matches,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Scores, or ages in seconds.
+    Number(i64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Text(String),
+    Number(i64, Option<char>),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        match name {
+            "score" => Some(Field::Score),
+            "age" => Some(Field::Age),
+            "subreddit" => Some(Field::Subreddit),
+            "body" => Some(Field::Body),
+            "title" => Some(Field::Title),
+            "text" => Some(Field::Text),
+            "domain" => Some(Field::Domain),
+            "flair" => Some(Field::Flair),
+            "kind" => Some(Field::Kind),
+            "edited" => Some(Field::Edited),
+            "stickied" => Some(Field::Stickied),
+            "distinguished" => Some(Field::Distinguished),
+            "controversial" => Some(Field::Controversial),
+            "archived" => Some(Field::Archived),
+            "saved" => Some(Field::Saved),
+            _ => None,
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        match self {
+            Field::Score | Field::Age => true,
+            _ => false,
+        }
+    }
+
+    fn is_flag(&self) -> bool {
+        match self {
+            Field::Edited
+            | Field::Stickied
+            | Field::Distinguished
+            | Field::Controversial
+            | Field::Archived
+            | Field::Saved => true,
+            _ => false,
+        }
+    }
+}
+
+fn tokenize(rule: &str) -> result::Result<Vec<Token>, String> {
+    let chars: Vec<char> = rule.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).cloned();
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let (token, len) = match (c, next) {
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('~', _) => (Token::Op(Op::Matches), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('"', _) => {
+                let mut text = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j).cloned() {
+                        None => return Err(String::from("unterminated string")),
+                        Some('"') => break,
+                        Some('\\') if j + 1 < chars.len() => {
+                            text.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(ch) => {
+                            text.push(ch);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Text(text), j + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' => {
+                let mut j = i + 1;
+                while j < chars.len() && chars[j].is_ascii_digit() {
+                    j += 1;
+                }
+                let digits: String = chars[i..j].iter().collect();
+                let number = digits
+                    .parse::<i64>()
+                    .map_err(|_| format!("invalid number {}", digits))?;
+                let unit = match chars.get(j) {
+                    Some(u) if u.is_ascii_alphabetic() => {
+                        j += 1;
+                        Some(*u)
+                    }
+                    _ => None,
+                };
+                (Token::Number(number, unit), j - i)
+            }
+            (c, _) if c.is_ascii_alphabetic() || c == '_' => {
+                let mut j = i + 1;
+                while j < chars.len() && (chars[j].is_ascii_alphanumeric() || chars[j] == '_') {
+                    j += 1;
+                }
+                (Token::Ident(chars[i..j].iter().collect()), j - i)
+            }
+            (c, _) => return Err(format!("unexpected character {}", c)),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+fn duration_secs(number: i64, unit: Option<char>) -> result::Result<i64, String> {
+    let multiplier = match unit {
+        Some('s') => 1,
+        Some('m') => 60,
+        None | Some('h') => 3600,
+        Some('d') => 86400,
+        Some('w') => 7 * 86400,
+        Some('y') => 365 * 86400,
+        Some(u) => return Err(format!("unknown duration unit {}", u)),
+    };
+    Ok(number * multiplier)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> result::Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> result::Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> result::Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(String::from("missing )")),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                let field = Field::from_name(&name).ok_or(format!("unknown field {}", name))?;
+                self.comparison(field)
+            }
+            Some(t) => Err(format!("expected a field, found {:?}", t)),
+            None => Err(String::from("rule ends too early")),
+        }
+    }
+
+    fn comparison(&mut self, field: Field) -> result::Result<Expr, String> {
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            _ if field.is_flag() => return Ok(Expr::Flag(field)),
+            _ => return Err(format!("{:?} needs a comparison", field).to_lowercase()),
+        };
+        self.pos += 1;
+        let value = match (field, self.next()) {
+            (Field::Age, Some(Token::Number(n, unit))) => Value::Number(duration_secs(n, unit)?),
+            (Field::Score, Some(Token::Number(n, None))) => Value::Number(n),
+            (f, Some(Token::Text(text))) if !f.is_number() && !f.is_flag() => {
+                if op == Op::Matches {
+                    patterns::validate_patterns(vec![&*text].into_iter())?;
+                }
+                Value::Text(text)
+            }
+            (f, _) => return Err(format!("invalid value for {:?}", f).to_lowercase()),
+        };
+        let allowed = match value {
+            Value::Number(_) => op != Op::Matches,
+            Value::Text(_) => op == Op::Eq || op == Op::Ne || op == Op::Matches,
+        };
+        if !allowed {
+            return Err(format!("{:?} can't be compared with {:?}", field, op).to_lowercase());
+        }
+        Ok(Expr::Compare(field, op, value))
+    }
+}
+
+pub fn parse(rule: &str) -> result::Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(rule).map_err(|e| format!("Invalid rule: {}", e))?,
+        pos: 0,
+    };
+    let expr = parser.or().map_err(|e| format!("Invalid rule: {}", e))?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("Invalid rule: unexpected {:?}", t)),
+    }
+}
+
+fn texts(field: Field, info: &DeletionInfo) -> Vec<String> {
+    let content = |fields: Vec<&Option<String>>| -> Vec<String> {
+        fields.into_iter().filter_map(|f| f.clone()).collect()
+    };
+    match field {
+        Field::Subreddit => vec![info.subreddit.clone()],
+        Field::Body => content(vec![&info.body]),
+        Field::Title => content(vec![&info.title]),
+        Field::Text => content(vec![&info.body, &info.title, &info.selftext]),
+        Field::Domain => content(vec![&info.domain]),
+        Field::Flair => content(vec![&info.link_flair_text, &info.author_flair_text]),
+        Field::Kind if info.name.starts_with("t1_") => vec![String::from("comment")],
+        Field::Kind => vec![String::from("submission")],
+        _ => Vec::new(),
+    }
+}
+
+/// Evaluates `expr` for one item. `now` is the current epoch time in seconds.
+pub fn evaluate(expr: &Expr, info: &DeletionInfo, now: f64) -> bool {
+    match expr {
+        Expr::And(a, b) => evaluate(a, info, now) && evaluate(b, info, now),
+        Expr::Or(a, b) => evaluate(a, info, now) || evaluate(b, info, now),
+        Expr::Not(e) => !evaluate(e, info, now),
+        Expr::Flag(field) => match field {
+            Field::Edited => info.edited,
+            Field::Stickied => info.stickied,
+            Field::Distinguished => info.distinguished.is_some(),
+            Field::Controversial => info.controversial,
+            Field::Archived => info.archived,
+            Field::Saved => info.saved,
+            _ => false,
+        },
+        Expr::Compare(field, op, Value::Number(n)) => {
+            let actual = match field {
+                Field::Age => (now - info.created_utc) as i64,
+                _ => info.score as i64,
+            };
+            match op {
+                Op::Eq => actual == *n,
+                Op::Ne => actual != *n,
+                Op::Lt => actual < *n,
+                Op::Le => actual <= *n,
+                Op::Gt => actual > *n,
+                Op::Ge => actual >= *n,
+                Op::Matches => false,
+            }
+        }
+        Expr::Compare(field, op, Value::Text(expected)) => {
+            let actual = texts(*field, info);
+            match op {
+                Op::Eq => actual.iter().any(|t| t.eq_ignore_ascii_case(expected)),
+                Op::Ne => !actual.iter().any(|t| t.eq_ignore_ascii_case(expected)),
+                Op::Matches => {
+                    let pattern = vec![expected.clone()];
+                    actual
+                        .iter()
+                        .any(|t| patterns::matches_any(&pattern, t).unwrap_or(false))
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
+/// True if the parsed `rule` allows deleting `info`.
+pub fn matches(rule: &Expr, info: &DeletionInfo) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Couldn't get systemtime")
+        .as_secs_f64();
+    evaluate(rule, info, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: f64 = 1_600_000_000.0;
+
+    fn comment(score: i32, days_old: f64, subreddit: &str, body: &str) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: "t1_abc".into(),
+            created_utc: NOW - days_old * 86400.0,
+            subreddit: subreddit.into(),
+            score,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some(body.into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    fn eval(rule: &str, info: &DeletionInfo) -> bool {
+        evaluate(&parse(rule).unwrap(), info, NOW)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("score <").is_err(), true);
+        assert_eq!(parse("karma > 5").is_err(), true);
+        assert_eq!(parse("score ~ \"5\"").is_err(), true);
+        assert_eq!(parse("subreddit > \"rust\"").is_err(), true);
+        assert_eq!(parse("(score < 5").is_err(), true);
+        assert_eq!(parse("body ~ \"(unclosed\"").is_err(), true);
+        assert_eq!(parse("age > 5q").is_err(), true);
+        assert_eq!(parse("score < 5 score").is_err(), true);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let rule = "score < 5 && age > 30d && subreddit != \"rust\"";
+        assert_eq!(eval(rule, &comment(1, 40.0, "webdev", "")), true);
+        assert_eq!(eval(rule, &comment(1, 40.0, "Rust", "")), false);
+        assert_eq!(eval(rule, &comment(10, 40.0, "webdev", "")), false);
+        assert_eq!(eval(rule, &comment(1, 10.0, "webdev", "")), false);
+    }
+
+    #[test]
+    fn test_precedence_and_negation() {
+        let info = comment(1, 1.0, "rust", "I love rust");
+        assert_eq!(
+            eval("score > 5 || score < 2 && kind == \"comment\"", &info),
+            true
+        );
+        assert_eq!(
+            eval("(score > 5 || score < 2) && kind == \"submission\"", &info),
+            false
+        );
+        assert_eq!(eval("!edited && body ~ \"(?i)LOVE\"", &info), true);
+        assert_eq!(eval("text ~ \"hate\" || age >= 2d", &info), false);
+    }
+}
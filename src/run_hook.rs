@@ -0,0 +1,91 @@
+use super::filter_hook::shell;
+use super::report::RunSummary;
+use custom_error::custom_error;
+use std::result;
+
+custom_error! {pub RunHookError
+    IO{source: std::io::Error} = "Unable to start the hook command",
+    Failed{command: String, status: String} = "Hook command `{command}` failed ({status})",
+}
+pub type Result<T> = result::Result<T, RunHookError>;
+
+/// What the pre run hook gets to know about the run that's about to start.
+pub fn pre_run_env(username: &str, dry_run: bool) -> Vec<(&'static str, String)> {
+    vec![
+        ("REDELETE_HOOK", String::from("pre")),
+        ("REDELETE_USERNAME", String::from(username)),
+        ("REDELETE_DRY_RUN", flag(dry_run)),
+    ]
+}
+
+/// The run's results, for the post run hook.
+pub fn post_run_env(summary: &RunSummary) -> Vec<(&'static str, String)> {
+    vec![
+        ("REDELETE_HOOK", String::from("post")),
+        ("REDELETE_USERNAME", String::from(&summary.username)),
+        ("REDELETE_DRY_RUN", flag(summary.dry_run)),
+        ("REDELETE_SCANNED", summary.scanned.to_string()),
+        ("REDELETE_MATCHED", summary.matched.to_string()),
+        ("REDELETE_DELETED", summary.deleted.to_string()),
+        ("REDELETE_FAILED", summary.failed.to_string()),
+        ("REDELETE_REMAINING", summary.remaining.to_string()),
+        ("REDELETE_REQUESTS", summary.requests.to_string()),
+        (
+            "REDELETE_ELAPSED_SECS",
+            summary.elapsed.as_secs().to_string(),
+        ),
+    ]
+}
+
+fn flag(value: bool) -> String {
+    String::from(if value { "1" } else { "0" })
+}
+
+/// Runs `command` through the shell with `env` added to its environment. Its output goes
+/// straight to ours, and it fails unless the command exits with status 0.
+pub fn run(command: &str, env: &[(&'static str, String)]) -> Result<()> {
+    let status = shell(command).envs(env.iter().cloned()).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(RunHookError::Failed {
+            command: String::from(command),
+            status: status.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_post_run_env() {
+        let summary = RunSummary {
+            username: "HookUser".into(),
+            matched: 4,
+            deleted: 3,
+            failed: 1,
+            elapsed: Duration::from_secs(12),
+            ..Default::default()
+        };
+        let env = post_run_env(&summary);
+        let get = |key| env.iter().find(|(k, _)| *k == key).unwrap().1.as_str();
+        assert_eq!(get("REDELETE_HOOK"), "post");
+        assert_eq!(get("REDELETE_USERNAME"), "HookUser");
+        assert_eq!(get("REDELETE_DRY_RUN"), "0");
+        assert_eq!(get("REDELETE_DELETED"), "3");
+        assert_eq!(get("REDELETE_FAILED"), "1");
+        assert_eq!(get("REDELETE_ELAPSED_SECS"), "12");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_run_hook() {
+        let env = pre_run_env("HookUser", true);
+        run("test \"$REDELETE_USERNAME\" = HookUser", &env).unwrap();
+        run("test \"$REDELETE_DRY_RUN\" = 1", &env).unwrap();
+        assert_eq!(run("exit 3", &env).is_err(), true);
+    }
+}
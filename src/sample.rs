@@ -0,0 +1,86 @@
+use super::reddit_api::DeletionInfo;
+
+const SUBREDDITS: [&str; 5] = ["rust", "pics", "AskReddit", "webdev", "news"];
+/// Two years, in hours.
+const SPREAD_HOURS: usize = 2 * 365 * 24;
+/// reddit archives items after six months.
+const ARCHIVED_HOURS: usize = 183 * 24;
+
+/// Made up comments and submissions for `redelete simulate --sample`, spread over the two
+/// years before `now` across a few subreddits with a mix of scores. Every third item is a
+/// submission, alternating between self and link posts. The same `count` and `now` always
+/// give the same items.
+pub fn history(count: usize, now: f64) -> Vec<DeletionInfo> {
+    (0..count).map(|i| item(i, now)).collect()
+}
+
+fn item(i: usize, now: f64) -> DeletionInfo {
+    let subreddit = SUBREDDITS[i % SUBREDDITS.len()];
+    // Multiplying by a prime scatters the ages without needing a random number generator.
+    let hours_ago = (i * 97) % SPREAD_HOURS;
+    let comment = i % 3 != 0;
+    let link = !comment && i % 2 == 0;
+    let id = format!("s{:x}", i);
+    DeletionInfo {
+        saved: false,
+        name: format!("{}{}", if comment { "t1_" } else { "t3_" }, id),
+        created_utc: now - (hours_ago * 3600) as f64,
+        subreddit: String::from(subreddit),
+        score: ((i * 31) % 60) as i32 - 10,
+        selftext: match (comment, link) {
+            (false, false) => Some(format!("Sample self post text {}.", i)),
+            _ => None,
+        },
+        url: if link {
+            Some(format!("https://example.com/{}", i))
+        } else {
+            None
+        },
+        title: if comment {
+            None
+        } else {
+            Some(format!("Sample submission {}", i))
+        },
+        body: if comment {
+            Some(format!("Sample comment {} in /r/{}.", i, subreddit))
+        } else {
+            None
+        },
+        distinguished: None,
+        stickied: false,
+        domain: if link {
+            Some(String::from("example.com"))
+        } else {
+            None
+        },
+        link_flair_text: None,
+        author_flair_text: None,
+        edited: i % 7 == 0,
+        controversial: comment && i % 11 == 0,
+        archived: hours_ago >= ARCHIVED_HOURS,
+        quarantined: false,
+        post_type: match (comment, link) {
+            (true, _) => None,
+            (false, true) => Some(String::from("link")),
+            (false, false) => Some(String::from("self")),
+        },
+        permalink: format!("/r/{}/comments/sample/{}/", subreddit, id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_history() {
+        let items = history(100, 1579506505.0);
+        assert_eq!(items.len(), 100);
+        assert_eq!(items.iter().filter(|i| i.body.is_some()).count(), 66);
+        assert_eq!(items.iter().any(|i| i.archived), true);
+        assert_eq!(items.iter().any(|i| i.score < 0), true);
+        assert_eq!(items[0].name, "t3_s0");
+        assert_eq!(items[0].post_type, Some(String::from("link")));
+        assert_eq!(history(100, 1579506505.0)[7].name, items[7].name);
+    }
+}
@@ -0,0 +1,146 @@
+use super::config::config_dir;
+use super::reddit_api::DeletionInfo;
+use custom_error::custom_error;
+use rhai::{Engine, Scope, AST};
+use std::path::PathBuf;
+use std::result;
+
+#[cfg(test)]
+use serial_test::serial;
+
+/// Keeps a runaway script from hanging a run.
+const MAX_OPERATIONS: u64 = 100_000;
+
+custom_error! {pub ScriptError
+    IO{source: std::io::Error} = "IO Error",
+    Parse{message: String} = "Unable to compile script: {message}",
+    Eval{message: String} = "Script failed: {message}",
+}
+pub type Result<T> = result::Result<T, ScriptError>;
+
+/// Where the script for `username` is kept, so it travels with the rest of the config.
+pub fn script_file_path(username: &str) -> PathBuf {
+    let mut path = config_dir();
+    path.push("scripts");
+    std::fs::create_dir_all(&path).expect("Unable to create scripts directory.");
+    path.push(format!("{}.rhai", username));
+    path
+}
+
+/// Copies a script into the config directory after checking that it compiles.
+pub fn install(username: &str, source: &PathBuf) -> Result<PathBuf> {
+    Script::compile(&std::fs::read_to_string(source)?)?;
+    let path = script_file_path(username);
+    std::fs::copy(source, &path)?;
+    Ok(path)
+}
+
+/// A compiled Rhai script defining `fn should_delete(item)`, where `item` is a map with
+/// the fields of `DeletionInfo`. Rhai can't touch the filesystem or network, and
+/// evaluation is capped at `MAX_OPERATIONS`.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    fn compile(source: &str) -> Result<Script> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|e| ScriptError::Parse {
+            message: e.to_string(),
+        })?;
+        Ok(Script { engine, ast })
+    }
+
+    pub fn load(path: &str) -> Result<Script> {
+        Script::compile(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn should_delete(&self, info: &DeletionInfo) -> Result<bool> {
+        let item = rhai::serde::to_dynamic(info).map_err(|e| ScriptError::Eval {
+            message: e.to_string(),
+        })?;
+        self.engine
+            .call_fn(&mut Scope::new(), &self.ast, "should_delete", (item,))
+            .map_err(|e| ScriptError::Eval {
+                message: e.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::prelude::*;
+
+    fn comment(score: i32, subreddit: &str) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: "t1_abc".into(),
+            created_utc: 0.0,
+            subreddit: subreddit.into(),
+            score,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some("hello".into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_should_delete() {
+        let script = Script::compile(
+            r#"
+            fn should_delete(item) {
+                item.score < 5 && item.subreddit != "rust"
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(script.should_delete(&comment(1, "webdev")).unwrap(), true);
+        assert_eq!(script.should_delete(&comment(1, "rust")).unwrap(), false);
+        assert_eq!(script.should_delete(&comment(10, "webdev")).unwrap(), false);
+    }
+
+    #[test]
+    fn test_bad_scripts() {
+        assert_eq!(
+            Script::compile("fn should_delete(item) { item.score > }").is_err(),
+            true
+        );
+        let missing = Script::compile("fn other(item) { true }").unwrap();
+        assert_eq!(missing.should_delete(&comment(1, "webdev")).is_err(), true);
+        let endless = Script::compile("fn should_delete(item) { loop {} }").unwrap();
+        assert_eq!(endless.should_delete(&comment(1, "webdev")).is_err(), true);
+    }
+
+    #[test]
+    #[serial]
+    fn test_install() {
+        let mut source = std::env::temp_dir();
+        source.push("redelete-test-script.rhai");
+        File::create(&source)
+            .unwrap()
+            .write_all(b"fn should_delete(item) { true }")
+            .unwrap();
+        let path = install("ScriptTestUser", &source).unwrap();
+        assert_eq!(path.exists(), true);
+        let script = Script::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(script.should_delete(&comment(1, "rust")).unwrap(), true);
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(source).unwrap();
+    }
+}
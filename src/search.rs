@@ -0,0 +1,118 @@
+use super::patterns;
+use super::reddit_api::DeletionInfo;
+use regex::{Regex, RegexBuilder};
+use std::result;
+
+/// What `redelete search` looks for in an account's history.
+#[derive(Debug, Clone)]
+pub struct Search {
+    text: Regex,
+    /// Entries in the same syntax as `--only-subreddits`.
+    pub subreddits: Option<Vec<String>>,
+    /// Epoch seconds. Items created before `after`, or at or after `before`, are left out.
+    pub after: Option<f64>,
+    pub before: Option<f64>,
+}
+
+impl Search {
+    /// Searches for `query` as plain text, or as a regular expression when `regex` is set.
+    /// Either way case is ignored.
+    pub fn new(query: &str, regex: bool) -> result::Result<Search, String> {
+        let pattern = if regex {
+            String::from(query)
+        } else {
+            regex::escape(query)
+        };
+        let text = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("Invalid regex {}: {}", query, e))?;
+        Ok(Search {
+            text,
+            subreddits: None,
+            after: None,
+            before: None,
+        })
+    }
+
+    /// Looks in a comment's body, or a submission's title, text and link.
+    pub fn matches(&self, item: &DeletionInfo) -> bool {
+        if let Some(subreddits) = &self.subreddits {
+            if !patterns::subreddit_matches(subreddits, &item.subreddit).unwrap_or(false) {
+                return false;
+            }
+        }
+        if self.after.map_or(false, |after| item.created_utc < after)
+            || self
+                .before
+                .map_or(false, |before| item.created_utc >= before)
+        {
+            return false;
+        }
+        [&item.body, &item.title, &item.selftext, &item.url]
+            .iter()
+            .filter_map(|text| text.as_ref())
+            .any(|text| self.text.is_match(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(subreddit: &str, body: &str, created_utc: f64) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: "t1_abc".into(),
+            created_utc,
+            subreddit: subreddit.into(),
+            score: 1,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some(body.into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_plain_text_search() {
+        let search = Search::new("a.b", false).unwrap();
+        assert_eq!(search.matches(&comment("rust", "see A.B here", 0.0)), true);
+        assert_eq!(search.matches(&comment("rust", "see axb here", 0.0)), false);
+    }
+
+    #[test]
+    fn test_regex_search() {
+        let search = Search::new(r"\bcrate[s]?\b", true).unwrap();
+        assert_eq!(search.matches(&comment("rust", "Two Crates", 0.0)), true);
+        assert_eq!(search.matches(&comment("rust", "crated", 0.0)), false);
+        assert_eq!(Search::new("(", true).is_err(), true);
+    }
+
+    #[test]
+    fn test_subreddit_and_date_filters() {
+        let mut search = Search::new("rust", false).unwrap();
+        search.subreddits = Some(vec!["rust*".into()]);
+        search.after = Some(100.0);
+        search.before = Some(200.0);
+        assert_eq!(search.matches(&comment("rust", "rust", 150.0)), true);
+        assert_eq!(
+            search.matches(&comment("rust_gamedev", "rust", 100.0)),
+            true
+        );
+        assert_eq!(search.matches(&comment("pics", "rust", 150.0)), false);
+        assert_eq!(search.matches(&comment("rust", "rust", 99.0)), false);
+        assert_eq!(search.matches(&comment("rust", "rust", 200.0)), false);
+    }
+}
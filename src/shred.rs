@@ -0,0 +1,73 @@
+use super::reddit_api::DeletionInfo;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::time::Duration;
+
+/// Overwrites an item's text before it's deleted, so sites that archive reddit only keep
+/// the replacement instead of the original.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shred {
+    /// Written over every item. Random text is used when this isn't set.
+    pub text: Option<String>,
+    /// How many times the text is overwritten before the delete, at least once.
+    pub passes: usize,
+    /// Pause between two passes of overwriting every item.
+    pub delay: Duration,
+}
+
+impl Shred {
+    pub fn replacement(&self) -> String {
+        match &self.text {
+            Some(text) => text.clone(),
+            None => random_text(),
+        }
+    }
+}
+
+/// A few words of random letters and digits.
+fn random_text() -> String {
+    let mut rng = rand::thread_rng();
+    let words = rng.gen_range(4, 12);
+    (0..words)
+        .map(|_| {
+            let len = rng.gen_range(2, 10);
+            rng.sample_iter(&Alphanumeric).take(len).collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Comments and self posts can be edited unless reddit archived them. Links can't be.
+pub fn can_shred(info: &DeletionInfo) -> bool {
+    if info.archived {
+        return false;
+    }
+    info.body.is_some() || info.post_type.as_ref().map(|t| t.as_str()) == Some("self")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replacement() {
+        let shred = Shred {
+            text: Some(String::from("[removed]")),
+            passes: 1,
+            delay: Duration::from_secs(0),
+        };
+        assert_eq!(shred.replacement(), "[removed]");
+        let shred = Shred {
+            text: None,
+            passes: 1,
+            delay: Duration::from_secs(0),
+        };
+        let first = shred.replacement();
+        assert_eq!(first.is_empty(), false);
+        assert_eq!(
+            first.chars().all(|c| c.is_ascii_alphanumeric() || c == ' '),
+            true
+        );
+        assert_ne!(first, shred.replacement());
+    }
+}
@@ -0,0 +1,260 @@
+use super::config::config_dir;
+use super::reddit_api::DeletionInfo;
+use custom_error::custom_error;
+use rusqlite::{params, Connection, NO_PARAMS};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+use serial_test::serial;
+
+custom_error! {pub StateError
+    Sqlite{source: rusqlite::Error} = "State database error",
+    Serde{source: serde_json::Error} = "Unable to read an item in the trash",
+}
+pub type Result<T> = result::Result<T, StateError>;
+
+/// What happened to an item in an earlier run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Deleted,
+    /// The user chose to keep it, so later runs skip it without checking the filters.
+    Kept,
+}
+
+impl Decision {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Deleted => "deleted",
+            Decision::Kept => "kept",
+        }
+    }
+}
+
+fn state_file_path(username: &str) -> PathBuf {
+    let mut path = config_dir();
+    path.push("state");
+    std::fs::create_dir_all(&path).expect("Unable to create state directory.");
+    path.push(format!("{}.sqlite", username));
+    path
+}
+
+fn open(username: &str) -> Result<Connection> {
+    let conn = Connection::open(state_file_path(username))?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS items (
+            name TEXT PRIMARY KEY,
+            subreddit TEXT NOT NULL,
+            decision TEXT NOT NULL,
+            decided_at INTEGER NOT NULL
+        )",
+        NO_PARAMS,
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pending (
+            name TEXT PRIMARY KEY,
+            item TEXT NOT NULL,
+            marked_at INTEGER NOT NULL
+        )",
+        NO_PARAMS,
+    )?;
+    Ok(conn)
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Couldn't get systemtime")
+        .as_secs() as i64
+}
+
+/// An item `run --trash` picked to delete later, kept with everything needed to delete it.
+#[derive(Debug)]
+pub struct PendingItem {
+    pub item: DeletionInfo,
+    /// Epoch seconds.
+    pub marked_at: u64,
+}
+
+/// Saves the decision for every (fullname, subreddit) pair, replacing earlier ones.
+pub fn record(username: &str, items: &Vec<(String, String)>, decision: Decision) -> Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    let mut conn = open(username)?;
+    let decided_at = now();
+    let tx = conn.transaction()?;
+    for (name, subreddit) in items {
+        tx.execute(
+            "INSERT OR REPLACE INTO items (name, subreddit, decision, decided_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![name, subreddit, decision.as_str(), decided_at],
+        )?;
+        // Deleted or kept, either way it's out of the trash.
+        tx.execute("DELETE FROM pending WHERE name = ?1", params![name])?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Fullnames the user chose to keep.
+pub fn kept(username: &str) -> Result<HashSet<String>> {
+    decided(username, Decision::Kept)
+}
+
+/// Fullnames earlier runs deleted. Archives and backfills still list them, and there's no
+/// point deleting them again.
+pub fn deleted(username: &str) -> Result<HashSet<String>> {
+    decided(username, Decision::Deleted)
+}
+
+fn decided(username: &str, decision: Decision) -> Result<HashSet<String>> {
+    let conn = open(username)?;
+    let mut statement = conn.prepare("SELECT name FROM items WHERE decision = ?1")?;
+    let names = statement
+        .query_map(params![decision.as_str()], |row| row.get(0))?
+        .collect::<rusqlite::Result<HashSet<String>>>()?;
+    Ok(names)
+}
+
+/// Puts `items` in the trash. Items already there keep the time they were first marked.
+/// Returns how many were new.
+pub fn mark_pending(username: &str, items: &[DeletionInfo]) -> Result<usize> {
+    let mut conn = open(username)?;
+    let marked_at = now();
+    let tx = conn.transaction()?;
+    let mut marked = 0;
+    for item in items {
+        marked += tx.execute(
+            "INSERT OR IGNORE INTO pending (name, item, marked_at) VALUES (?1, ?2, ?3)",
+            params![item.name, serde_json::to_string(item)?, marked_at],
+        )?;
+    }
+    tx.commit()?;
+    Ok(marked)
+}
+
+/// Everything in the trash, oldest first.
+pub fn pending(username: &str) -> Result<Vec<PendingItem>> {
+    let conn = open(username)?;
+    let mut statement = conn.prepare("SELECT item, marked_at FROM pending ORDER BY marked_at")?;
+    let rows = statement
+        .query_map(NO_PARAMS, |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<(String, i64)>>>()?;
+    let mut items = Vec::new();
+    for (item, marked_at) in rows {
+        items.push(PendingItem {
+            item: serde_json::from_str(&item)?,
+            marked_at: marked_at as u64,
+        });
+    }
+    Ok(items)
+}
+
+/// Takes these fullnames out of the trash, returning how many were in it.
+pub fn unmark_pending(username: &str, names: &[String]) -> Result<usize> {
+    let conn = open(username)?;
+    let mut removed = 0;
+    for name in names {
+        removed += conn.execute("DELETE FROM pending WHERE name = ?1", params![name])?;
+    }
+    Ok(removed)
+}
+
+/// Drops any saved decision for these fullnames, returning how many there were.
+pub fn forget(username: &str, names: &Vec<String>) -> Result<usize> {
+    let conn = open(username)?;
+    let mut removed = 0;
+    for name in names {
+        removed += conn.execute("DELETE FROM items WHERE name = ?1", params![name])?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TEST_USER: &'static str = "StateTestUser";
+
+    fn cleanup() {
+        let _ = std::fs::remove_file(state_file_path(TEST_USER));
+    }
+
+    #[test]
+    #[serial]
+    fn test_kept_items() {
+        cleanup();
+        assert_eq!(kept(TEST_USER).unwrap().is_empty(), true);
+        let items = vec![
+            (String::from("t1_a"), String::from("rust")),
+            (String::from("t3_b"), String::from("webdev")),
+        ];
+        record(TEST_USER, &items, Decision::Kept).unwrap();
+        record(TEST_USER, &items[..1].to_vec(), Decision::Deleted).unwrap();
+        let kept_names = kept(TEST_USER).unwrap();
+        assert_eq!(kept_names.len(), 1);
+        assert_eq!(kept_names.contains("t3_b"), true);
+        let deleted_names = deleted(TEST_USER).unwrap();
+        assert_eq!(deleted_names.len(), 1);
+        assert_eq!(deleted_names.contains("t1_a"), true);
+        assert_eq!(
+            forget(TEST_USER, &vec!["t3_b".into(), "t1_z".into()]).unwrap(),
+            1
+        );
+        assert_eq!(kept(TEST_USER).unwrap().is_empty(), true);
+        cleanup();
+    }
+
+    fn item(name: &str) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: name.into(),
+            created_utc: 1579506505.0,
+            subreddit: "rust".into(),
+            score: 1,
+            selftext: None,
+            url: None,
+            title: None,
+            body: Some("I love rust".into()),
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_pending_items() {
+        cleanup();
+        assert_eq!(
+            mark_pending(TEST_USER, &[item("t1_a"), item("t1_b")]).unwrap(),
+            2
+        );
+        assert_eq!(mark_pending(TEST_USER, &[item("t1_a")]).unwrap(), 0);
+        let trash = pending(TEST_USER).unwrap();
+        assert_eq!(trash.len(), 2);
+        assert_eq!(trash[0].item.body, Some("I love rust".into()));
+        assert_eq!(unmark_pending(TEST_USER, &["t1_b".into()]).unwrap(), 1);
+        // Deleting an item takes it out of the trash.
+        record(
+            TEST_USER,
+            &vec![(String::from("t1_a"), String::from("rust"))],
+            Decision::Deleted,
+        )
+        .unwrap();
+        assert_eq!(pending(TEST_USER).unwrap().is_empty(), true);
+        cleanup();
+    }
+}
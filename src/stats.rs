@@ -0,0 +1,188 @@
+use super::reddit_api::DeletionInfo;
+use super::timezone::Zone;
+use std::collections::BTreeMap;
+
+/// Comments, submissions and karma in one subreddit.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Counts {
+    pub comments: usize,
+    pub submissions: usize,
+    pub karma: i64,
+}
+
+impl Counts {
+    fn add(&mut self, item: &DeletionInfo) {
+        if item.body.is_some() {
+            self.comments += 1;
+        } else {
+            self.submissions += 1;
+        }
+        self.karma += item.score as i64;
+    }
+
+    fn total(&self) -> usize {
+        self.comments + self.submissions
+    }
+}
+
+/// What's in an account's history, for `redelete stats`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HistoryStats {
+    pub username: String,
+    pub totals: Counts,
+    pub by_subreddit: BTreeMap<String, Counts>,
+    /// `created_utc` of the oldest and newest items.
+    pub oldest: Option<f64>,
+    pub newest: Option<f64>,
+    /// Items a run would delete right now.
+    pub matching: usize,
+    /// Zone the oldest and newest dates are shown in.
+    pub zone: Zone,
+}
+
+impl HistoryStats {
+    pub fn new(
+        username: &str,
+        items: &[DeletionInfo],
+        matching: usize,
+        zone: Zone,
+    ) -> HistoryStats {
+        let mut stats = HistoryStats {
+            username: String::from(username),
+            matching,
+            zone,
+            ..Default::default()
+        };
+        for item in items {
+            stats.totals.add(item);
+            stats
+                .by_subreddit
+                .entry(item.subreddit.clone())
+                .or_insert_with(Counts::default)
+                .add(item);
+            if stats
+                .oldest
+                .map_or(true, |oldest| item.created_utc < oldest)
+            {
+                stats.oldest = Some(item.created_utc);
+            }
+            if stats
+                .newest
+                .map_or(true, |newest| item.created_utc > newest)
+            {
+                stats.newest = Some(item.created_utc);
+            }
+        }
+        stats
+    }
+
+    pub fn body(&self) -> String {
+        let mut lines = vec![format!("Account: /u/{}", self.username)];
+        lines.push(format!("Comments: {}", self.totals.comments));
+        lines.push(format!("Submissions: {}", self.totals.submissions));
+        lines.push(format!("Karma: {}", self.totals.karma));
+        if let (Some(oldest), Some(newest)) = (self.oldest, self.newest) {
+            lines.push(format!("Oldest: {}", self.zone.format_timestamp(oldest)));
+            lines.push(format!("Newest: {}", self.zone.format_timestamp(newest)));
+        }
+        lines.push(format!(
+            "Matching your filters: {} of {}",
+            self.matching,
+            self.totals.total()
+        ));
+        if !self.by_subreddit.is_empty() {
+            lines.push(String::new());
+            lines.extend(self.subreddit_table());
+        }
+        lines.join("\n")
+    }
+
+    /// One row per subreddit, busiest first.
+    fn subreddit_table(&self) -> Vec<String> {
+        let mut rows: Vec<(&String, &Counts)> = self.by_subreddit.iter().collect();
+        rows.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(b.0)));
+        let width = rows
+            .iter()
+            .map(|(subreddit, _)| subreddit.len() + 3)
+            .max()
+            .unwrap_or(0)
+            .max(9);
+        let mut lines = vec![format!(
+            "{:width$}  {:>8}  {:>11}  {:>7}",
+            "SUBREDDIT",
+            "COMMENTS",
+            "SUBMISSIONS",
+            "KARMA",
+            width = width
+        )];
+        for (subreddit, counts) in rows {
+            lines.push(format!(
+                "{:width$}  {:>8}  {:>11}  {:>7}",
+                format!("/r/{}", subreddit),
+                counts.comments,
+                counts.submissions,
+                counts.karma,
+                width = width
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(subreddit: &str, comment: bool, score: i32, created_utc: f64) -> DeletionInfo {
+        DeletionInfo {
+            saved: false,
+            name: "t1_abc".into(),
+            created_utc,
+            subreddit: subreddit.into(),
+            score,
+            selftext: None,
+            url: None,
+            title: if comment { None } else { Some("title".into()) },
+            body: if comment { Some("body".into()) } else { None },
+            distinguished: None,
+            stickied: false,
+            domain: None,
+            link_flair_text: None,
+            author_flair_text: None,
+            edited: false,
+            controversial: false,
+            archived: false,
+            quarantined: false,
+            post_type: None,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_history_stats() {
+        let items = vec![
+            item("rust", true, 5, 300.0),
+            item("rust", false, 10, 100.0),
+            item("pics", true, -2, 200.0),
+            item("rust", true, 1, 400.0),
+        ];
+        let stats = HistoryStats::new("StatsUser", &items, 2, Zone::Local);
+        assert_eq!(stats.totals.comments, 3);
+        assert_eq!(stats.totals.submissions, 1);
+        assert_eq!(stats.totals.karma, 14);
+        assert_eq!(stats.oldest, Some(100.0));
+        assert_eq!(stats.newest, Some(400.0));
+        assert_eq!(
+            stats.by_subreddit["rust"],
+            Counts {
+                comments: 2,
+                submissions: 1,
+                karma: 16
+            }
+        );
+        let body = stats.body();
+        assert_eq!(body.contains("Matching your filters: 2 of 4"), true);
+        // Busiest subreddit first.
+        assert_eq!(body.find("/r/rust") < body.find("/r/pics"), true);
+    }
+}
@@ -0,0 +1,225 @@
+use super::cron::Schedule;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use std::fmt::Display;
+use std::result;
+
+const DATE_FORMAT: &'static str = "%Y-%m-%d %H:%M %Z";
+const MONTH_FORMAT: &'static str = "%Y-%m";
+
+/// The zone dates are shown in and `--at` times are interpreted in. Accounts without a
+/// configured time zone use the system's local time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Default for Zone {
+    fn default() -> Zone {
+        Zone::Local
+    }
+}
+
+impl Zone {
+    pub fn from_config(timezone: &Option<String>) -> result::Result<Zone, String> {
+        match timezone {
+            None => Ok(Zone::Local),
+            Some(name) => Ok(Zone::Named(parse_timezone(name)?)),
+        }
+    }
+
+    /// Formats a reddit `created_utc` style epoch timestamp.
+    pub fn format_timestamp(&self, epoch_secs: f64) -> String {
+        match self {
+            Zone::Local => format_in(&Local, epoch_secs, DATE_FORMAT),
+            Zone::Named(tz) => format_in(tz, epoch_secs, DATE_FORMAT),
+        }
+    }
+
+    /// The year and month of an epoch timestamp, like "2020-01".
+    pub fn format_month(&self, epoch_secs: f64) -> String {
+        match self {
+            Zone::Local => format_in(&Local, epoch_secs, MONTH_FORMAT),
+            Zone::Named(tz) => format_in(tz, epoch_secs, MONTH_FORMAT),
+        }
+    }
+
+    /// The next time the wall clock in this zone reads `at`, strictly after `now`.
+    pub fn next_occurrence(&self, at: NaiveTime, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Zone::Local => next_occurrence_in(&Local, at, now),
+            Zone::Named(tz) => next_occurrence_in(tz, at, now),
+        }
+    }
+
+    /// The next minute `schedule` runs at on this zone's wall clock, strictly after `now`.
+    /// `None` for schedules that never run, like the 31st of February.
+    pub fn next_scheduled(&self, schedule: &Schedule, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Local => next_scheduled_in(&Local, schedule, now),
+            Zone::Named(tz) => next_scheduled_in(tz, schedule, now),
+        }
+    }
+
+    /// Epoch seconds of the start of `date` on this zone's wall clock.
+    pub fn start_of_day(&self, date: NaiveDate) -> f64 {
+        match self {
+            Zone::Local => start_of_day_in(&Local, date),
+            Zone::Named(tz) => start_of_day_in(tz, date),
+        }
+    }
+}
+
+pub fn parse_timezone(name: &str) -> result::Result<Tz, String> {
+    name.parse::<Tz>().map_err(|_| {
+        format!(
+            "Unknown time zone {}. Use a name like America/New_York.",
+            name
+        )
+    })
+}
+
+pub fn parse_time_of_day(time: &str) -> result::Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid time {}. Use 24 hour HH:MM, e.g. 03:00.", time))
+}
+
+pub fn parse_date(date: &str) -> result::Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date {}. Use YYYY-MM-DD, e.g. 2020-01-31.", date))
+}
+
+fn format_in<Z: TimeZone>(zone: &Z, epoch_secs: f64, format: &str) -> String
+where
+    Z::Offset: Display,
+{
+    zone.timestamp(epoch_secs as i64, 0)
+        .format(format)
+        .to_string()
+}
+
+/// Walks forward a day at a time so times skipped by a DST change fall through to the next day.
+fn next_occurrence_in<Z: TimeZone>(zone: &Z, at: NaiveTime, now: DateTime<Utc>) -> DateTime<Utc> {
+    let mut day = now.with_timezone(zone).date().naive_local();
+    loop {
+        if let Some(candidate) = zone.from_local_datetime(&day.and_time(at)).earliest() {
+            let candidate = candidate.with_timezone(&Utc);
+            if candidate > now {
+                return candidate;
+            }
+        }
+        day = day.succ();
+    }
+}
+
+/// Some zones skip midnight when DST starts, the day then starts an hour later.
+fn start_of_day_in<Z: TimeZone>(zone: &Z, date: NaiveDate) -> f64 {
+    (0..2)
+        .filter_map(|hour| {
+            zone.from_local_datetime(&date.and_hms(hour, 0, 0))
+                .earliest()
+        })
+        .next()
+        .map_or(date.and_hms(0, 0, 0).timestamp(), |start| start.timestamp()) as f64
+}
+
+/// Walks forward a minute at a time, skipping whole days the schedule doesn't run on.
+/// Minutes skipped by a DST change are passed over.
+fn next_scheduled_in<Z: TimeZone>(
+    zone: &Z,
+    schedule: &Schedule,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let start = now.with_timezone(zone).naive_local();
+    let mut time = start.date().and_hms(start.hour(), start.minute(), 0) + Duration::minutes(1);
+    let give_up = time + Duration::days(5 * 366);
+    while time < give_up {
+        if !schedule.matches_day(&time) {
+            time = time.date().succ().and_hms(0, 0, 0);
+            continue;
+        }
+        if schedule.matches(&time) {
+            if let Some(candidate) = zone.from_local_datetime(&time).earliest() {
+                let candidate = candidate.with_timezone(&Utc);
+                if candidate > now {
+                    return Some(candidate);
+                }
+            }
+        }
+        time += Duration::minutes(1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        let zone = Zone::from_config(&Some("America/New_York".into())).unwrap();
+        assert_eq!(zone.format_timestamp(1579506505.0), "2020-01-20 02:48 EST");
+        // Just after midnight on February 1st in UTC, still January in New York.
+        assert_eq!(zone.format_month(1580515500.0), "2020-01");
+    }
+
+    #[test]
+    fn test_invalid_timezone() {
+        assert_eq!(
+            Zone::from_config(&Some("Mars/Olympus_Mons".into())).is_err(),
+            true
+        );
+        assert_eq!(Zone::from_config(&None).unwrap(), Zone::Local);
+    }
+
+    #[test]
+    fn test_next_scheduled() {
+        let zone = Zone::Named(parse_timezone("America/New_York").unwrap());
+        let schedule = Schedule::parse("0 3 * * *").unwrap();
+        // 05:00 in New York, so the next 03:00 is tomorrow.
+        let now = Utc.ymd(2020, 1, 15).and_hms(10, 0, 0);
+        assert_eq!(
+            zone.next_scheduled(&schedule, now),
+            Some(Utc.ymd(2020, 1, 16).and_hms(8, 0, 0))
+        );
+        let never = Schedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(zone.next_scheduled(&never, now), None);
+    }
+
+    #[test]
+    fn test_start_of_day() {
+        let zone = Zone::Named(parse_timezone("America/New_York").unwrap());
+        let date = parse_date("2020-01-15").unwrap();
+        assert_eq!(
+            zone.start_of_day(date),
+            Utc.ymd(2020, 1, 15).and_hms(5, 0, 0).timestamp() as f64
+        );
+        // Midnight was skipped when Sao Paulo started DST.
+        let zone = Zone::Named(parse_timezone("America/Sao_Paulo").unwrap());
+        let date = parse_date("2018-11-04").unwrap();
+        assert_eq!(
+            zone.start_of_day(date),
+            Utc.ymd(2018, 11, 4).and_hms(3, 0, 0).timestamp() as f64
+        );
+        assert_eq!(parse_date("15/01/2020").is_err(), true);
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let zone = Zone::Named(parse_timezone("America/New_York").unwrap());
+        let at = parse_time_of_day("03:00").unwrap();
+        // 05:00 in New York, so the next 03:00 is tomorrow.
+        let now = Utc.ymd(2020, 1, 15).and_hms(10, 0, 0);
+        assert_eq!(
+            zone.next_occurrence(at, now),
+            Utc.ymd(2020, 1, 16).and_hms(8, 0, 0)
+        );
+        // 02:00 in New York, so later today.
+        let now = Utc.ymd(2020, 1, 15).and_hms(7, 0, 0);
+        assert_eq!(
+            zone.next_occurrence(at, now),
+            Utc.ymd(2020, 1, 15).and_hms(8, 0, 0)
+        );
+    }
+}
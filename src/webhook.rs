@@ -0,0 +1,157 @@
+use super::config::WebhookFormat;
+use super::report::RunSummary;
+use custom_error::custom_error;
+use serde_json::{json, Value};
+use std::result;
+use std::time::Duration;
+
+custom_error! {pub WebhookError
+    Reqwest{source: reqwest::Error} = "Reqwest error",
+    Rejected{status: String} = "Webhook rejected the run summary ({status})"
+}
+pub type Result<T> = result::Result<T, WebhookError>;
+
+/// Discord embed colors.
+const GREEN: u32 = 0x2e_cc_71;
+const RED: u32 = 0xe7_4c_3c;
+
+/// A webhook that doesn't answer shouldn't hold up the end of a run.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What gets posted at the end of a run, as a chat message for Discord and Slack.
+pub fn payload(summary: &RunSummary, format: Option<WebhookFormat>) -> Value {
+    match format {
+        None => plain(summary),
+        Some(WebhookFormat::Discord) => discord(summary),
+        Some(WebhookFormat::Slack) => slack(summary),
+    }
+}
+
+/// The name and value of each field the chat messages show.
+fn fields(summary: &RunSummary) -> Vec<(&'static str, String)> {
+    vec![
+        ("Matched", summary.matched.to_string()),
+        ("Deleted", summary.deleted.to_string()),
+        ("Failed", summary.failed.to_string()),
+        ("Duration", format!("{:.1}s", summary.elapsed.as_secs_f64())),
+    ]
+}
+
+fn discord(summary: &RunSummary) -> Value {
+    let fields: Vec<Value> = fields(summary)
+        .into_iter()
+        .map(|(name, value)| json!({ "name": name, "value": value, "inline": true }))
+        .collect();
+    json!({
+        "username": "redelete",
+        "embeds": [{
+            "title": summary.subject(),
+            "description": summary.headline(),
+            "color": if summary.failed > 0 { RED } else { GREEN },
+            "fields": fields,
+        }],
+    })
+}
+
+fn slack(summary: &RunSummary) -> Value {
+    let fields: Vec<Value> = fields(summary)
+        .into_iter()
+        .map(|(name, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", name, value) }))
+        .collect();
+    json!({
+        "text": summary.subject(),
+        "blocks": [
+            { "type": "header", "text": { "type": "plain_text", "text": summary.subject() } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": summary.headline() } },
+            { "type": "section", "fields": fields },
+        ],
+    })
+}
+
+/// Kept flat so it's easy to use from home automation and alerting tools.
+fn plain(summary: &RunSummary) -> Value {
+    json!({
+        "account": summary.username,
+        "dry_run": summary.dry_run,
+        "scanned": summary.scanned,
+        "matched": summary.matched,
+        "deleted": summary.deleted,
+        "failed": summary.failed,
+        "remaining": summary.remaining,
+        "duration_secs": summary.elapsed.as_secs_f64(),
+    })
+}
+
+/// POSTs `body` as json to `url`.
+pub async fn post(url: &str, body: &Value) -> Result<()> {
+    let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+    let response = client.post(url).json(body).send().await?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(WebhookError::Rejected {
+            status: response.status().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, server_url, Matcher};
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn test_post_summary() {
+        let summary = RunSummary {
+            username: String::from("WebhookUser"),
+            matched: 3,
+            deleted: 2,
+            failed: 1,
+            elapsed: Duration::from_secs(4),
+            ..Default::default()
+        };
+        let _m = mock("POST", "/hook")
+            .match_body(Matcher::PartialJson(json!({
+                "account": "WebhookUser",
+                "deleted": 2,
+                "failed": 1,
+                "duration_secs": 4.0,
+            })))
+            .with_status(204)
+            .create();
+        let url = format!("{}/hook", server_url());
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { post(&url, &payload(&summary, None)).await });
+        assert_eq!(result.is_ok(), true);
+        _m.assert();
+
+        let _rejected = mock("POST", "/gone").with_status(410).create();
+        let url = format!("{}/gone", server_url());
+        let result = Runtime::new()
+            .unwrap()
+            .block_on(async { post(&url, &payload(&summary, None)).await });
+        assert_eq!(result.is_err(), true);
+    }
+
+    #[test]
+    fn test_chat_payloads() {
+        let summary = RunSummary {
+            username: String::from("WebhookUser"),
+            matched: 3,
+            deleted: 2,
+            failed: 1,
+            ..Default::default()
+        };
+        let discord = payload(&summary, Some(WebhookFormat::Discord));
+        let embed = &discord["embeds"][0];
+        assert_eq!(embed["title"], summary.subject());
+        assert_eq!(embed["color"], RED);
+        assert_eq!(embed["fields"][1]["name"], "Deleted");
+        assert_eq!(embed["fields"][1]["value"], "2");
+        let slack = payload(&summary, Some(WebhookFormat::Slack));
+        assert_eq!(slack["text"], summary.subject());
+        assert_eq!(slack["blocks"][2]["fields"][2]["text"], "*Failed*\n1");
+    }
+}